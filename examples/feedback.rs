@@ -2,138 +2,143 @@
 
 extern crate coreaudio;
 
-use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
-
-use coreaudio::audio_unit::audio_format::LinearPcmFlags;
-use coreaudio::audio_unit::macos_helpers::{audio_unit_from_device_id, get_default_device_id};
-use coreaudio::audio_unit::render_callback::{self, data};
-use coreaudio::audio_unit::{Element, SampleFormat, Scope, StreamFormat};
-use objc2_audio_toolbox::kAudioUnitProperty_StreamFormat;
-
-const SAMPLE_RATE: f64 = 44100.0;
-
-type S = f32;
-const SAMPLE_FORMAT: SampleFormat = SampleFormat::F32;
-// type S = i32; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I32;
-// type S = i16; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I16;
-// type S = i8; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I8;
-
+// The example opens devices through the macOS helpers.
+#[cfg(target_os = "macos")]
 fn main() -> Result<(), coreaudio::Error> {
-    let mut input_audio_unit =
-        audio_unit_from_device_id(get_default_device_id(true).unwrap(), true)?;
-    let mut output_audio_unit =
-        audio_unit_from_device_id(get_default_device_id(false).unwrap(), false)?;
-
-    let format_flag = match SAMPLE_FORMAT {
-        SampleFormat::F32 => LinearPcmFlags::IS_FLOAT,
-        SampleFormat::I32 | SampleFormat::I16 | SampleFormat::I8 => {
-            LinearPcmFlags::IS_SIGNED_INTEGER
-        }
-        _ => {
-            unimplemented!("Other formats are not implemented for this example.");
-        }
-    };
-
-    // Using IS_NON_INTERLEAVED everywhere because data::Interleaved is commented out / not implemented
-    let in_stream_format = StreamFormat {
-        sample_rate: SAMPLE_RATE,
-        sample_format: SAMPLE_FORMAT,
-        flags: format_flag | LinearPcmFlags::IS_PACKED | LinearPcmFlags::IS_NON_INTERLEAVED,
-        // audio_unit.set_input_callback is hardcoded to 1 buffer, and when using non_interleaved
-        // we are forced to 1 channel
-        channels: 1,
-    };
-
-    let out_stream_format = StreamFormat {
-        sample_rate: SAMPLE_RATE,
-        sample_format: SAMPLE_FORMAT,
-        flags: format_flag | LinearPcmFlags::IS_PACKED | LinearPcmFlags::IS_NON_INTERLEAVED,
-        // you can change this to 1
-        channels: 2,
-    };
-
-    println!("input={:#?}", &in_stream_format);
-    println!("output={:#?}", &out_stream_format);
-    println!("input_asbd={:#?}", &in_stream_format.to_asbd());
-    println!("output_asbd={:#?}", &out_stream_format.to_asbd());
-
-    let id = kAudioUnitProperty_StreamFormat;
-    let asbd = in_stream_format.to_asbd();
-    input_audio_unit.set_property(id, Scope::Output, Element::Input, Some(&asbd))?;
-
-    let asbd = out_stream_format.to_asbd();
-    output_audio_unit.set_property(id, Scope::Input, Element::Output, Some(&asbd))?;
-
-    let buffer_left = Arc::new(Mutex::new(VecDeque::<S>::new()));
-    let producer_left = buffer_left.clone();
-    let consumer_left = buffer_left.clone();
-    let buffer_right = Arc::new(Mutex::new(VecDeque::<S>::new()));
-    let producer_right = buffer_right.clone();
-    let consumer_right = buffer_right.clone();
-
-    // seed roughly 1 second of data to create a delay in the feedback loop for easier testing
-    for buffer in vec![buffer_left, buffer_right] {
-        let mut buffer = buffer.lock().unwrap();
-        for _ in 0..(out_stream_format.sample_rate as i32) {
-            buffer.push_back(0 as S);
-        }
-    }
+    macos::main()
+}
 
-    type Args = render_callback::Args<data::NonInterleaved<S>>;
-
-    input_audio_unit.set_input_callback(move |args| {
-        let Args {
-            num_frames,
-            mut data,
-            ..
-        } = args;
-        // Print the number of frames the callback provides.
-        // Included to aid understanding, don't use println and other things
-        // that may block for an unknown amount of time inside the callback
-        // of a real application.
-        println!("input cb {} frames", num_frames);
-        let buffer_left = producer_left.lock().unwrap();
-        let buffer_right = producer_right.lock().unwrap();
-        let mut buffers = vec![buffer_left, buffer_right];
-        for i in 0..num_frames {
-            for (ch, channel) in data.channels_mut().enumerate() {
-                let value: S = channel[i];
-                buffers[ch].push_back(value);
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example is only supported on macOS");
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use coreaudio::audio_unit::audio_format::LinearPcmFlags;
+    use coreaudio::audio_unit::macos_helpers::{audio_unit_from_device_id, get_default_device_id};
+    use coreaudio::audio_unit::render_callback::{self, data};
+    use coreaudio::audio_unit::ring_buffer;
+    use coreaudio::audio_unit::{Element, SampleFormat, Scope, StreamFormat};
+    use objc2_audio_toolbox::kAudioUnitProperty_StreamFormat;
+
+    const SAMPLE_RATE: f64 = 44100.0;
+
+    type S = f32;
+    const SAMPLE_FORMAT: SampleFormat = SampleFormat::F32;
+    // type S = i32; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I32;
+    // type S = i16; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I16;
+    // type S = i8; const SAMPLE_FORMAT: SampleFormat = SampleFormat::I8;
+
+    pub fn main() -> Result<(), coreaudio::Error> {
+        let mut input_audio_unit =
+            audio_unit_from_device_id(get_default_device_id(true).unwrap(), true)?;
+        let mut output_audio_unit =
+            audio_unit_from_device_id(get_default_device_id(false).unwrap(), false)?;
+
+        let format_flag = match SAMPLE_FORMAT {
+            SampleFormat::F32 => LinearPcmFlags::IS_FLOAT,
+            SampleFormat::I32 | SampleFormat::I16 | SampleFormat::I8 => {
+                LinearPcmFlags::IS_SIGNED_INTEGER
             }
-        }
-        Ok(())
-    })?;
-    input_audio_unit.start()?;
-
-    output_audio_unit.set_render_callback(move |args: Args| {
-        let Args {
-            num_frames,
-            mut data,
-            ..
-        } = args;
-        // Print the number of frames the callback requests.
-        // Included to aid understanding, don't use println and other things
-        // that may block for an unknown amount of time inside the callback
-        // of a real application.
-        println!("output cb {} frames", num_frames);
-        let buffer_left = consumer_left.lock().unwrap();
-        let buffer_right = consumer_right.lock().unwrap();
-        let mut buffers = vec![buffer_left, buffer_right];
-        for i in 0..num_frames {
-            // Default other channels to copy value from first channel as a fallback
-            let zero: S = 0 as S;
-            let f: S = *buffers[0].front().unwrap_or(&zero);
-            for (ch, channel) in data.channels_mut().enumerate() {
-                let sample: S = buffers[ch].pop_front().unwrap_or(f);
-                channel[i] = sample;
+            _ => {
+                unimplemented!("Other formats are not implemented for this example.");
             }
-        }
-        Ok(())
-    })?;
-    output_audio_unit.start()?;
+        };
+
+        // Using IS_NON_INTERLEAVED everywhere because data::Interleaved is commented out / not implemented
+        let in_stream_format = StreamFormat {
+            sample_rate: SAMPLE_RATE,
+            sample_format: SAMPLE_FORMAT,
+            flags: format_flag | LinearPcmFlags::IS_PACKED | LinearPcmFlags::IS_NON_INTERLEAVED,
+            // you can change this to 1
+            channels: 2,
+        };
+
+        let out_stream_format = StreamFormat {
+            sample_rate: SAMPLE_RATE,
+            sample_format: SAMPLE_FORMAT,
+            flags: format_flag | LinearPcmFlags::IS_PACKED | LinearPcmFlags::IS_NON_INTERLEAVED,
+            channels: 2,
+        };
+
+        println!("input={:#?}", &in_stream_format);
+        println!("output={:#?}", &out_stream_format);
+        println!("input_asbd={:#?}", &in_stream_format.to_asbd());
+        println!("output_asbd={:#?}", &out_stream_format.to_asbd());
+
+        let id = kAudioUnitProperty_StreamFormat;
+        let asbd = in_stream_format.to_asbd();
+        input_audio_unit.set_property(id, Scope::Output, Element::Input, Some(&asbd))?;
+
+        let asbd = out_stream_format.to_asbd();
+        output_audio_unit.set_property(id, Scope::Input, Element::Output, Some(&asbd))?;
+
+        // A mono input is copied to every output channel, otherwise the channel counts must match.
+        assert!(
+            in_stream_format.channels == 1
+                || in_stream_format.channels == out_stream_format.channels,
+            "the input must be mono or have as many channels as the output"
+        );
+
+        // Room for two seconds of audio, which the callbacks pass through without locking.
+        let channels = in_stream_format.channels as usize;
+        let sample_rate = out_stream_format.sample_rate as usize;
+        let (mut producer, mut consumer) =
+            ring_buffer::non_interleaved::<S>(channels, 2 * sample_rate);
+
+        // seed roughly 1 second of data to create a delay in the feedback loop for easier testing
+        producer.push_interleaved(&vec![0 as S; sample_rate * channels]);
+
+        type Args = render_callback::Args<data::NonInterleaved<S>>;
+
+        input_audio_unit.set_input_callback(move |args| {
+            let Args {
+                num_frames, data, ..
+            } = args;
+            // Print the number of frames the callback provides.
+            // Included to aid understanding, don't use println and other things
+            // that may block for an unknown amount of time inside the callback
+            // of a real application.
+            println!("input cb {} frames", num_frames);
+            producer.push_data(&data);
+            Ok(())
+        })?;
+        input_audio_unit.start()?;
+
+        output_audio_unit.set_render_callback(move |args: Args| {
+            let Args {
+                num_frames,
+                mut data,
+                ..
+            } = args;
+            // Print the number of frames the callback requests.
+            // Included to aid understanding, don't use println and other things
+            // that may block for an unknown amount of time inside the callback
+            // of a real application.
+            println!("output cb {} frames", num_frames);
+            // Play silence for any frames the input has not provided yet.
+            if consumer.channels() == data.num_channels() {
+                let popped = consumer.pop_data(&mut data);
+                for channel in data.channels_mut() {
+                    channel[popped..].fill(0 as S);
+                }
+            } else {
+                // Fall back to copying the mono input to every output channel.
+                let mut channels = data.channels_mut();
+                let first = channels.next().unwrap();
+                let popped = consumer.pop_channels(Some(&mut *first));
+                first[popped..].fill(0 as S);
+                for channel in channels {
+                    channel.copy_from_slice(first);
+                }
+            }
+            Ok(())
+        })?;
+        output_audio_unit.start()?;
 
-    std::thread::sleep(std::time::Duration::from_millis(100000));
+        std::thread::sleep(std::time::Duration::from_millis(100000));
 
-    Ok(())
+        Ok(())
+    }
 }
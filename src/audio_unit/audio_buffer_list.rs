@@ -0,0 +1,234 @@
+//! An owned, variable-length `AudioBufferList`.
+//!
+//! Core Audio declares `AudioBufferList` with a single `AudioBuffer`, but the list is really of
+//! variable length, with `mNumberBuffers` buffers laid out contiguously. **AudioBufferListBuf**
+//! allocates such a list along with the data for each of its buffers and frees both on drop.
+
+use crate::sys::{AudioBuffer, AudioBufferList};
+
+use super::audio_format::LinearPcmFlags;
+use super::StreamFormat;
+use std::alloc::{self, Layout};
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr::NonNull;
+use std::slice;
+
+/// An owned `AudioBufferList` holding a fixed number of buffers of equal byte size.
+///
+/// The pointer returned by `as_mut_ptr` may be handed to functions such as `AudioUnitRender`.
+/// Resizing only reallocates the data of the buffers when the new size exceeds their capacity,
+/// so a list sized for the largest expected frame count never allocates again.
+pub struct AudioBufferListBuf {
+    list: NonNull<AudioBufferList>,
+    layout: Layout,
+    data: Vec<Vec<u8>>,
+}
+
+// The list only points into memory owned by the `AudioBufferListBuf`.
+unsafe impl Send for AudioBufferListBuf {}
+unsafe impl Sync for AudioBufferListBuf {}
+
+impl AudioBufferListBuf {
+    /// Allocate a list of `n_buffers` zeroed buffers of `byte_size` bytes, each holding
+    /// `channels_per_buffer` interleaved channels.
+    pub fn new(n_buffers: u32, channels_per_buffer: u32, byte_size: u32) -> Self {
+        let size = mem::size_of::<AudioBufferList>()
+            + (n_buffers.max(1) as usize - 1) * mem::size_of::<AudioBuffer>();
+        let layout = Layout::from_size_align(size, mem::align_of::<AudioBufferList>())
+            .expect("invalid AudioBufferList layout");
+        let list = unsafe { alloc::alloc_zeroed(layout) as *mut AudioBufferList };
+        let list = NonNull::new(list).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        let mut data: Vec<Vec<u8>> = (0..n_buffers)
+            .map(|_| vec![0u8; byte_size as usize])
+            .collect();
+        unsafe {
+            (*list.as_ptr()).mNumberBuffers = n_buffers;
+            let buffers = (*list.as_ptr()).mBuffers.as_mut_ptr();
+            for (i, data) in data.iter_mut().enumerate() {
+                buffers.add(i).write(AudioBuffer {
+                    mNumberChannels: channels_per_buffer,
+                    mDataByteSize: byte_size,
+                    mData: data.as_mut_ptr() as *mut c_void,
+                });
+            }
+        }
+        AudioBufferListBuf { list, layout, data }
+    }
+
+    /// Allocate a list able to hold `frames` frames of audio in the given **StreamFormat**.
+    ///
+    /// Non-interleaved formats get one buffer per channel, interleaved formats a single buffer
+    /// holding all channels.
+    pub fn for_format(format: &StreamFormat, frames: u32) -> Self {
+        let (n_buffers, channels_per_buffer) =
+            if format.flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED) {
+                (format.channels, 1)
+            } else {
+                (1, format.channels)
+            };
+        let sample_bytes = format.sample_format.size_in_bytes_with_flags(format.flags) as u32;
+        let byte_size = frames * sample_bytes * channels_per_buffer;
+        AudioBufferListBuf::new(n_buffers, channels_per_buffer, byte_size)
+    }
+
+    /// The number of buffers in the list.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the list holds no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The number of bytes each buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.data.iter().map(Vec::capacity).min().unwrap_or(0)
+    }
+
+    /// Set the byte size of every buffer.
+    ///
+    /// Buffers are only reallocated if `byte_size` exceeds their capacity. Bytes beyond the
+    /// previous size are zeroed.
+    pub fn resize(&mut self, byte_size: u32) {
+        let buffers = unsafe { audio_buffers_mut(self.list, self.data.len()) };
+        for (data, buffer) in self.data.iter_mut().zip(buffers) {
+            data.resize(byte_size as usize, 0);
+            buffer.mData = data.as_mut_ptr() as *mut c_void;
+            buffer.mDataByteSize = byte_size;
+        }
+    }
+
+    /// Ensure every buffer can hold at least `byte_size` bytes without reallocating.
+    pub fn reserve(&mut self, byte_size: u32) {
+        let buffers = unsafe { audio_buffers_mut(self.list, self.data.len()) };
+        for (data, buffer) in self.data.iter_mut().zip(buffers) {
+            data.reserve((byte_size as usize).saturating_sub(data.len()));
+            buffer.mData = data.as_mut_ptr() as *mut c_void;
+        }
+    }
+
+    /// The data of the buffer at `index`, limited to its current `mDataByteSize`.
+    pub fn buffer(&self, index: usize) -> Option<&[u8]> {
+        let size = self.audio_buffers().get(index)?.mDataByteSize as usize;
+        self.data
+            .get(index)
+            .map(|data| &data[..size.min(data.len())])
+    }
+
+    /// The mutable data of the buffer at `index`, limited to its current `mDataByteSize`.
+    pub fn buffer_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        let size = self.audio_buffers().get(index)?.mDataByteSize as usize;
+        self.data.get_mut(index).map(|data| {
+            let len = size.min(data.len());
+            &mut data[..len]
+        })
+    }
+
+    /// The `AudioBuffer`s of the list.
+    pub fn audio_buffers(&self) -> &[AudioBuffer] {
+        unsafe {
+            let list = self.list.as_ptr();
+            slice::from_raw_parts((*list).mBuffers.as_ptr(), self.data.len())
+        }
+    }
+
+    /// A pointer to the list for passing to Core Audio.
+    pub fn as_ptr(&self) -> *const AudioBufferList {
+        self.list.as_ptr()
+    }
+
+    /// A mutable pointer to the list for passing to Core Audio.
+    ///
+    /// Core Audio may change the `mDataByteSize` of each buffer, but must not replace `mData`.
+    pub fn as_mut_ptr(&mut self) -> *mut AudioBufferList {
+        self.list.as_ptr()
+    }
+
+    /// The list as a `NonNull` pointer, as taken by `AudioUnitRender`.
+    pub fn as_non_null(&mut self) -> NonNull<AudioBufferList> {
+        self.list
+    }
+}
+
+/// The `AudioBuffer`s of `list`, which must hold `len` of them.
+///
+/// The returned slice does not borrow the **AudioBufferListBuf**, so that the buffer data may be
+/// modified alongside it.
+unsafe fn audio_buffers_mut<'a>(
+    list: NonNull<AudioBufferList>,
+    len: usize,
+) -> &'a mut [AudioBuffer] {
+    slice::from_raw_parts_mut((*list.as_ptr()).mBuffers.as_mut_ptr(), len)
+}
+
+impl Drop for AudioBufferListBuf {
+    fn drop(&mut self) {
+        // The buffer data is freed when `self.data` is dropped.
+        unsafe { alloc::dealloc(self.list.as_ptr() as *mut u8, self.layout) };
+    }
+}
+
+impl ::std::fmt::Debug for AudioBufferListBuf {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("AudioBufferListBuf")
+            .field("buffers", &self.audio_buffers())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::SampleFormat;
+
+    #[test]
+    fn test_for_format() {
+        let format = StreamFormat {
+            sample_rate: 48_000.0,
+            sample_format: SampleFormat::F32,
+            flags: LinearPcmFlags::IS_FLOAT
+                | LinearPcmFlags::IS_PACKED
+                | LinearPcmFlags::IS_NON_INTERLEAVED,
+            channels: 8,
+        };
+        let list = AudioBufferListBuf::for_format(&format, 256);
+        assert_eq!(list.len(), 8);
+        unsafe { assert_eq!((*list.as_ptr()).mNumberBuffers, 8) };
+        for buffer in list.audio_buffers() {
+            assert_eq!(buffer.mNumberChannels, 1);
+            assert_eq!(buffer.mDataByteSize, 1024);
+        }
+
+        let format = StreamFormat {
+            flags: LinearPcmFlags::IS_FLOAT | LinearPcmFlags::IS_PACKED,
+            ..format
+        };
+        let list = AudioBufferListBuf::for_format(&format, 256);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.audio_buffers()[0].mNumberChannels, 8);
+        assert_eq!(list.buffer(0).unwrap().len(), 8192);
+    }
+
+    #[test]
+    fn test_resize_within_capacity() {
+        let mut list = AudioBufferListBuf::new(2, 1, 64);
+        list.buffer_mut(1).unwrap().fill(1);
+        let data = list.audio_buffers()[1].mData;
+
+        list.resize(16);
+        assert_eq!(list.audio_buffers()[1].mData, data);
+        assert_eq!(list.buffer(1).unwrap(), &[1; 16][..]);
+
+        list.resize(64);
+        assert_eq!(list.audio_buffers()[1].mData, data);
+        assert!(list.buffer(1).unwrap()[16..].iter().all(|&b| b == 0));
+
+        list.resize(128);
+        assert!(list.capacity() >= 128);
+        for buffer in list.audio_buffers() {
+            assert_eq!(buffer.mDataByteSize, 128);
+        }
+    }
+}
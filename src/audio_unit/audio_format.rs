@@ -3,7 +3,7 @@
 //! See the Core Audio Data Types Reference
 //! [here](https://developer.apple.com/library/mac/documentation/MusicAudio/Reference/CoreAudioDataTypesRef/#//apple_ref/doc/constant_group/Audio_Data_Format_Identifiers) for more info.
 
-use objc2_core_audio_types::{
+use crate::sys::{
     kAppleLosslessFormatFlag_16BitSourceData, kAppleLosslessFormatFlag_20BitSourceData,
     kAppleLosslessFormatFlag_24BitSourceData, kAppleLosslessFormatFlag_32BitSourceData,
     kAudioFormat60958AC3, kAudioFormatAC3, kAudioFormatAES3, kAudioFormatALaw, kAudioFormatAMR,
@@ -395,8 +395,8 @@ bitflags! {
         /// This constant indicates the bit position (counting from the right) of the bitfield in
         /// `mFormatFlags` field.
         ///
-        /// TODO: Review whether or not this flag indicates that we need to treat LinearPCM format
-        /// uniquely in some way.
+        /// Use [**LinearPcmFlags::fraction_bits**](./struct.LinearPcmFlags.html#method.fraction_bits)
+        /// to read the number of fractional bits.
         ///
         /// **Available** in OS X v10.6 and later.
         const FLAGS_SAMPLE_FRACTION_SHIFT = kLinearPCMFormatFlagsSampleFractionShift;
@@ -409,6 +409,25 @@ bitflags! {
     }
 }
 
+impl LinearPcmFlags {
+    /// The number of bits used to represent the fractional portion of fixed-point samples.
+    ///
+    /// This is zero for integer and floating point formats.
+    pub fn fraction_bits(&self) -> u32 {
+        (self.bits() & kLinearPCMFormatFlagsSampleFractionMask)
+            >> kLinearPCMFormatFlagsSampleFractionShift
+    }
+
+    /// Return these flags with the number of fractional bits replaced by `fraction_bits`.
+    ///
+    /// Only the low 6 bits of `fraction_bits` are representable.
+    pub fn with_fraction_bits(self, fraction_bits: u32) -> Self {
+        let mask = kLinearPCMFormatFlagsSampleFractionMask;
+        let fraction = (fraction_bits << kLinearPCMFormatFlagsSampleFractionShift) & mask;
+        LinearPcmFlags::from_bits_truncate((self.bits() & !mask) | fraction)
+    }
+}
+
 bitflags! {
     /// Flags set for Apple Lossless data.
     ///
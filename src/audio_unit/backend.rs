@@ -0,0 +1,415 @@
+//! The host-side operations that an [AudioUnit](../struct.AudioUnit.html) is built upon.
+//!
+//! Every call the **AudioUnit** makes into the host (property access, lifecycle and rendering)
+//! goes through the **Backend** trait. The default implementation forwards to the Audio Toolbox
+//! framework, while [**simulated**](../simulated/index.html) provides a pure-Rust host that can
+//! drive render and input callbacks without any audio hardware.
+
+#[cfg(target_vendor = "apple")]
+use objc2_audio_toolbox::{
+    AudioComponentInstanceDispose, AudioOutputUnitStart, AudioOutputUnitStop,
+    AudioUnitAddPropertyListener, AudioUnitAddRenderNotify, AudioUnitGetParameter,
+    AudioUnitGetProperty, AudioUnitGetPropertyInfo, AudioUnitInitialize,
+    AudioUnitRemovePropertyListenerWithUserData, AudioUnitRemoveRenderNotify, AudioUnitRender,
+    AudioUnitSetParameter, AudioUnitSetProperty, AudioUnitUninitialize,
+};
+
+use super::parameter::RawParameterEvent;
+use super::{Element, Scope};
+use crate::error::Error;
+#[cfg(target_vendor = "apple")]
+use crate::sys::AudioUnit as InnerAudioUnit;
+use crate::sys::{
+    AURenderCallback, AudioBufferList, AudioTimeStamp, AudioUnitPropertyListenerProc,
+    AudioUnitRenderActionFlags,
+};
+use crate::OSStatus;
+use std::any::Any;
+use std::os::raw::{c_uint, c_void};
+use std::ptr::NonNull;
+#[cfg(target_vendor = "apple")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(target_vendor = "apple")]
+extern "C-unwind" {
+    // Not currently covered by the objc2 bindings.
+    fn AudioUnitScheduleParameters(
+        unit: InnerAudioUnit,
+        events: *const RawParameterEvent,
+        num_events: u32,
+    ) -> OSStatus;
+}
+
+/// The operations an **AudioUnit** requires from the host that owns the underlying unit.
+///
+/// The methods mirror their Audio Toolbox counterparts and report failure through the returned
+/// `OSStatus`, so that the same error handling applies regardless of the host.
+pub(crate) trait Backend: Send + Sync {
+    /// See `AudioUnitInitialize`.
+    fn initialize(&self) -> OSStatus;
+    /// See `AudioUnitUninitialize`.
+    fn uninitialize(&self) -> OSStatus;
+    /// See `AudioOutputUnitStart`.
+    fn start(&self) -> OSStatus;
+    /// See `AudioOutputUnitStop`.
+    fn stop(&self) -> OSStatus;
+    /// See `AudioUnitSetProperty`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must either be null or point to `size` readable bytes.
+    unsafe fn set_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: *const c_void,
+        size: u32,
+    ) -> OSStatus;
+    /// See `AudioUnitGetProperty`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to at least `*size` writable bytes.
+    unsafe fn get_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: NonNull<c_void>,
+        size: NonNull<u32>,
+    ) -> OSStatus;
+    /// See `AudioUnitGetPropertyInfo`.
+    fn get_property_info(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        size: &mut u32,
+        writable: &mut bool,
+    ) -> OSStatus;
+    /// See `AudioUnitAddPropertyListener`.
+    ///
+    /// # Safety
+    ///
+    /// `user_data` must remain valid for `proc_` until the listener is removed.
+    unsafe fn add_property_listener(
+        &self,
+        id: u32,
+        proc_: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus;
+    /// See `AudioUnitRemovePropertyListenerWithUserData`.
+    ///
+    /// A listener already running on another thread may not have returned when this returns, so
+    /// `user_data` must stay valid until it is passed to **retire**.
+    fn remove_property_listener(
+        &self,
+        id: u32,
+        proc_: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus;
+    /// See `AudioUnitGetParameter`.
+    fn get_parameter(&self, id: u32, scope: c_uint, elem: c_uint, value: &mut f32) -> OSStatus;
+    /// See `AudioUnitSetParameter`.
+    fn set_parameter(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        value: f32,
+        buffer_offset: u32,
+    ) -> OSStatus;
+    /// See `AudioUnitScheduleParameters`.
+    fn schedule_parameters(&self, events: &[RawParameterEvent]) -> OSStatus;
+    /// See `AudioUnitAddRenderNotify`.
+    ///
+    /// # Safety
+    ///
+    /// `ref_con` must remain valid for `proc_` until the notification is removed.
+    unsafe fn add_render_notify(&self, proc_: AURenderCallback, ref_con: *mut c_void) -> OSStatus;
+    /// See `AudioUnitRemoveRenderNotify`.
+    ///
+    /// A notification already running on the render thread may not have returned when this
+    /// returns, so `ref_con` must stay valid until the unit has stopped rendering.
+    fn remove_render_notify(&self, proc_: AURenderCallback, ref_con: *mut c_void) -> OSStatus;
+    /// Keep `value` alive for as long as the host may still be using it.
+    ///
+    /// Used for the data of a listener that was removed while it may still have been running.
+    fn retire(&self, value: Box<dyn Any + Send>);
+    /// See `AudioUnitRender`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers must be valid for the duration of the call and `data` must describe buffers
+    /// large enough to hold `frames` frames.
+    unsafe fn render(
+        &self,
+        flags: *mut AudioUnitRenderActionFlags,
+        time_stamp: NonNull<AudioTimeStamp>,
+        bus: u32,
+        frames: u32,
+        data: NonNull<AudioBufferList>,
+    ) -> OSStatus;
+}
+
+/// A **Backend** that forwards every call to an Audio Toolbox audio unit instance.
+///
+/// The instance is disposed of when the backend is dropped, before anything it retired is freed.
+#[cfg(target_vendor = "apple")]
+pub(crate) struct CoreAudioBackend {
+    instance: InnerAudioUnit,
+    retired: Mutex<Vec<Box<dyn Any + Send>>>,
+}
+
+#[cfg(target_vendor = "apple")]
+impl CoreAudioBackend {
+    /// Take ownership of the given audio unit instance.
+    pub(crate) fn new(instance: InnerAudioUnit) -> Self {
+        CoreAudioBackend {
+            instance,
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+// The Audio Toolbox API may be called from any thread for a given instance.
+unsafe impl Send for CoreAudioBackend {}
+#[cfg(target_vendor = "apple")]
+unsafe impl Sync for CoreAudioBackend {}
+
+#[cfg(target_vendor = "apple")]
+impl Backend for CoreAudioBackend {
+    fn initialize(&self) -> OSStatus {
+        unsafe { AudioUnitInitialize(self.instance) }
+    }
+
+    fn uninitialize(&self) -> OSStatus {
+        unsafe { AudioUnitUninitialize(self.instance) }
+    }
+
+    fn start(&self) -> OSStatus {
+        unsafe { AudioOutputUnitStart(self.instance) }
+    }
+
+    fn stop(&self) -> OSStatus {
+        unsafe { AudioOutputUnitStop(self.instance) }
+    }
+
+    unsafe fn set_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: *const c_void,
+        size: u32,
+    ) -> OSStatus {
+        AudioUnitSetProperty(self.instance, id, scope, elem, data, size)
+    }
+
+    unsafe fn get_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: NonNull<c_void>,
+        size: NonNull<u32>,
+    ) -> OSStatus {
+        AudioUnitGetProperty(self.instance, id, scope, elem, data, size)
+    }
+
+    fn get_property_info(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        size: &mut u32,
+        writable: &mut bool,
+    ) -> OSStatus {
+        let mut is_writable = 0;
+        let status = unsafe {
+            AudioUnitGetPropertyInfo(self.instance, id, scope, elem, size, &mut is_writable)
+        };
+        *writable = is_writable != 0;
+        status
+    }
+
+    unsafe fn add_property_listener(
+        &self,
+        id: u32,
+        proc_: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus {
+        AudioUnitAddPropertyListener(self.instance, id, proc_, user_data)
+    }
+
+    fn remove_property_listener(
+        &self,
+        id: u32,
+        proc_: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus {
+        unsafe { AudioUnitRemovePropertyListenerWithUserData(self.instance, id, proc_, user_data) }
+    }
+
+    fn get_parameter(&self, id: u32, scope: c_uint, elem: c_uint, value: &mut f32) -> OSStatus {
+        unsafe { AudioUnitGetParameter(self.instance, id, scope, elem, NonNull::from(value)) }
+    }
+
+    fn set_parameter(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        value: f32,
+        buffer_offset: u32,
+    ) -> OSStatus {
+        unsafe { AudioUnitSetParameter(self.instance, id, scope, elem, value, buffer_offset) }
+    }
+
+    fn schedule_parameters(&self, events: &[RawParameterEvent]) -> OSStatus {
+        unsafe { AudioUnitScheduleParameters(self.instance, events.as_ptr(), events.len() as u32) }
+    }
+
+    unsafe fn add_render_notify(&self, proc_: AURenderCallback, ref_con: *mut c_void) -> OSStatus {
+        AudioUnitAddRenderNotify(self.instance, proc_, ref_con)
+    }
+
+    fn remove_render_notify(&self, proc_: AURenderCallback, ref_con: *mut c_void) -> OSStatus {
+        unsafe { AudioUnitRemoveRenderNotify(self.instance, proc_, ref_con) }
+    }
+
+    fn retire(&self, value: Box<dyn Any + Send>) {
+        // Freed once the instance has been disposed of, when nothing can call into it any more.
+        let mut retired = self.retired.lock().unwrap_or_else(PoisonError::into_inner);
+        retired.push(value);
+    }
+
+    unsafe fn render(
+        &self,
+        flags: *mut AudioUnitRenderActionFlags,
+        time_stamp: NonNull<AudioTimeStamp>,
+        bus: u32,
+        frames: u32,
+        data: NonNull<AudioBufferList>,
+    ) -> OSStatus {
+        AudioUnitRender(self.instance, flags, time_stamp, bus, frames, data)
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl Drop for CoreAudioBackend {
+    fn drop(&mut self) {
+        // We don't want to panic in `drop`, so we'll ignore returned errors.
+        unsafe {
+            Error::from_os_status(AudioComponentInstanceDispose(self.instance)).ok();
+        }
+    }
+}
+
+/// Sets the value of a property through the given **Backend**.
+///
+/// Passing `None` clears the property.
+pub(crate) fn set_property<T>(
+    backend: &dyn Backend,
+    id: u32,
+    scope: Scope,
+    elem: Element,
+    maybe_data: Option<&T>,
+) -> Result<(), Error> {
+    let (data_ptr, size) = maybe_data
+        .map(|data| {
+            let ptr = data as *const _ as *const c_void;
+            let size = ::std::mem::size_of::<T>() as u32;
+            (ptr, size)
+        })
+        .unwrap_or_else(|| (::std::ptr::null(), 0));
+    let scope = scope as c_uint;
+    let elem = elem.0 as c_uint;
+    Error::from_os_status(unsafe { backend.set_property(id, scope, elem, data_ptr, size) })
+}
+
+/// Gets the value of a property through the given **Backend**.
+///
+/// # Safety
+///
+/// `T` must be the type the host uses to represent the property, as the returned bytes are
+/// reinterpreted as a `T`.
+pub(crate) unsafe fn get_property<T>(
+    backend: &dyn Backend,
+    id: u32,
+    scope: Scope,
+    elem: Element,
+) -> Result<T, Error> {
+    let scope = scope as c_uint;
+    let elem = elem.0 as c_uint;
+    let mut size = ::std::mem::size_of::<T>() as u32;
+    let mut data_uninit = ::std::mem::MaybeUninit::<T>::uninit();
+    let data_ptr = NonNull::from(&mut data_uninit).cast::<c_void>();
+    let size_ptr = NonNull::from(&mut size);
+    Error::from_os_status(backend.get_property(id, scope, elem, data_ptr, size_ptr))?;
+    Ok(data_uninit.assume_init())
+}
+
+/// Gets the size in bytes of a property's value and whether it is writable through the given
+/// **Backend**.
+pub(crate) fn get_property_info(
+    backend: &dyn Backend,
+    id: u32,
+    scope: Scope,
+    elem: Element,
+) -> Result<(u32, bool), Error> {
+    let mut size = 0;
+    let mut writable = false;
+    let status = backend.get_property_info(
+        id,
+        scope as c_uint,
+        elem.0 as c_uint,
+        &mut size,
+        &mut writable,
+    );
+    Error::from_os_status(status)?;
+    Ok((size, writable))
+}
+
+/// Gets the value of a variable-length property as a `Vec<T>` through the given **Backend**.
+///
+/// Returns `Error::PropertySizeMismatch` if the size of the value is not a multiple of the size
+/// of `T`.
+///
+/// # Safety
+///
+/// `T` must be the type the host uses to represent the elements of the property, as the returned
+/// bytes are reinterpreted as `T`s.
+pub(crate) unsafe fn get_property_vec<T: Copy>(
+    backend: &dyn Backend,
+    id: u32,
+    scope: Scope,
+    elem: Element,
+) -> Result<Vec<T>, Error> {
+    let element_size = ::std::mem::size_of::<T>() as u32;
+    assert!(
+        element_size > 0,
+        "zero-sized property elements are not supported"
+    );
+    let check_size = |size: u32| {
+        if size % element_size != 0 {
+            return Err(Error::PropertySizeMismatch { size, element_size });
+        }
+        Ok(())
+    };
+    let (mut size, _) = get_property_info(backend, id, scope, elem)?;
+    check_size(size)?;
+    let mut data: Vec<T> = Vec::with_capacity((size / element_size) as usize);
+    if size > 0 {
+        let data_ptr = NonNull::new_unchecked(data.as_mut_ptr()).cast::<c_void>();
+        let size_ptr = NonNull::from(&mut size);
+        let status =
+            backend.get_property(id, scope as c_uint, elem.0 as c_uint, data_ptr, size_ptr);
+        Error::from_os_status(status)?;
+        check_size(size)?;
+        data.set_len((size / element_size) as usize);
+    }
+    Ok(data)
+}
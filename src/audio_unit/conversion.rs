@@ -0,0 +1,741 @@
+//! Conversion of audio data between sample formats.
+//!
+//! All conversions go through a normalized `f64` representation in which full scale integer
+//! samples map to `[-1.0, 1.0)`:
+//!
+//! - Signed integers of `N` bits are divided by `2^(N - 1)`, so `i16::MIN` becomes `-1.0` and
+//!   `i16::MAX` becomes `32767.0 / 32768.0`.
+//! - Unsigned integers are offset by `2^(N - 1)` before scaling, so `128u8` becomes `0.0`.
+//! - Fixed-point samples are divided by `2^FRAC` and are not limited to `[-1.0, 1.0)`.
+//! - Floats are used as is.
+//!
+//! When converting to an integer or fixed-point format, values are rounded to the nearest
+//! representable value (halfway cases away from zero) and clipped to the range of the format.
+//! When the destination has a coarser resolution than the source, TPDF dither with a peak
+//! amplitude of one quantization step of the destination may optionally be added before rounding.
+//!
+//! None of the functions in this module allocate, so they are safe to call from within render
+//! and input callbacks.
+
+use super::audio_format::LinearPcmFlags;
+use super::render_callback::data::NonInterleaved;
+use super::sample_format::{
+    BigEndian, FixedPoint, I24AlignedHigh, I24AlignedLow, LittleEndian, Sample, SwapBytes, I24,
+};
+use super::{SampleFormat, StreamFormat};
+use std::mem;
+use std::ptr;
+
+/// Sample types that can be converted to and from the normalized `f64` representation.
+pub trait ConvertSample: Sample + Copy {
+    /// Convert the sample to the normalized float representation.
+    fn to_f64(self) -> f64;
+    /// Convert from the normalized float representation, rounding to the nearest representable
+    /// value and clipping to the range of the type.
+    fn from_f64(value: f64) -> Self;
+    /// The size of one quantization step in the normalized representation, or `None` for
+    /// floating point types.
+    fn quantization_step() -> Option<f64>;
+}
+
+impl ConvertSample for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn quantization_step() -> Option<f64> {
+        None
+    }
+}
+
+impl ConvertSample for f32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+    fn quantization_step() -> Option<f64> {
+        None
+    }
+}
+
+/// Implement `ConvertSample` for signed integer types.
+///
+/// Float to integer `as` casts saturate, which takes care of clipping.
+macro_rules! impl_convert_signed {
+    ($($T:ident $bits:expr),* $(,)*) => {
+        $(
+            impl ConvertSample for $T {
+                fn to_f64(self) -> f64 {
+                    self as f64 / SCALE_TO_BITS[$bits]
+                }
+                fn from_f64(value: f64) -> Self {
+                    (value * SCALE_TO_BITS[$bits]).round() as $T
+                }
+                fn quantization_step() -> Option<f64> {
+                    Some(1.0 / SCALE_TO_BITS[$bits])
+                }
+            }
+        )*
+    }
+}
+
+/// Implement `ConvertSample` for unsigned integer types.
+macro_rules! impl_convert_unsigned {
+    ($($T:ident $bits:expr),* $(,)*) => {
+        $(
+            impl ConvertSample for $T {
+                fn to_f64(self) -> f64 {
+                    (self as f64 - SCALE_TO_BITS[$bits]) / SCALE_TO_BITS[$bits]
+                }
+                fn from_f64(value: f64) -> Self {
+                    ((value * SCALE_TO_BITS[$bits]).round() + SCALE_TO_BITS[$bits]) as $T
+                }
+                fn quantization_step() -> Option<f64> {
+                    Some(1.0 / SCALE_TO_BITS[$bits])
+                }
+            }
+        )*
+    }
+}
+
+/// `SCALE_TO_BITS[n]` is `2^(n - 1)`, the magnitude of the most negative `n` bit integer.
+const SCALE_TO_BITS: [f64; 33] = {
+    let mut table = [0.0; 33];
+    let mut n = 1;
+    while n < 33 {
+        table[n] = (1u64 << (n - 1)) as f64;
+        n += 1;
+    }
+    table
+};
+
+impl_convert_signed!(i32 32, i16 16, i8 8);
+impl_convert_unsigned!(u16 16, u8 8);
+
+/// Implement `ConvertSample` for the 24-bit types, which clamp on `new`.
+macro_rules! impl_convert_i24 {
+    ($($T:ident),* $(,)*) => {
+        $(
+            impl ConvertSample for $T {
+                fn to_f64(self) -> f64 {
+                    self.get() as f64 / SCALE_TO_BITS[24]
+                }
+                fn from_f64(value: f64) -> Self {
+                    $T::new((value * SCALE_TO_BITS[24]).round() as i32)
+                }
+                fn quantization_step() -> Option<f64> {
+                    Some(1.0 / SCALE_TO_BITS[24])
+                }
+            }
+        )*
+    }
+}
+
+impl_convert_i24!(I24, I24AlignedHigh, I24AlignedLow);
+
+impl<const FRAC: u32> ConvertSample for FixedPoint<FRAC> {
+    fn to_f64(self) -> f64 {
+        self.to_bits() as f64 / (1u64 << FRAC) as f64
+    }
+    fn from_f64(value: f64) -> Self {
+        FixedPoint::from_bits((value * (1u64 << FRAC) as f64).round() as i32)
+    }
+    fn quantization_step() -> Option<f64> {
+        Some(1.0 / (1u64 << FRAC) as f64)
+    }
+}
+
+impl<S: ConvertSample + SwapBytes> ConvertSample for BigEndian<S> {
+    fn to_f64(self) -> f64 {
+        self.get().to_f64()
+    }
+    fn from_f64(value: f64) -> Self {
+        BigEndian::new(S::from_f64(value))
+    }
+    fn quantization_step() -> Option<f64> {
+        S::quantization_step()
+    }
+}
+
+impl<S: ConvertSample + SwapBytes> ConvertSample for LittleEndian<S> {
+    fn to_f64(self) -> f64 {
+        self.get().to_f64()
+    }
+    fn from_f64(value: f64) -> Self {
+        LittleEndian::new(S::from_f64(value))
+    }
+    fn quantization_step() -> Option<f64> {
+        S::quantization_step()
+    }
+}
+
+/// A source of triangular probability density function (TPDF) dither.
+///
+/// This uses a small xorshift generator, so it is cheap to call from the audio thread. The
+/// generator is deterministic for a given seed.
+#[derive(Copy, Clone, Debug)]
+pub struct Dither {
+    state: u32,
+}
+
+impl Dither {
+    /// Create a dither source from the given seed.
+    pub fn new(seed: u32) -> Self {
+        // The xorshift state must never be zero.
+        Dither {
+            state: if seed == 0 { 0x9e37_79b9 } else { seed },
+        }
+    }
+
+    fn next_unit(&mut self) -> f64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x as f64 / (u32::MAX as f64 + 1.0)
+    }
+
+    /// A random value in `(-1.0, 1.0)` with a triangular distribution centered on zero.
+    pub fn tpdf(&mut self) -> f64 {
+        self.next_unit() - self.next_unit()
+    }
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Dither::new(0)
+    }
+}
+
+/// The dither to apply when converting between quantization steps, if any.
+fn dither_amplitude(
+    dither: Option<&mut Dither>,
+    src_step: Option<f64>,
+    dst_step: Option<f64>,
+) -> Option<(&mut Dither, f64)> {
+    let dither = dither?;
+    let dst_step = dst_step?;
+    match src_step {
+        Some(src_step) if src_step >= dst_step => None,
+        _ => Some((dither, dst_step)),
+    }
+}
+
+/// Convert the samples of `src` into `dst`.
+///
+/// Converts `min(src.len(), dst.len())` samples and returns the number converted.
+///
+/// If `dither` is given and `D` has a coarser resolution than `S`, TPDF dither is added before
+/// rounding.
+pub fn convert<S, D>(src: &[S], dst: &mut [D], dither: Option<&mut Dither>) -> usize
+where
+    S: ConvertSample,
+    D: ConvertSample,
+{
+    let len = src.len().min(dst.len());
+    let pairs = src.iter().zip(dst.iter_mut());
+    match dither_amplitude(dither, S::quantization_step(), D::quantization_step()) {
+        Some((dither, step)) => {
+            for (s, d) in pairs {
+                *d = D::from_f64(s.to_f64() + dither.tpdf() * step);
+            }
+        }
+        None => {
+            for (s, d) in pairs {
+                *d = D::from_f64(s.to_f64());
+            }
+        }
+    }
+    len
+}
+
+/// Convert samples of type `S`, stored as plain bytes as in
+/// [**InterleavedBytes**](../render_callback/data/struct.InterleavedBytes.html), into `dst`.
+///
+/// Returns the number of samples converted. Trailing bytes that do not make up a whole sample
+/// are ignored.
+pub fn convert_from_bytes<S, D>(src: &[u8], dst: &mut [D], dither: Option<&mut Dither>) -> usize
+where
+    S: ConvertSample,
+    D: ConvertSample,
+{
+    let size = mem::size_of::<S>();
+    let len = (src.len() / size).min(dst.len());
+    let read = |i: usize| unsafe { ptr::read_unaligned(src.as_ptr().add(i * size) as *const S) };
+    match dither_amplitude(dither, S::quantization_step(), D::quantization_step()) {
+        Some((dither, step)) => {
+            for (i, d) in dst[..len].iter_mut().enumerate() {
+                *d = D::from_f64(read(i).to_f64() + dither.tpdf() * step);
+            }
+        }
+        None => {
+            for (i, d) in dst[..len].iter_mut().enumerate() {
+                *d = D::from_f64(read(i).to_f64());
+            }
+        }
+    }
+    len
+}
+
+/// Convert the samples of `src` into samples of type `D` stored as plain bytes, as in
+/// [**InterleavedBytes**](../render_callback/data/struct.InterleavedBytes.html).
+///
+/// Returns the number of samples converted.
+pub fn convert_to_bytes<S, D>(src: &[S], dst: &mut [u8], dither: Option<&mut Dither>) -> usize
+where
+    S: ConvertSample,
+    D: ConvertSample,
+{
+    let size = mem::size_of::<D>();
+    let len = src.len().min(dst.len() / size);
+    let dst_ptr = dst.as_mut_ptr();
+    let write =
+        |i: usize, d: D| unsafe { ptr::write_unaligned(dst_ptr.add(i * size) as *mut D, d) };
+    match dither_amplitude(dither, S::quantization_step(), D::quantization_step()) {
+        Some((dither, step)) => {
+            for (i, s) in src[..len].iter().enumerate() {
+                write(i, D::from_f64(s.to_f64() + dither.tpdf() * step));
+            }
+        }
+        None => {
+            for (i, s) in src[..len].iter().enumerate() {
+                write(i, D::from_f64(s.to_f64()));
+            }
+        }
+    }
+    len
+}
+
+/// Convert each channel of `src` into the corresponding channel of `dst`.
+///
+/// Channels without a counterpart are left untouched. Returns the number of samples converted
+/// across all channels.
+pub fn convert_non_interleaved<S, D>(
+    src: &NonInterleaved<S>,
+    dst: &mut NonInterleaved<D>,
+    mut dither: Option<&mut Dither>,
+) -> usize
+where
+    S: ConvertSample,
+    D: ConvertSample,
+{
+    src.channels()
+        .zip(dst.channels_mut())
+        .map(|(s, d)| convert(s, d, dither.as_deref_mut()))
+        .sum()
+}
+
+/// Convert samples stored as plain bytes in the format described by `src_format` into `dst`,
+/// stored in the format described by `dst_format`.
+///
+/// Only the `sample_format` and `flags` of the formats are used. The flags determine the byte
+/// order, the packing of 24-bit samples and the number of fractional bits of fixed-point
+/// samples.
+///
+/// Returns the number of samples converted. Trailing bytes that do not make up a whole sample
+/// are ignored.
+pub fn convert_bytes(
+    src: &[u8],
+    src_format: &StreamFormat,
+    dst: &mut [u8],
+    dst_format: &StreamFormat,
+    dither: Option<&mut Dither>,
+) -> usize {
+    let src_layout = Layout::new(src_format.sample_format, src_format.flags);
+    let dst_layout = Layout::new(dst_format.sample_format, dst_format.flags);
+    let len = (src.len() / src_layout.size).min(dst.len() / dst_layout.size);
+    let src_samples = src.chunks_exact(src_layout.size);
+    let dst_samples = dst.chunks_exact_mut(dst_layout.size);
+    let pairs = src_samples.zip(dst_samples);
+    match dither_amplitude(dither, src_layout.step(), dst_layout.step()) {
+        Some((dither, step)) => {
+            for (s, d) in pairs {
+                dst_layout.write(d, src_layout.read(s) + dither.tpdf() * step);
+            }
+        }
+        None => {
+            for (s, d) in pairs {
+                dst_layout.write(d, src_layout.read(s));
+            }
+        }
+    }
+    len
+}
+
+/// Convert one channel of interleaved samples, stored as plain bytes in the format described by
+/// `src_format`, into `dst`.
+///
+/// The samples of the channel are `channels` samples apart, starting from sample `channel`. Only
+/// the `sample_format` and `flags` of the format are used.
+///
+/// Returns the number of samples converted. Trailing bytes that do not make up a whole frame are
+/// ignored.
+///
+/// Panics if `channel` is not less than `channels`.
+pub fn convert_channel_from_bytes<D: ConvertSample>(
+    src: &[u8],
+    src_format: &StreamFormat,
+    channel: usize,
+    channels: usize,
+    dst: &mut [D],
+    dither: Option<&mut Dither>,
+) -> usize {
+    assert!(channel < channels, "channel out of range");
+    let layout = Layout::new(src_format.sample_format, src_format.flags);
+    let offset = channel * layout.size;
+    let frames = src.chunks_exact(layout.size * channels);
+    let samples = frames.map(|frame| &frame[offset..offset + layout.size]);
+    let len = (src.len() / (layout.size * channels)).min(dst.len());
+    let pairs = samples.zip(dst.iter_mut());
+    match dither_amplitude(dither, layout.step(), D::quantization_step()) {
+        Some((dither, step)) => {
+            for (s, d) in pairs {
+                *d = D::from_f64(layout.read(s) + dither.tpdf() * step);
+            }
+        }
+        None => {
+            for (s, d) in pairs {
+                *d = D::from_f64(layout.read(s));
+            }
+        }
+    }
+    len
+}
+
+/// Convert the samples of `src` into one channel of interleaved samples, stored as plain bytes in
+/// the format described by `dst_format`.
+///
+/// The samples of the channel are `channels` samples apart, starting from sample `channel`. The
+/// samples of other channels are left untouched. Only the `sample_format` and `flags` of the
+/// format are used.
+///
+/// Returns the number of samples converted.
+///
+/// Panics if `channel` is not less than `channels`.
+pub fn convert_channel_to_bytes<S: ConvertSample>(
+    src: &[S],
+    dst: &mut [u8],
+    dst_format: &StreamFormat,
+    channel: usize,
+    channels: usize,
+    dither: Option<&mut Dither>,
+) -> usize {
+    assert!(channel < channels, "channel out of range");
+    let layout = Layout::new(dst_format.sample_format, dst_format.flags);
+    let offset = channel * layout.size;
+    let len = (dst.len() / (layout.size * channels)).min(src.len());
+    let frames = dst.chunks_exact_mut(layout.size * channels);
+    let samples = frames.map(|frame| &mut frame[offset..offset + layout.size]);
+    let pairs = src.iter().zip(samples);
+    match dither_amplitude(dither, S::quantization_step(), layout.step()) {
+        Some((dither, step)) => {
+            for (s, d) in pairs {
+                layout.write(d, s.to_f64() + dither.tpdf() * step);
+            }
+        }
+        None => {
+            for (s, d) in pairs {
+                layout.write(d, s.to_f64());
+            }
+        }
+    }
+    len
+}
+
+/// The in-memory representation of a sample as described by a `SampleFormat` and its flags.
+#[derive(Copy, Clone, Debug)]
+enum Kind {
+    F64,
+    F32,
+    I32,
+    Fixed(u32),
+    I24,
+    I24AlignedHigh,
+    I24AlignedLow,
+    I16,
+    I8,
+    U16,
+    U8,
+}
+
+/// A dynamically described sample layout used by `convert_bytes`.
+#[derive(Copy, Clone, Debug)]
+struct Layout {
+    kind: Kind,
+    big_endian: bool,
+    size: usize,
+}
+
+/// Dispatch on a `Kind` to code generic over the matching sample type.
+macro_rules! with_kind {
+    ($kind:expr, $big_endian:expr, |$T:ident| $body:expr) => {
+        match ($kind, $big_endian) {
+            (Kind::F64, false) => {
+                type $T = LittleEndian<f64>;
+                $body
+            }
+            (Kind::F64, true) => {
+                type $T = BigEndian<f64>;
+                $body
+            }
+            (Kind::F32, false) => {
+                type $T = LittleEndian<f32>;
+                $body
+            }
+            (Kind::F32, true) => {
+                type $T = BigEndian<f32>;
+                $body
+            }
+            (Kind::I32, false) => {
+                type $T = LittleEndian<i32>;
+                $body
+            }
+            (Kind::I32, true) => {
+                type $T = BigEndian<i32>;
+                $body
+            }
+            (Kind::I24, false) => {
+                type $T = LittleEndian<I24>;
+                $body
+            }
+            (Kind::I24, true) => {
+                type $T = BigEndian<I24>;
+                $body
+            }
+            (Kind::I24AlignedHigh, false) => {
+                type $T = LittleEndian<I24AlignedHigh>;
+                $body
+            }
+            (Kind::I24AlignedHigh, true) => {
+                type $T = BigEndian<I24AlignedHigh>;
+                $body
+            }
+            (Kind::I24AlignedLow, false) => {
+                type $T = LittleEndian<I24AlignedLow>;
+                $body
+            }
+            (Kind::I24AlignedLow, true) => {
+                type $T = BigEndian<I24AlignedLow>;
+                $body
+            }
+            (Kind::I16, false) => {
+                type $T = LittleEndian<i16>;
+                $body
+            }
+            (Kind::I16, true) => {
+                type $T = BigEndian<i16>;
+                $body
+            }
+            (Kind::I8, _) => {
+                type $T = i8;
+                $body
+            }
+            (Kind::U16, false) => {
+                type $T = LittleEndian<u16>;
+                $body
+            }
+            (Kind::U16, true) => {
+                type $T = BigEndian<u16>;
+                $body
+            }
+            (Kind::U8, _) => {
+                type $T = u8;
+                $body
+            }
+            (Kind::Fixed(_), _) => unreachable!(),
+        }
+    };
+}
+
+impl Layout {
+    fn new(sample_format: SampleFormat, flags: LinearPcmFlags) -> Self {
+        let kind = match sample_format {
+            SampleFormat::F64 => Kind::F64,
+            SampleFormat::F32 => Kind::F32,
+            SampleFormat::I32 if flags.fraction_bits() > 0 => Kind::Fixed(flags.fraction_bits()),
+            SampleFormat::I32 => Kind::I32,
+            SampleFormat::I24 if flags.contains(LinearPcmFlags::IS_PACKED) => Kind::I24,
+            SampleFormat::I24 if flags.contains(LinearPcmFlags::IS_ALIGNED_HIGH) => {
+                Kind::I24AlignedHigh
+            }
+            SampleFormat::I24 => Kind::I24AlignedLow,
+            SampleFormat::I16 => Kind::I16,
+            SampleFormat::I8 => Kind::I8,
+            SampleFormat::U16 => Kind::U16,
+            SampleFormat::U8 => Kind::U8,
+        };
+        Layout {
+            kind,
+            big_endian: flags.contains(LinearPcmFlags::IS_BIG_ENDIAN),
+            size: sample_format.size_in_bytes_with_flags(flags),
+        }
+    }
+
+    fn step(&self) -> Option<f64> {
+        match self.kind {
+            Kind::Fixed(frac) => Some(1.0 / (1u64 << frac) as f64),
+            kind => with_kind!(kind, self.big_endian, |T| T::quantization_step()),
+        }
+    }
+
+    /// Read a sample from exactly `self.size` bytes.
+    fn read(&self, bytes: &[u8]) -> f64 {
+        match self.kind {
+            Kind::Fixed(frac) => {
+                let bits = read_unaligned::<i32>(bytes);
+                let bits = if self.big_endian {
+                    i32::from_be(bits)
+                } else {
+                    i32::from_le(bits)
+                };
+                bits as f64 / (1u64 << frac) as f64
+            }
+            kind => with_kind!(kind, self.big_endian, |T| read_unaligned::<T>(bytes)
+                .to_f64()),
+        }
+    }
+
+    /// Write a sample to exactly `self.size` bytes.
+    fn write(&self, bytes: &mut [u8], value: f64) {
+        match self.kind {
+            Kind::Fixed(frac) => {
+                let bits = (value * (1u64 << frac) as f64).round() as i32;
+                let bits = if self.big_endian {
+                    bits.to_be()
+                } else {
+                    bits.to_le()
+                };
+                write_unaligned(bytes, bits);
+            }
+            kind => with_kind!(kind, self.big_endian, |T| write_unaligned(
+                bytes,
+                T::from_f64(value)
+            )),
+        }
+    }
+}
+
+fn read_unaligned<T: Copy>(bytes: &[u8]) -> T {
+    assert!(bytes.len() >= mem::size_of::<T>());
+    unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) }
+}
+
+fn write_unaligned<T: Copy>(bytes: &mut [u8], value: T) {
+    assert!(bytes.len() >= mem::size_of::<T>());
+    unsafe { ptr::write_unaligned(bytes.as_mut_ptr() as *mut T, value) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn test_integer_scaling() {
+        let src = [i16::MIN, 0, 16384, i16::MAX];
+        let mut dst = [0.0f32; 4];
+        assert_eq!(convert(&src, &mut dst, None), 4);
+        assert_eq!(dst, [-1.0, 0.0, 0.5, 32767.0 / 32768.0]);
+
+        let mut back = [0i16; 4];
+        convert(&dst, &mut back, None);
+        assert_eq!(back, src);
+
+        let mut unsigned = [0u8; 4];
+        convert(&src, &mut unsigned, None);
+        assert_eq!(unsigned, [0, 128, 192, 255]);
+    }
+
+    #[test]
+    fn test_rounding_and_clipping() {
+        let src = [2.0f32, -2.0, 0.25 / 32768.0, 0.75 / 32768.0, f32::NAN];
+        let mut dst = [0i16; 5];
+        convert(&src, &mut dst, None);
+        assert_eq!(dst, [i16::MAX, i16::MIN, 0, 1, 0]);
+
+        let mut i24 = [I24::default(); 2];
+        convert(&[1.0f64, -1.0], &mut i24, None);
+        assert_eq!(i24.map(I24::get), [I24::MAX, I24::MIN]);
+    }
+
+    #[test]
+    fn test_dither() {
+        let src = [0.1f32; 256];
+        let mut dst = [0i8; 256];
+        let mut dither = Dither::new(1);
+        convert(&src, &mut dst, Some(&mut dither));
+        // 0.1 is 12.8 steps of an i8, so dither may move it to a neighbouring step.
+        assert!(dst.iter().all(|&s| (12..=14).contains(&s)));
+        assert!(dst.iter().any(|&s| s != 13));
+
+        // No dither is applied when the resolution increases.
+        let src = [3i8; 4];
+        let mut dst = [0i16; 4];
+        convert(&src, &mut dst, Some(&mut dither));
+        assert_eq!(dst, [3 << 8; 4]);
+    }
+
+    #[test]
+    fn test_convert_bytes() {
+        let flags = LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED;
+        let format = |sample_format, flags| StreamFormat {
+            sample_rate: 44_100.0,
+            sample_format,
+            flags,
+            channels: 1,
+        };
+        let src_format = format(SampleFormat::I16, flags | LinearPcmFlags::IS_BIG_ENDIAN);
+        let dst_format = format(SampleFormat::I24, flags);
+        let src = [0x40u8, 0x00, 0xc0, 0x00];
+        let mut dst = [0u8; 6];
+        let n = convert_bytes(&src, &src_format, &mut dst, &dst_format, None);
+        assert_eq!(n, 2);
+        let samples = I24::slice_from_bytes(&dst).unwrap();
+        assert_eq!(samples[0].get(), 0x40_0000);
+        assert_eq!(samples[1].get(), -0x40_0000);
+
+        let fixed_format = format(SampleFormat::I32, flags.with_fraction_bits(24));
+        let mut fixed = [0u8; 8];
+        convert_bytes(&dst, &dst_format, &mut fixed, &fixed_format, None);
+        assert_eq!(i32::from_ne_bytes(fixed[..4].try_into().unwrap()), 1 << 23);
+    }
+
+    #[test]
+    fn test_convert_typed_bytes() {
+        let src = [0.5f32, -0.5];
+        let mut bytes = [0u8; 6];
+        assert_eq!(convert_to_bytes::<f32, I24>(&src, &mut bytes, None), 2);
+        let mut dst = [0.0f32; 2];
+        assert_eq!(convert_from_bytes::<I24, f32>(&bytes, &mut dst, None), 2);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn test_convert_channel_bytes() {
+        let format = StreamFormat {
+            sample_rate: 44_100.0,
+            sample_format: SampleFormat::I16,
+            flags: LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED,
+            channels: 2,
+        };
+        let mut bytes = [0u8; 12];
+        convert_channel_to_bytes(&[0.5f32, -0.5, 0.25], &mut bytes, &format, 1, 2, None);
+        let samples: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(samples, [0, 16384, 0, -16384, 0, 8192]);
+
+        let mut dst = [0.0f32; 4];
+        assert_eq!(
+            convert_channel_from_bytes(&bytes, &format, 1, 2, &mut dst, None),
+            3
+        );
+        assert_eq!(dst, [0.5, -0.5, 0.25, 0.0]);
+    }
+}
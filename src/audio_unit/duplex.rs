@@ -0,0 +1,531 @@
+//! A full-duplex stream, passing the audio captured by one **AudioUnit** to a closure that also
+//! fills the output of another.
+//!
+//! Input and output callbacks are generally called on different threads, with different buffer
+//! sizes and from devices whose clocks drift apart over time. A **DuplexStream** moves the input
+//! through a [**ring_buffer**](../ring_buffer/index.html) and hands it to the output callback
+//! once a target **Latency** has built up. If the input runs ahead of the output the oldest
+//! frames are dropped to return to the target, and if it falls behind the output is given
+//! silence while the target builds up again.
+//!
+//! The buffering itself is available as a **LatencyConsumer** from
+//! [**latency_buffer**](./fn.latency_buffer.html), for hosts that drive their own callbacks.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::duplex::{self, DuplexStream, Latency};
+//! use coreaudio::audio_unit::render_callback::data;
+//! use coreaudio::audio_unit::{AudioUnit, IOType};
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let input = AudioUnit::new(IOType::HalOutput)?;
+//! let output = AudioUnit::new(IOType::DefaultOutput)?;
+//! type Args<'a> = duplex::Args<'a, f32, data::NonInterleaved<f32>>;
+//! let mut stream = DuplexStream::new(input, output, Latency::frames(512), |args: Args| {
+//!     let Args {
+//!         input,
+//!         input_channels,
+//!         mut output,
+//!         ..
+//!     } = args;
+//!     // Play the first input channel on every output channel.
+//!     for channel in output.channels_mut() {
+//!         for (sample, frame) in channel.iter_mut().zip(input.chunks(input_channels)) {
+//!             *sample = frame[0];
+//!         }
+//!     }
+//!     Ok(())
+//! })?;
+//! stream.start()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::sys::AudioTimeStamp;
+
+use super::audio_format::LinearPcmFlags;
+use super::render_callback::{self, action_flags, data, Data, IntoCallbackOutcome};
+use super::ring_buffer::{self, Consumer, Producer, RingData};
+use super::{AudioUnit, Sample};
+use crate::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The number of frames of input to buffer when no **Latency** is given.
+const DEFAULT_TARGET_FRAMES: usize = 1024;
+
+/// Room in the buffer beyond the latency for the largest block a callback is expected to push.
+const HEADROOM_FRAMES: usize = 4096;
+
+/// The amount of input a **LatencyConsumer** holds back between the input and the output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Latency {
+    /// The number of frames of input left in the buffer once it has primed, which is how late
+    /// the input may arrive before the output runs dry.
+    pub target_frames: usize,
+    /// How many frames above the target the buffer may hold before frames are dropped.
+    ///
+    /// This should be at least the largest block the input pushes at once, as the buffer grows
+    /// by a whole block whenever the input callback runs just before the output callback.
+    pub tolerance_frames: usize,
+}
+
+impl Latency {
+    /// Aim for `target_frames` frames of latency, tolerating as many again before catching up.
+    pub fn frames(target_frames: usize) -> Self {
+        Latency {
+            target_frames,
+            tolerance_frames: target_frames,
+        }
+    }
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Latency::frames(DEFAULT_TARGET_FRAMES)
+    }
+}
+
+/// The frames a **LatencyConsumer** has dropped or inserted to hold its target latency, shared
+/// with the **DuplexStream** that owns it.
+#[derive(Debug, Default)]
+struct Corrections {
+    dropped: AtomicUsize,
+    inserted: AtomicUsize,
+}
+
+/// The reading end of a latency buffer, created by [**latency_buffer**](./fn.latency_buffer.html).
+///
+/// Pulls give silence until the buffer holds `latency.target_frames` frames beyond those
+/// requested, and then hand out the oldest frames in the buffer. The buffer primes again whenever
+/// it runs dry, and drops its oldest frames whenever it holds more than the tolerance above the
+/// target.
+pub struct LatencyConsumer<S> {
+    consumer: Consumer<S>,
+    latency: Latency,
+    priming: bool,
+    corrections: Arc<Corrections>,
+}
+
+/// Create a buffer of `channels` channels that holds back the given **Latency** of input.
+///
+/// Frames pushed to the **Producer** are pulled from the **LatencyConsumer**, typically from
+/// another thread.
+pub fn latency_buffer<S>(channels: usize, latency: Latency) -> (Producer<S>, LatencyConsumer<S>)
+where
+    S: Sample + Copy + Send,
+{
+    let capacity = 2 * (latency.target_frames + latency.tolerance_frames) + HEADROOM_FRAMES;
+    let (producer, consumer) = ring_buffer::interleaved(channels, capacity);
+    let consumer = LatencyConsumer {
+        consumer,
+        latency,
+        priming: true,
+        corrections: Arc::new(Corrections::default()),
+    };
+    (producer, consumer)
+}
+
+impl<S: Copy + Default> LatencyConsumer<S> {
+    /// The number of channels in each frame.
+    pub fn channels(&self) -> usize {
+        self.consumer.channels()
+    }
+
+    /// The latency the buffer aims for.
+    pub fn latency(&self) -> Latency {
+        self.latency
+    }
+
+    /// The number of frames in the buffer.
+    pub fn len(&self) -> usize {
+        self.consumer.len()
+    }
+
+    /// Returns `true` if there are no frames in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.consumer.is_empty()
+    }
+
+    /// Returns `true` while the buffer is building up to the target latency, during which pulls
+    /// return silence.
+    pub fn is_priming(&self) -> bool {
+        self.priming
+    }
+
+    /// The total number of frames dropped because the input ran ahead of the output.
+    pub fn dropped_frames(&self) -> usize {
+        self.corrections.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The total number of frames of silence inserted because the input fell behind the output.
+    pub fn inserted_frames(&self) -> usize {
+        self.corrections.inserted.load(Ordering::Relaxed)
+    }
+
+    /// Pull interleaved frames into `samples`, returning the number of frames of input pulled.
+    ///
+    /// Any remaining frames are filled with `S::default()`. While priming, no input is pulled
+    /// until the buffer holds the target latency beyond the frames requested. If the buffer then
+    /// runs dry, the missing frames are filled and the buffer primes again.
+    ///
+    /// Panics if the length of `samples` is not a multiple of the number of channels.
+    pub fn pull_interleaved(&mut self, samples: &mut [S]) -> usize {
+        let channels = self.consumer.channels();
+        assert_eq!(
+            samples.len() % channels,
+            0,
+            "the samples must hold whole frames of {} channels",
+            channels
+        );
+        let frames = samples.len() / channels;
+        let Latency {
+            target_frames,
+            tolerance_frames,
+        } = self.latency;
+        let len = self.consumer.len();
+
+        if self.priming {
+            if len < frames + target_frames {
+                samples.fill(S::default());
+                self.corrections
+                    .inserted
+                    .fetch_add(frames, Ordering::Relaxed);
+                return 0;
+            }
+            self.priming = false;
+        }
+
+        // Catch up with input that has run ahead, e.g. from a faster input clock.
+        if len > frames + target_frames + tolerance_frames {
+            let dropped = self.consumer.skip(len - frames - target_frames);
+            self.corrections
+                .dropped
+                .fetch_add(dropped, Ordering::Relaxed);
+        }
+
+        let popped = self.consumer.pop_interleaved(samples);
+        if popped < frames {
+            samples[popped * channels..].fill(S::default());
+            self.corrections
+                .inserted
+                .fetch_add(frames - popped, Ordering::Relaxed);
+            self.priming = true;
+        }
+        popped
+    }
+}
+
+/// Arguments given to the closure of a **DuplexStream** once per output cycle.
+pub struct Args<'a, S, D> {
+    /// The input for this cycle as interleaved samples, holding `num_frames` frames.
+    pub input: &'a [S],
+    /// The number of channels in each frame of `input`.
+    pub input_channels: usize,
+    /// The output buffer to fill.
+    pub output: D,
+    /// Timing information for the output cycle.
+    pub time_stamp: AudioTimeStamp,
+    /// The number of frames in the input and output buffers.
+    pub num_frames: usize,
+    /// Flags for configuring the output render.
+    pub flags: action_flags::Handle,
+}
+
+/// A pair of input and output units whose audio is handled by a single closure.
+///
+/// Created by [**new**](./struct.DuplexStream.html#method.new) from separate units, or by
+/// [**from_unit**](./struct.DuplexStream.html#method.from_unit) from a single unit with both
+/// input and output enabled. The callbacks are removed when the stream is dropped.
+pub struct DuplexStream {
+    input: Option<AudioUnit>,
+    output: AudioUnit,
+    corrections: Arc<Corrections>,
+}
+
+impl DuplexStream {
+    /// Pair the input captured by `input` with the output rendered by `output`.
+    ///
+    /// Both units must run at the same sample rate, or `Error::UnsupportedSampleRate` is
+    /// returned. Their stream formats must hold samples of type `S`, and the output format must
+    /// match `D`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **input**: The unit whose input callback captures the input, e.g. a **HalOutput** unit
+    ///   with input enabled.
+    /// - **output**: The unit whose render callback calls `f`.
+    /// - **latency**: The **Latency** to hold between the input and the output.
+    /// - **f**: The closure called with the input and output of each cycle.
+    pub fn new<S, D, F, R>(
+        mut input: AudioUnit,
+        mut output: AudioUnit,
+        latency: Latency,
+        f: F,
+    ) -> Result<Self, Error>
+    where
+        S: Sample + Copy + Default + Send + 'static,
+        D: Data,
+        F: for<'a> FnMut(Args<'a, S, D>) -> R + 'static,
+        R: IntoCallbackOutcome,
+    {
+        let corrections = connect(&mut input, Some(&mut output), latency, f)?;
+        Ok(DuplexStream {
+            input: Some(input),
+            output,
+            corrections,
+        })
+    }
+
+    /// Pair the input and output of a single unit, such as a **HalOutput** unit with both input
+    /// and output enabled on the same device.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **unit**: The unit whose input and render callbacks are set.
+    /// - **latency**: The **Latency** to hold between the input and the output.
+    /// - **f**: The closure called with the input and output of each cycle.
+    pub fn from_unit<S, D, F, R>(mut unit: AudioUnit, latency: Latency, f: F) -> Result<Self, Error>
+    where
+        S: Sample + Copy + Default + Send + 'static,
+        D: Data,
+        F: for<'a> FnMut(Args<'a, S, D>) -> R + 'static,
+        R: IntoCallbackOutcome,
+    {
+        let corrections = connect(&mut unit, None, latency, f)?;
+        Ok(DuplexStream {
+            input: None,
+            output: unit,
+            corrections,
+        })
+    }
+
+    /// Start the input and output units.
+    pub fn start(&mut self) -> Result<(), Error> {
+        if let Some(input) = self.input.as_mut() {
+            input.start()?;
+        }
+        self.output.start()
+    }
+
+    /// Stop the output and input units.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        self.output.stop()?;
+        if let Some(input) = self.input.as_mut() {
+            input.stop()?;
+        }
+        Ok(())
+    }
+
+    /// The unit capturing the input, which is the output unit for a stream created by
+    /// [**from_unit**](./struct.DuplexStream.html#method.from_unit).
+    pub fn input_unit(&self) -> &AudioUnit {
+        self.input.as_ref().unwrap_or(&self.output)
+    }
+
+    /// The unit rendering the output.
+    pub fn output_unit(&self) -> &AudioUnit {
+        &self.output
+    }
+
+    /// The total number of input frames dropped because the input ran ahead of the output.
+    pub fn dropped_frames(&self) -> usize {
+        self.corrections.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The total number of frames of silence given in place of input because the input fell
+    /// behind the output, including while the latency first builds up.
+    pub fn inserted_frames(&self) -> usize {
+        self.corrections.inserted.load(Ordering::Relaxed)
+    }
+}
+
+/// Set the input callback of `input` and the render callback of `output`, or of `input` itself if
+/// `output` is `None`, joined by a latency buffer.
+fn connect<S, D, F, R>(
+    input: &mut AudioUnit,
+    output: Option<&mut AudioUnit>,
+    latency: Latency,
+    mut f: F,
+) -> Result<Arc<Corrections>, Error>
+where
+    S: Sample + Copy + Default + Send + 'static,
+    D: Data,
+    F: for<'a> FnMut(Args<'a, S, D>) -> R + 'static,
+    R: IntoCallbackOutcome,
+{
+    let input_format = input.input_stream_format()?;
+    let output = match output {
+        Some(output) => output,
+        None => &mut *input,
+    };
+    let output_format = output.output_stream_format()?;
+    if input_format.sample_rate != output_format.sample_rate {
+        return Err(Error::UnsupportedSampleRate);
+    }
+    let input_channels = input_format.channels as usize;
+    let (producer, mut consumer) = latency_buffer::<S>(input_channels, latency);
+    let corrections = consumer.corrections.clone();
+
+    // Preallocate the input for the largest cycle the output is expected to render.
+    let max_frames = output.max_frames_per_slice();
+    let mut scratch = vec![S::default(); max_frames as usize * input_channels];
+
+    type OutputArgs<D> = render_callback::Args<D>;
+    output.set_render_callback(move |args: OutputArgs<D>| {
+        let OutputArgs {
+            data,
+            time_stamp,
+            num_frames,
+            flags,
+            ..
+        } = args;
+        let len = num_frames * input_channels;
+        if scratch.len() < len {
+            scratch.resize(len, S::default());
+        }
+        let input = &mut scratch[..len];
+        consumer.pull_interleaved(input);
+        f(Args {
+            input,
+            input_channels,
+            output: data,
+            time_stamp,
+            num_frames,
+            flags,
+        })
+    })?;
+
+    if input_format
+        .flags
+        .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
+    {
+        set_input::<S, data::NonInterleaved<S>>(input, producer)?;
+    } else {
+        set_input::<S, data::Interleaved<S>>(input, producer)?;
+    }
+    Ok(corrections)
+}
+
+/// Push the input of `unit` to `producer`.
+fn set_input<S, D>(unit: &mut AudioUnit, mut producer: Producer<S>) -> Result<(), Error>
+where
+    S: Sample + Copy + 'static,
+    D: Data + RingData<S>,
+{
+    unit.set_input_callback(move |args: render_callback::Args<D>| {
+        producer.push_data(&args.data);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mismatched_block_sizes() {
+        let latency = Latency {
+            target_frames: 256,
+            tolerance_frames: 512,
+        };
+        let (mut producer, mut consumer) = latency_buffer::<i32>(1, latency);
+        let mut next = 0;
+        let mut block = [0; 128];
+        let mut pulled = vec![];
+        // The input delivers blocks of 512 frames just before every fourth output block of 128.
+        for cycle in 0..64 {
+            if cycle % 4 == 3 {
+                let samples: Vec<i32> = (next..next + 512).collect();
+                next += producer.push_interleaved(&samples) as i32;
+            }
+            let popped = consumer.pull_interleaved(&mut block);
+            pulled.extend_from_slice(&block[..popped]);
+        }
+        // Silence is only given until the first input arrives, after which the input comes out
+        // in order without gaps.
+        assert_eq!(consumer.inserted_frames(), 3 * 128);
+        assert_eq!(consumer.dropped_frames(), 0);
+        let expected: Vec<i32> = (0..61 * 128).collect();
+        assert_eq!(pulled, expected);
+        assert!(!consumer.is_priming());
+    }
+
+    #[test]
+    fn test_fast_input_is_dropped() {
+        let latency = Latency::frames(64);
+        let (mut producer, mut consumer) = latency_buffer::<i32>(1, latency);
+        let mut next = 1;
+        let mut block = [0; 64];
+        let mut pulled = vec![];
+        for _ in 0..200 {
+            let samples: Vec<i32> = (next..next + 66).collect();
+            next += producer.push_interleaved(&samples) as i32;
+            let popped = consumer.pull_interleaved(&mut block);
+            pulled.extend_from_slice(&block[..popped]);
+            // The buffer never holds more than the tolerance above the target.
+            assert!(consumer.len() <= 128, "{}", consumer.len());
+        }
+        assert!(consumer.dropped_frames() > 0);
+        assert_eq!(consumer.inserted_frames(), 64);
+        // Dropped frames make the output jump ahead, but input is never repeated or reordered.
+        assert!(pulled.windows(2).all(|w| w[1] > w[0]));
+        assert!(pulled.windows(2).any(|w| w[1] > w[0] + 1));
+    }
+
+    #[test]
+    fn test_slow_input_is_padded_with_silence() {
+        let latency = Latency::frames(32);
+        let (mut producer, mut consumer) = latency_buffer::<i32>(1, latency);
+        let mut next = 1;
+        let mut block = [0; 64];
+        let mut pulled = vec![];
+        for _ in 0..200 {
+            let samples: Vec<i32> = (next..next + 62).collect();
+            next += producer.push_interleaved(&samples) as i32;
+            let popped = consumer.pull_interleaved(&mut block);
+            assert!(block[popped..].iter().all(|&sample| sample == 0));
+            pulled.extend_from_slice(&block[..popped]);
+        }
+        assert_eq!(consumer.dropped_frames(), 0);
+        assert!(consumer.inserted_frames() > 64);
+        // Every frame of input is played exactly once, in order.
+        let expected: Vec<i32> = (1..=pulled.len() as i32).collect();
+        assert_eq!(pulled, expected);
+        assert_eq!(pulled.len() + consumer.len(), 200 * 62);
+    }
+
+    #[cfg(not(target_os = "ios"))]
+    #[test]
+    fn test_duplex_stream_calls_closure_with_input_and_output() {
+        use crate::audio_unit::simulated::SimulatedConfig;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let config = SimulatedConfig {
+            buffer_frames: 64,
+            input_channels: 1,
+            realtime: false,
+            ..SimulatedConfig::default()
+        };
+        let input = AudioUnit::new_simulated(config).unwrap();
+        let output = AudioUnit::new_simulated(config).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        type Args<'a> = super::Args<'a, f32, data::NonInterleaved<f32>>;
+        let mut stream =
+            DuplexStream::new(input, output, Latency::frames(64), move |args: Args| {
+                let lens: Vec<usize> = args.output.channels().map(|c| c.len()).collect();
+                sender
+                    .send((args.input.len(), args.input_channels, lens))
+                    .ok();
+                Ok(())
+            })
+            .unwrap();
+        stream.start().unwrap();
+        let received = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(received, (64, 1, vec![64, 64]));
+        stream.stop().unwrap();
+        assert!(stream.inserted_frames() >= 64);
+    }
+}
@@ -3,10 +3,11 @@
 //! These functions are only implemented for macOS, not iOS.
 use crate::error::Error;
 use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr::{null, NonNull};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Sender};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::Duration;
 use std::{mem, thread};
 
@@ -15,23 +16,29 @@ use objc2_audio_toolbox::{
     kAudioOutputUnitProperty_CurrentDevice, kAudioOutputUnitProperty_EnableIO,
 };
 use objc2_core_audio::{
-    kAudioDevicePropertyAvailableNominalSampleRates, kAudioDevicePropertyDeviceIsAlive,
-    kAudioDevicePropertyDeviceNameCFString, kAudioDevicePropertyHogMode,
+    kAudioDeviceProcessorOverload, kAudioDevicePropertyAvailableNominalSampleRates,
+    kAudioDevicePropertyBufferFrameSize, kAudioDevicePropertyBufferFrameSizeRange,
+    kAudioDevicePropertyDeviceIsAlive, kAudioDevicePropertyDeviceNameCFString,
+    kAudioDevicePropertyHogMode, kAudioDevicePropertyJackIsConnected,
     kAudioDevicePropertyNominalSampleRate, kAudioDevicePropertyScopeOutput,
-    kAudioDevicePropertyStreamConfiguration, kAudioHardwareNoError,
-    kAudioHardwarePropertyDefaultInputDevice, kAudioHardwarePropertyDefaultOutputDevice,
-    kAudioHardwarePropertyDevices, kAudioObjectPropertyElementMaster,
-    kAudioObjectPropertyElementWildcard, kAudioObjectPropertyScopeGlobal,
-    kAudioObjectPropertyScopeInput, kAudioObjectPropertyScopeOutput, kAudioObjectSystemObject,
+    kAudioDevicePropertyStreamConfiguration, kAudioDevicePropertyStreamFormat,
+    kAudioHardwareNoError, kAudioHardwarePropertyDefaultInputDevice,
+    kAudioHardwarePropertyDefaultOutputDevice, kAudioHardwarePropertyDevices,
+    kAudioObjectPropertyElementMaster, kAudioObjectPropertyElementWildcard,
+    kAudioObjectPropertyScopeGlobal, kAudioObjectPropertyScopeInput,
+    kAudioObjectPropertyScopeOutput, kAudioObjectSystemObject,
     kAudioStreamPropertyAvailablePhysicalFormats, kAudioStreamPropertyPhysicalFormat,
     AudioDeviceID, AudioObjectAddPropertyListener, AudioObjectGetPropertyData,
     AudioObjectGetPropertyDataSize, AudioObjectID, AudioObjectPropertyAddress,
     AudioObjectPropertyListenerProc, AudioObjectPropertyScope, AudioObjectRemovePropertyListener,
     AudioObjectSetPropertyData, AudioStreamRangedDescription,
 };
-use objc2_core_audio_types::{AudioBufferList, AudioStreamBasicDescription, AudioValueRange};
+use objc2_core_audio_types::{
+    AudioBuffer, AudioBufferList, AudioStreamBasicDescription, AudioValueRange,
+};
 use objc2_core_foundation::CFString;
 
+use crate::audio_unit::audio_buffer_list::AudioBufferListBuf;
 use crate::audio_unit::audio_format::{AudioFormat, LinearPcmFlags};
 use crate::audio_unit::sample_format::SampleFormat;
 use crate::audio_unit::stream_format::StreamFormat;
@@ -301,7 +308,7 @@ pub fn set_device_sample_rate(device_id: AudioDeviceID, new_rate: f64) -> Result
     // Check whether or not we need to change the device sample rate to suit the one specified for the stream.
     unsafe {
         // Get the current sample rate.
-        let mut property_address = AudioObjectPropertyAddress {
+        let property_address = AudioObjectPropertyAddress {
             mSelector: kAudioDevicePropertyNominalSampleRate,
             mScope: kAudioObjectPropertyScopeGlobal,
             mElement: kAudioObjectPropertyElementMaster,
@@ -321,29 +328,7 @@ pub fn set_device_sample_rate(device_id: AudioDeviceID, new_rate: f64) -> Result
         // If the requested sample rate is different to the device sample rate, update the device.
         if sample_rate as u32 != new_rate as u32 {
             // Get available sample rate ranges.
-            property_address.mSelector = kAudioDevicePropertyAvailableNominalSampleRates;
-            let mut data_size = 0u32;
-            let status = AudioObjectGetPropertyDataSize(
-                device_id,
-                NonNull::from(&property_address),
-                0,
-                null(),
-                NonNull::from(&mut data_size),
-            );
-            Error::from_os_status(status)?;
-            let n_ranges = data_size as usize / mem::size_of::<AudioValueRange>();
-            let mut ranges: Vec<AudioValueRange> = vec![];
-            ranges.reserve_exact(n_ranges);
-            ranges.set_len(n_ranges);
-            let status = AudioObjectGetPropertyData(
-                device_id,
-                NonNull::from(&property_address),
-                0,
-                null(),
-                NonNull::from(&data_size),
-                NonNull::new(ranges.as_mut_ptr()).unwrap().cast(),
-            );
-            Error::from_os_status(status)?;
+            let ranges = get_available_sample_rates(device_id)?;
 
             // Now that we have the available ranges, pick the one matching the desired rate.
             let new_rate_integer = new_rate as u32;
@@ -355,13 +340,11 @@ pub fn set_device_sample_rate(device_id: AudioDeviceID, new_rate: f64) -> Result
                 Some(i) => i,
             };
 
-            // Update the property selector to specify the nominal sample rate.
-            property_address.mSelector = kAudioDevicePropertyNominalSampleRate;
-
             // Add a listener to know when the sample rate changes.
             // Since the listener implements Drop, we don't need to manually unregister this later.
             let (sender, receiver) = channel();
-            let mut listener = RateListener::new(device_id, Some(sender));
+            let delivery = Delivery::Sender(sender);
+            let mut listener = ObjectPropertyListener::nominal_sample_rate(device_id, delivery);
             listener.register()?;
 
             // Finally, set the sample rate.
@@ -371,7 +354,7 @@ pub fn set_device_sample_rate(device_id: AudioDeviceID, new_rate: f64) -> Result
                 0,
                 null(),
                 data_size,
-                NonNull::from(&ranges[range_index]).cast(),
+                NonNull::from(&ranges[range_index].mMinimum).cast(),
             );
             Error::from_os_status(status)?;
 
@@ -404,7 +387,14 @@ pub fn find_matching_physical_format(
     if let Ok(all_formats) = get_supported_physical_stream_formats(device_id) {
         let requested_samplerate = stream_format.sample_rate as usize;
         let requested_bits = stream_format.sample_format.size_in_bits();
-        let requested_float = stream_format.sample_format == SampleFormat::F32;
+        let requested_float = matches!(
+            stream_format.sample_format,
+            SampleFormat::F64 | SampleFormat::F32
+        );
+        let requested_unsigned = matches!(
+            stream_format.sample_format,
+            SampleFormat::U16 | SampleFormat::U8
+        );
         let requested_channels = stream_format.channels;
         for fmt in all_formats {
             let min_rate = fmt.mSampleRateRange.mMinimum as usize;
@@ -425,7 +415,7 @@ pub fn find_matching_physical_format(
                     // Wrong number type
                     continue;
                 }
-                if !requested_float && !is_int {
+                if !requested_float && (is_float || requested_unsigned == is_int) {
                     // Wrong number type
                     continue;
                 }
@@ -576,223 +566,408 @@ pub fn get_supported_physical_stream_formats(
     Ok(allformats)
 }
 
-/// Changing the sample rate is an asynchronous process.
-/// A RateListener can be used to get notified when the rate is changed.
-pub struct RateListener {
-    pub queue: Mutex<VecDeque<f64>>,
-    sync_channel: Option<Sender<f64>>,
-    device_id: AudioDeviceID,
+/// Get the nominal sample rate of a device.
+pub fn get_device_sample_rate(device_id: AudioDeviceID) -> Result<f64, Error> {
+    let address = device_property_address(kAudioDevicePropertyNominalSampleRate);
+    read_plain_property(device_id, &address)
+}
+
+/// Get the ranges of nominal sample rates that a device supports.
+pub fn get_available_sample_rates(device_id: AudioDeviceID) -> Result<Vec<AudioValueRange>, Error> {
+    let address = device_property_address(kAudioDevicePropertyAvailableNominalSampleRates);
+    unsafe {
+        let mut data_size = 0u32;
+        let status = AudioObjectGetPropertyDataSize(
+            device_id,
+            NonNull::from(&address),
+            0,
+            null(),
+            NonNull::from(&mut data_size),
+        );
+        Error::from_os_status(status)?;
+        let n_ranges = data_size as usize / mem::size_of::<AudioValueRange>();
+        let mut ranges: Vec<AudioValueRange> = Vec::with_capacity(n_ranges);
+        let status = AudioObjectGetPropertyData(
+            device_id,
+            NonNull::from(&address),
+            0,
+            null(),
+            NonNull::from(&mut data_size),
+            NonNull::new(ranges.as_mut_ptr()).unwrap().cast(),
+        );
+        Error::from_os_status(status)?;
+        ranges.set_len(data_size as usize / mem::size_of::<AudioValueRange>());
+        Ok(ranges)
+    }
+}
+
+/// Get the total number of channels in the input or output streams of a device.
+pub fn get_device_channel_count(device_id: AudioDeviceID, scope: Scope) -> Result<u32, Error> {
+    let address = device_scope_property_address(kAudioDevicePropertyStreamConfiguration, scope);
+    unsafe {
+        let mut data_size = 0u32;
+        let status = AudioObjectGetPropertyDataSize(
+            device_id,
+            NonNull::from(&address),
+            0,
+            null(),
+            NonNull::from(&mut data_size),
+        );
+        Error::from_os_status(status)?;
+        if (data_size as usize) < mem::size_of::<AudioBufferList>() {
+            return Ok(0);
+        }
+        // The list holds a variable number of buffers, without any data of their own.
+        let n_buffers = (data_size as usize - mem::size_of::<AudioBufferList>())
+            / mem::size_of::<AudioBuffer>()
+            + 1;
+        let mut list = AudioBufferListBuf::new(n_buffers as u32, 0, 0);
+        let status = AudioObjectGetPropertyData(
+            device_id,
+            NonNull::from(&address),
+            0,
+            null(),
+            NonNull::from(&mut data_size),
+            list.as_non_null().cast(),
+        );
+        Error::from_os_status(status)?;
+        let n_buffers = ((*list.as_ptr()).mNumberBuffers as usize).min(list.len());
+        let buffers = &list.audio_buffers()[..n_buffers];
+        Ok(buffers.iter().map(|buffer| buffer.mNumberChannels).sum())
+    }
+}
+
+/// Get the range of buffer sizes, in frames, that a device supports.
+pub fn get_buffer_frame_size_range(device_id: AudioDeviceID) -> Result<AudioValueRange, Error> {
+    let address = device_property_address(kAudioDevicePropertyBufferFrameSizeRange);
+    read_plain_property(device_id, &address)
+}
+
+/// A value that an **ObjectPropertyListener** decodes from an audio object property.
+pub trait PropertyValue: Sized + Send + 'static {
+    /// Read the current value of the property at `address` of the object `object_id`.
+    fn read(object_id: AudioObjectID, address: &AudioObjectPropertyAddress) -> Result<Self, Error>;
+}
+
+/// Read a property whose value the host represents as a plain `T`.
+fn read_plain_property<T: Copy>(
+    object_id: AudioObjectID,
+    address: &AudioObjectPropertyAddress,
+) -> Result<T, Error> {
+    let mut value = mem::MaybeUninit::<T>::uninit();
+    let mut data_size = mem::size_of::<T>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            object_id,
+            NonNull::from(address),
+            0,
+            null(),
+            NonNull::from(&mut data_size),
+            NonNull::from(&mut value).cast(),
+        )
+    };
+    Error::from_os_status(status)?;
+    Ok(unsafe { value.assume_init() })
+}
+
+impl PropertyValue for f64 {
+    fn read(object_id: AudioObjectID, address: &AudioObjectPropertyAddress) -> Result<Self, Error> {
+        read_plain_property(object_id, address)
+    }
+}
+
+impl PropertyValue for u32 {
+    fn read(object_id: AudioObjectID, address: &AudioObjectPropertyAddress) -> Result<Self, Error> {
+        read_plain_property(object_id, address)
+    }
+}
+
+impl PropertyValue for bool {
+    fn read(object_id: AudioObjectID, address: &AudioObjectPropertyAddress) -> Result<Self, Error> {
+        read_plain_property::<u32>(object_id, address).map(|value| value != 0)
+    }
+}
+
+// For properties that only signal an event, such as processor overloads.
+impl PropertyValue for () {
+    fn read(_: AudioObjectID, _: &AudioObjectPropertyAddress) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+impl PropertyValue for StreamFormat {
+    fn read(object_id: AudioObjectID, address: &AudioObjectPropertyAddress) -> Result<Self, Error> {
+        let asbd = read_plain_property::<AudioStreamBasicDescription>(object_id, address)?;
+        StreamFormat::from_asbd(asbd)
+    }
+}
+
+/// How an **ObjectPropertyListener** delivers the values it decodes.
+pub enum Delivery<T> {
+    /// Store the values in an internal queue that needs to be polled, e.g. with
+    /// `ObjectPropertyListener::drain_values`.
+    Queue,
+    /// Send the values through the given channel.
+    Sender(Sender<T>),
+    /// Call the given closure with each value, on a thread owned by Core Audio.
+    Callback(Box<dyn FnMut(T) + Send>),
+}
+
+/// The destination of the values, boxed by the listener so that the pointer handed to Core Audio
+/// remains valid when the listener is moved.
+enum Sink<T> {
+    Queue(Mutex<VecDeque<T>>),
+    Sender(Sender<T>),
+    Callback(Mutex<Box<dyn FnMut(T) + Send>>),
+}
+
+/// Lock a mutex of a **Sink**, ignoring poisoning by a panic that was caught in a callback.
+fn lock<U>(mutex: &Mutex<U>) -> MutexGuard<'_, U> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T> Sink<T> {
+    fn deliver(&self, value: T) {
+        match self {
+            Sink::Queue(queue) => lock(queue).push_back(value),
+            Sink::Sender(sender) => {
+                sender.send(value).ok();
+            }
+            Sink::Callback(callback) => (lock(callback))(value),
+        }
+    }
+}
+
+/// An ObjectPropertyListener can be used to get notified when a property of an audio object, such
+/// as a device, changes.
+///
+/// Each time the property changes its new value is decoded as a `T` and delivered according to
+/// the listener's **Delivery**. Listeners for common device properties are created with
+/// constructors such as [**nominal_sample_rate**](#method.nominal_sample_rate) and
+/// [**device_alive**](#method.device_alive).
+/// The listener must be registered by calling `register()` in order to start receiving
+/// notifications, and is unregistered when dropped.
+pub struct ObjectPropertyListener<T: PropertyValue> {
+    object_id: AudioObjectID,
     property_address: AudioObjectPropertyAddress,
-    rate_listener: AudioObjectPropertyListenerProc,
+    sink: Box<Sink<T>>,
+    listener: AudioObjectPropertyListenerProc,
 }
 
-impl Drop for RateListener {
+impl<T: PropertyValue> Drop for ObjectPropertyListener<T> {
     fn drop(&mut self) {
         let _ = self.unregister();
     }
 }
 
-impl RateListener {
-    /// Create a new RateListener for the given AudioDeviceID.
-    /// If an `std::sync::mpsc::Sender` is provided, then events will be pushed to that channel.
-    /// If not, they will instead be stored in an internal queue that will need to be polled.
-    /// The listener must be registered by calling `register()` in order to start receiving notifications.
-    pub fn new(device_id: AudioDeviceID, sync_channel: Option<Sender<f64>>) -> RateListener {
-        // Add our sample rate change listener callback.
-        let property_address = AudioObjectPropertyAddress {
-            mSelector: kAudioDevicePropertyNominalSampleRate,
-            mScope: kAudioObjectPropertyScopeGlobal,
-            mElement: kAudioObjectPropertyElementMaster,
+impl<T: PropertyValue> ObjectPropertyListener<T> {
+    /// Create a new ObjectPropertyListener for the property at `property_address` of the object
+    /// `object_id`.
+    pub fn new(
+        object_id: AudioObjectID,
+        property_address: AudioObjectPropertyAddress,
+        delivery: Delivery<T>,
+    ) -> ObjectPropertyListener<T> {
+        let sink = match delivery {
+            Delivery::Queue => Sink::Queue(Mutex::new(VecDeque::new())),
+            Delivery::Sender(sender) => Sink::Sender(sender),
+            Delivery::Callback(callback) => Sink::Callback(Mutex::new(callback)),
         };
-        let queue = Mutex::new(VecDeque::new());
-        RateListener {
-            queue,
-            sync_channel,
-            device_id,
+        ObjectPropertyListener {
+            object_id,
             property_address,
-            rate_listener: None,
+            sink: Box::new(sink),
+            listener: None,
         }
     }
 
     /// Register this listener to receive notifications.
     pub fn register(&mut self) -> Result<(), Error> {
-        unsafe extern "C-unwind" fn rate_listener(
-            device_id: AudioObjectID,
-            _n_addresses: u32,
-            _properties: NonNull<AudioObjectPropertyAddress>,
-            self_ptr: *mut ::std::os::raw::c_void,
+        unsafe extern "C-unwind" fn listener<T: PropertyValue>(
+            object_id: AudioObjectID,
+            n_addresses: u32,
+            properties: NonNull<AudioObjectPropertyAddress>,
+            sink_ptr: *mut ::std::os::raw::c_void,
         ) -> OSStatus {
-            let self_ptr: &mut RateListener = &mut *(self_ptr as *mut RateListener);
-            let mut rate: f64 = 0.0;
-            let data_size = mem::size_of::<f64>() as u32;
-            let property_address = AudioObjectPropertyAddress {
-                mSelector: kAudioDevicePropertyNominalSampleRate,
-                mScope: kAudioObjectPropertyScopeGlobal,
-                mElement: kAudioObjectPropertyElementMaster,
-            };
-            let result = AudioObjectGetPropertyData(
-                device_id,
-                NonNull::from(&property_address),
-                0,
-                null(),
-                NonNull::from(&data_size),
-                NonNull::from(&mut rate).cast(),
-            );
-            if let Some(sender) = &self_ptr.sync_channel {
-                sender.send(rate).unwrap();
-            } else {
-                let mut queue = self_ptr.queue.lock().unwrap();
-                queue.push_back(rate);
+            let sink: &Sink<T> = &*(sink_ptr as *const Sink<T>);
+            let addresses = std::slice::from_raw_parts(properties.as_ptr(), n_addresses as usize);
+            // A failed read must not hold back the values of the other addresses, so the first
+            // error is only reported once they have all been delivered.
+            let mut status = kAudioHardwareNoError as OSStatus;
+            for address in addresses {
+                match T::read(object_id, address) {
+                    // A panic must not unwind into Core Audio, so it is caught and the value dropped.
+                    Ok(value) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(|| sink.deliver(value)));
+                    }
+                    Err(err) if status == kAudioHardwareNoError as OSStatus => {
+                        status = err.as_os_status();
+                    }
+                    Err(_) => (),
+                }
             }
-            result
+            status
         }
 
-        // Add our sample rate change listener callback.
+        if self.listener.is_some() {
+            return Ok(());
+        }
         let status = unsafe {
             AudioObjectAddPropertyListener(
-                self.device_id,
+                self.object_id,
                 NonNull::from(&self.property_address),
-                Some(rate_listener),
-                self as *const _ as *mut _,
+                Some(listener::<T>),
+                self.sink_ptr(),
             )
         };
         Error::from_os_status(status)?;
-        self.rate_listener = Some(rate_listener);
+        self.listener = Some(listener::<T>);
         Ok(())
     }
 
     /// Unregister this listener to stop receiving notifications.
     pub fn unregister(&mut self) -> Result<(), Error> {
-        if self.rate_listener.is_some() {
+        if self.listener.is_some() {
             let status = unsafe {
                 AudioObjectRemovePropertyListener(
-                    self.device_id,
+                    self.object_id,
                     NonNull::from(&self.property_address),
-                    self.rate_listener,
-                    self as *const _ as *mut _,
+                    self.listener,
+                    self.sink_ptr(),
                 )
             };
             Error::from_os_status(status)?;
-            self.rate_listener = None;
+            self.listener = None;
         }
         Ok(())
     }
 
+    /// The address of the property being listened to.
+    pub fn property_address(&self) -> AudioObjectPropertyAddress {
+        self.property_address
+    }
+
+    /// Get the number of values received (equals the number of change events).
+    /// Only used if the ObjectPropertyListener was created with `Delivery::Queue`.
+    pub fn get_nbr_values(&self) -> usize {
+        match *self.sink {
+            Sink::Queue(ref queue) => lock(queue).len(),
+            _ => 0,
+        }
+    }
+
+    /// Get all received values as a Vec. The latest value is the last element.
+    /// This clears the internal buffer.
+    /// Only used if the ObjectPropertyListener was created with `Delivery::Queue`.
+    pub fn drain_values(&mut self) -> Vec<T> {
+        match *self.sink {
+            Sink::Queue(ref queue) => lock(queue).drain(..).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn sink_ptr(&self) -> *mut ::std::os::raw::c_void {
+        &*self.sink as *const Sink<T> as *mut _
+    }
+}
+
+impl<T: PropertyValue + Clone> ObjectPropertyListener<T> {
+    /// Copy all received values to a Vec. The latest value is the last element.
+    /// The internal buffer is preserved.
+    /// Only used if the ObjectPropertyListener was created with `Delivery::Queue`.
+    pub fn copy_values(&self) -> Vec<T> {
+        match *self.sink {
+            Sink::Queue(ref queue) => lock(queue).iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Changing the sample rate is an asynchronous process.
+/// A RateListener can be used to get notified when the rate is changed.
+#[deprecated(note = "use `ObjectPropertyListener::nominal_sample_rate` instead")]
+pub struct RateListener {
+    listener: ObjectPropertyListener<f64>,
+}
+
+#[allow(deprecated)]
+impl RateListener {
+    /// Create a new RateListener for the given AudioDeviceID.
+    /// If an `std::sync::mpsc::Sender` is provided, then events will be pushed to that channel.
+    /// If not, they will instead be stored in an internal queue that will need to be polled.
+    /// The listener must be registered by calling `register()` in order to start receiving notifications.
+    pub fn new(device_id: AudioDeviceID, sync_channel: Option<Sender<f64>>) -> RateListener {
+        let delivery = match sync_channel {
+            Some(sender) => Delivery::Sender(sender),
+            None => Delivery::Queue,
+        };
+        RateListener {
+            listener: ObjectPropertyListener::nominal_sample_rate(device_id, delivery),
+        }
+    }
+
+    /// Register this listener to receive notifications.
+    pub fn register(&mut self) -> Result<(), Error> {
+        self.listener.register()
+    }
+
+    /// Unregister this listener to stop receiving notifications.
+    pub fn unregister(&mut self) -> Result<(), Error> {
+        self.listener.unregister()
+    }
+
     /// Get the number of sample rate values received (equals the number of change events).
     /// Not used if the RateListener was created with a `std::sync::mpsc::Sender`.
     pub fn get_nbr_values(&self) -> usize {
-        self.queue.lock().unwrap().len()
+        self.listener.get_nbr_values()
     }
 
     /// Copy all received values to a Vec. The latest value is the last element.
     /// The internal buffer is preserved.
     /// Not used if the RateListener was created with a `std::sync::mpsc::Sender`.
     pub fn copy_values(&self) -> Vec<f64> {
-        self.queue
-            .lock()
-            .unwrap()
-            .iter()
-            .copied()
-            .collect::<Vec<f64>>()
+        self.listener.copy_values()
     }
 
     /// Get all received values as a Vec. The latest value is the last element.
     /// This clears the internal buffer.
     /// Not used if the RateListener was created with a `std::sync::mpsc::Sender`.
     pub fn drain_values(&mut self) -> Vec<f64> {
-        self.queue.lock().unwrap().drain(..).collect::<Vec<f64>>()
+        self.listener.drain_values()
     }
 }
 
 /// An AliveListener is used to get notified when a device is disconnected.
+#[deprecated(note = "use `ObjectPropertyListener::device_alive` instead")]
 pub struct AliveListener {
-    alive: Box<AtomicBool>,
-    device_id: AudioDeviceID,
-    property_address: AudioObjectPropertyAddress,
-    alive_listener: AudioObjectPropertyListenerProc,
-}
-
-impl Drop for AliveListener {
-    fn drop(&mut self) {
-        let _ = self.unregister();
-    }
+    alive: Arc<AtomicBool>,
+    listener: ObjectPropertyListener<bool>,
 }
 
+#[allow(deprecated)]
 impl AliveListener {
     /// Create a new AliveListener for the given AudioDeviceID.
     /// The listener must be registered by calling `register()` in order to start receiving notifications.
     pub fn new(device_id: AudioDeviceID) -> AliveListener {
-        // Add our listener callback.
-        let property_address = AudioObjectPropertyAddress {
-            mSelector: kAudioDevicePropertyDeviceIsAlive,
-            mScope: kAudioObjectPropertyScopeGlobal,
-            mElement: kAudioObjectPropertyElementMaster,
-        };
+        let alive = Arc::new(AtomicBool::new(true));
+        let flag = alive.clone();
+        let delivery = Delivery::Callback(Box::new(move |is_alive| {
+            flag.store(is_alive, Ordering::SeqCst)
+        }));
         AliveListener {
-            alive: Box::new(AtomicBool::new(true)),
-            device_id,
-            property_address,
-            alive_listener: None,
+            alive,
+            listener: ObjectPropertyListener::device_alive(device_id, delivery),
         }
     }
 
     /// Register this listener to receive notifications.
     pub fn register(&mut self) -> Result<(), Error> {
-        unsafe extern "C-unwind" fn alive_listener(
-            device_id: AudioObjectID,
-            _n_addresses: u32,
-            _properties: NonNull<AudioObjectPropertyAddress>,
-            self_ptr: *mut ::std::os::raw::c_void,
-        ) -> OSStatus {
-            let self_ptr: &mut AliveListener = &mut *(self_ptr as *mut AliveListener);
-            let mut alive: u32 = 0;
-            let data_size = mem::size_of::<u32>() as u32;
-            let property_address = AudioObjectPropertyAddress {
-                mSelector: kAudioDevicePropertyDeviceIsAlive,
-                mScope: kAudioObjectPropertyScopeGlobal,
-                mElement: kAudioObjectPropertyElementMaster,
-            };
-            let result = AudioObjectGetPropertyData(
-                device_id,
-                NonNull::from(&property_address),
-                0,
-                null(),
-                NonNull::from(&data_size),
-                NonNull::from(&mut alive).cast(),
-            );
-            self_ptr.alive.store(alive > 0, Ordering::SeqCst);
-            result
-        }
-
-        // Add our listener callback.
-        let status = unsafe {
-            AudioObjectAddPropertyListener(
-                self.device_id,
-                NonNull::from(&self.property_address),
-                Some(alive_listener),
-                self as *const _ as *mut _,
-            )
-        };
-        Error::from_os_status(status)?;
-        self.alive_listener = Some(alive_listener);
-        Ok(())
+        self.listener.register()
     }
 
     /// Unregister this listener to stop receiving notifications
     pub fn unregister(&mut self) -> Result<(), Error> {
-        if self.alive_listener.is_some() {
-            let status = unsafe {
-                AudioObjectRemovePropertyListener(
-                    self.device_id,
-                    NonNull::from(&self.property_address),
-                    self.alive_listener,
-                    self as *const _ as *mut _,
-                )
-            };
-            Error::from_os_status(status)?;
-            self.alive_listener = None;
-        }
-        Ok(())
+        self.listener.unregister()
     }
 
     /// Check if the device is still alive.
@@ -801,6 +976,127 @@ impl AliveListener {
     }
 }
 
+#[test]
+fn test_property_listener_delivery() {
+    let mut listener = ObjectPropertyListener::nominal_sample_rate(0, Delivery::Queue);
+    listener.sink.deliver(44_100.0);
+    listener.sink.deliver(48_000.0);
+    assert_eq!(listener.get_nbr_values(), 2);
+    assert_eq!(listener.copy_values(), vec![44_100.0, 48_000.0]);
+    assert_eq!(listener.drain_values(), vec![44_100.0, 48_000.0]);
+    assert_eq!(listener.get_nbr_values(), 0);
+
+    let (sender, receiver) = channel();
+    let listener = ObjectPropertyListener::device_alive(0, Delivery::Sender(sender));
+    listener.sink.deliver(false);
+    assert_eq!(receiver.try_recv(), Ok(false));
+    assert_eq!(listener.get_nbr_values(), 0);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_listeners() {
+    let rate_listener = RateListener::new(0, None);
+    rate_listener.listener.sink.deliver(48_000.0);
+    assert_eq!(rate_listener.copy_values(), vec![48_000.0]);
+
+    let alive_listener = AliveListener::new(0);
+    assert!(alive_listener.is_alive());
+    alive_listener.listener.sink.deliver(false);
+    assert!(!alive_listener.is_alive());
+}
+
+/// The address of a global property of a device.
+fn device_property_address(selector: u32) -> AudioObjectPropertyAddress {
+    AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    }
+}
+
+/// The address of a property of a device's input or output streams.
+fn device_scope_property_address(selector: u32, scope: Scope) -> AudioObjectPropertyAddress {
+    let dev_scope: AudioObjectPropertyScope = match scope {
+        Scope::Input => kAudioObjectPropertyScopeInput,
+        Scope::Output => kAudioObjectPropertyScopeOutput,
+        _ => kAudioObjectPropertyScopeGlobal,
+    };
+    AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: dev_scope,
+        mElement: kAudioObjectPropertyElementMaster,
+    }
+}
+
+impl ObjectPropertyListener<f64> {
+    /// Listen to the nominal sample rate of a device.
+    /// Changing the sample rate is an asynchronous process, which this can be used to follow.
+    pub fn nominal_sample_rate(device_id: AudioDeviceID, delivery: Delivery<f64>) -> Self {
+        let address = device_property_address(kAudioDevicePropertyNominalSampleRate);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+}
+
+impl ObjectPropertyListener<bool> {
+    /// Listen to whether a device is alive, which becomes `false` when it is disconnected.
+    pub fn device_alive(device_id: AudioDeviceID, delivery: Delivery<bool>) -> Self {
+        let address = device_property_address(kAudioDevicePropertyDeviceIsAlive);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+
+    /// Listen to whether a jack, such as headphones, is connected to the input or output of a
+    /// device.
+    pub fn jack_connection(
+        device_id: AudioDeviceID,
+        scope: Scope,
+        delivery: Delivery<bool>,
+    ) -> Self {
+        let address = device_scope_property_address(kAudioDevicePropertyJackIsConnected, scope);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+}
+
+impl ObjectPropertyListener<u32> {
+    /// Listen to the default input or output device of the system, delivering the
+    /// `AudioDeviceID` of the new default device.
+    pub fn default_device(input: bool, delivery: Delivery<AudioDeviceID>) -> Self {
+        let selector = match input {
+            false => kAudioHardwarePropertyDefaultOutputDevice,
+            true => kAudioHardwarePropertyDefaultInputDevice,
+        };
+        let address = device_property_address(selector);
+        ObjectPropertyListener::new(kAudioObjectSystemObject as AudioObjectID, address, delivery)
+    }
+
+    /// Listen to the number of frames a device processes per IO cycle.
+    pub fn buffer_size(device_id: AudioDeviceID, delivery: Delivery<u32>) -> Self {
+        let address = device_property_address(kAudioDevicePropertyBufferFrameSize);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+}
+
+impl ObjectPropertyListener<()> {
+    /// Listen to processor overloads of a device, which occur when an IO cycle misses its
+    /// deadline.
+    pub fn overloads(device_id: AudioDeviceID, delivery: Delivery<()>) -> Self {
+        let address = device_property_address(kAudioDeviceProcessorOverload);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+}
+
+impl ObjectPropertyListener<StreamFormat> {
+    /// Listen to the format of the input or output streams of a device.
+    pub fn stream_format(
+        device_id: AudioDeviceID,
+        scope: Scope,
+        delivery: Delivery<StreamFormat>,
+    ) -> Self {
+        let address = device_scope_property_address(kAudioDevicePropertyStreamFormat, scope);
+        ObjectPropertyListener::new(device_id, address, delivery)
+    }
+}
+
 /// Helper for hog mode (exclusive access).
 /// Get the pid of the process that currently owns exclusive access to a device.
 /// A pid value of -1 means no process owns exclusive access.
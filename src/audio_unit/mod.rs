@@ -18,24 +18,38 @@
 //! If you can find documentation on these, please feel free to submit an issue or PR with the
 //! fixes!
 
+#[cfg(target_vendor = "apple")]
 use objc2_audio_toolbox::{
-    kAudioUnitManufacturer_Apple, kAudioUnitProperty_SampleRate, kAudioUnitProperty_StreamFormat,
-    kAudioUnitScope_Global, kAudioUnitScope_Group, kAudioUnitScope_Input, kAudioUnitScope_Layer,
-    kAudioUnitScope_LayerItem, kAudioUnitScope_Note, kAudioUnitScope_Output, kAudioUnitScope_Part,
-    AudioComponentDescription, AudioComponentFindNext, AudioComponentInstanceDispose,
-    AudioComponentInstanceNew, AudioOutputUnitStart, AudioOutputUnitStop,
-    AudioUnit as InnerAudioUnit, AudioUnitGetProperty, AudioUnitInitialize, AudioUnitSetProperty,
-    AudioUnitUninitialize,
+    AudioComponentDescription, AudioComponentFindNext, AudioComponentInstanceNew,
+    AudioUnitGetProperty, AudioUnitSetProperty,
 };
-use objc2_core_audio_types::AudioBufferList;
 
+use self::backend::Backend;
+#[cfg(target_vendor = "apple")]
+use self::backend::CoreAudioBackend;
 use crate::error::Error;
+#[cfg(target_vendor = "apple")]
+use crate::sys::kAudioUnitManufacturer_Apple;
+use crate::sys::{
+    kAudioUnitProperty_SampleRate, kAudioUnitProperty_StreamFormat, kAudioUnitScope_Global,
+    kAudioUnitScope_Group, kAudioUnitScope_Input, kAudioUnitScope_Layer, kAudioUnitScope_LayerItem,
+    kAudioUnitScope_Note, kAudioUnitScope_Output, kAudioUnitScope_Part,
+    AudioUnit as InnerAudioUnit,
+};
+#[cfg(target_vendor = "apple")]
 use std::mem;
+#[cfg(target_vendor = "apple")]
 use std::os::raw::{c_uint, c_void};
+#[cfg(target_vendor = "apple")]
 use std::ptr::{self, NonNull};
+use std::sync::Arc;
 
+pub use self::audio_buffer_list::AudioBufferListBuf;
 pub use self::audio_format::AudioFormat;
-pub use self::sample_format::{Sample, SampleFormat};
+pub use self::sample_format::{
+    BigEndian, Fixed8_24, FixedPoint, I24AlignedHigh, I24AlignedLow, LittleEndian, Sample,
+    SampleFormat, SwapBytes, I24,
+};
 pub use self::stream_format::StreamFormat;
 pub use self::types::{
     EffectType, FormatConverterType, GeneratorType, IOType, MixerType, MusicDeviceType, Type,
@@ -44,9 +58,23 @@ pub use self::types::{
 #[cfg(target_os = "macos")]
 pub mod macos_helpers;
 
+pub mod audio_buffer_list;
 pub mod audio_format;
+mod backend;
+pub mod conversion;
+pub mod duplex;
+pub mod graph;
+pub mod offline;
+pub mod parameter;
+pub mod parameter_types;
+pub mod property;
+pub mod property_listener;
 pub mod render_callback;
+pub mod ring_buffer;
 pub mod sample_format;
+pub mod simulated;
+#[cfg(target_os = "macos")]
+pub mod stream;
 pub mod stream_format;
 pub mod types;
 
@@ -54,7 +82,7 @@ pub mod types;
 ///
 /// More info [here](https://developer.apple.com/library/ios/documentation/AudioUnit/Reference/AudioUnitPropertiesReference/index.html#//apple_ref/doc/constant_group/Audio_Unit_Scopes)
 /// and [here](https://developer.apple.com/library/mac/documentation/MusicAudio/Conceptual/AudioUnitProgrammingGuide/TheAudioUnit/TheAudioUnit.html).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Scope {
     Global = kAudioUnitScope_Global as isize,
     Input = kAudioUnitScope_Input as isize,
@@ -66,13 +94,36 @@ pub enum Scope {
     LayerItem = kAudioUnitScope_LayerItem as isize,
 }
 
-/// Represents the **Input** and **Output** **Element**s.
+/// An **Element** (also known as a bus) of an **AudioUnit**, identified by its index within a
+/// **Scope**.
 ///
-/// These are used when specifying which **Element** we're setting the properties of.
-#[derive(Copy, Clone, Debug)]
-pub enum Element {
-    Output = 0,
-    Input = 1,
+/// These are used when specifying which **Element** we're setting the properties of. I/O units
+/// have an **Output** and an **Input** element, while units such as mixers, mergers and splitters
+/// may have any number of elements, which can be addressed with `Element(n)` or `Element::from(n)`.
+///
+/// Before version 0.14 this was an enum of the two I/O elements. The index that was read with
+/// `element as u32` is now read with `element.0`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Element(pub u32);
+
+#[allow(non_upper_case_globals)]
+impl Element {
+    /// The output element of an I/O unit, which sends audio to the output hardware.
+    pub const Output: Element = Element(0);
+    /// The input element of an I/O unit, which receives audio from the input hardware.
+    pub const Input: Element = Element(1);
+}
+
+impl From<u32> for Element {
+    fn from(index: u32) -> Self {
+        Element(index)
+    }
+}
+
+impl From<Element> for u32 {
+    fn from(element: Element) -> Self {
+        element.0
+    }
 }
 
 /// A rust representation of the [`objc2_audio_toolbox::AudioUnit`], including
@@ -81,13 +132,20 @@ pub enum Element {
 /// Find the original Audio Unit Programming Guide [here](https://developer.apple.com/library/mac/documentation/MusicAudio/Conceptual/AudioUnitProgrammingGuide/TheAudioUnit/TheAudioUnit.html).
 pub struct AudioUnit {
     instance: InnerAudioUnit,
+    backend: Arc<dyn Backend>,
+    unit_type: Option<Type>,
     maybe_render_callback: Option<*mut render_callback::InputProcFnWrapper>,
     maybe_input_callback: Option<InputCallback>,
+    render_notifies: Vec<*mut render_callback::InputProcFnWrapper>,
+    // Notifications that were removed, but may still be running on the render thread, so they are
+    // only freed once the unit is dropped.
+    removed_render_notifies: Vec<*mut render_callback::InputProcFnWrapper>,
+    data_error_policy: render_callback::DataErrorPolicy,
+    callback_panic: render_callback::CallbackPanic,
 }
 
 struct InputCallback {
-    // The audio buffer list to which input data is rendered.
-    buffer_list: *mut AudioBufferList,
+    // The callback, which also owns the audio buffer list to which input data is rendered.
     callback: *mut render_callback::InputProcFnWrapper,
 }
 
@@ -126,16 +184,31 @@ impl AudioUnit {
 
     /// The same as [**AudioUnit::new**](./struct.AudioUnit#method.new) but with the given
     /// component flags and mask.
+    ///
+    /// Outside Apple platforms there are no system audio units to find, so this always returns
+    /// `Error::NoMatchingDefaultAudioUnitFound`.
     pub fn new_with_flags<T>(ty: T, flags: u32, mask: u32) -> Result<AudioUnit, Error>
     where
         T: Into<Type>,
     {
-        const MANUFACTURER_IDENTIFIER: u32 = kAudioUnitManufacturer_Apple;
         let au_type: Type = ty.into();
         let sub_type_u32 = match au_type.as_subtype_u32() {
             Some(u) => u,
             None => return Err(Error::NoKnownSubtype),
         };
+        AudioUnit::instantiate(au_type, sub_type_u32, flags, mask)
+    }
+
+    /// Find the first system audio unit matching the given type and flags and create an
+    /// initialised instance of it.
+    #[cfg(target_vendor = "apple")]
+    fn instantiate(
+        au_type: Type,
+        sub_type_u32: u32,
+        flags: u32,
+        mask: u32,
+    ) -> Result<AudioUnit, Error> {
+        const MANUFACTURER_IDENTIFIER: u32 = kAudioUnitManufacturer_Apple;
 
         // A description of the audio unit we desire.
         let desc = AudioComponentDescription {
@@ -167,17 +240,51 @@ impl AudioUnit {
                 NonNull::from(&mut instance_uninit).cast()
             ));
             let instance: InnerAudioUnit = instance_uninit.assume_init();
+            let backend = Arc::new(CoreAudioBackend::new(instance));
 
             // Initialise the audio unit!
-            try_os_status!(AudioUnitInitialize(instance));
-            Ok(AudioUnit {
-                instance,
-                maybe_render_callback: None,
-                maybe_input_callback: None,
-            })
+            try_os_status!(backend.initialize());
+            Ok(AudioUnit::from_backend(instance, backend, Some(au_type)))
+        }
+    }
+
+    #[cfg(not(target_vendor = "apple"))]
+    fn instantiate(
+        _au_type: Type,
+        _sub_type_u32: u32,
+        _flags: u32,
+        _mask: u32,
+    ) -> Result<AudioUnit, Error> {
+        Err(Error::NoMatchingDefaultAudioUnitFound)
+    }
+
+    /// Wrap the given **Backend**.
+    ///
+    /// `instance` is the raw handle exposed through `AsRef`/`AsMut`. It is null for backends that
+    /// are not driven by Audio Toolbox.
+    fn from_backend(
+        instance: InnerAudioUnit,
+        backend: Arc<dyn Backend>,
+        unit_type: Option<Type>,
+    ) -> AudioUnit {
+        AudioUnit {
+            instance,
+            backend,
+            unit_type,
+            maybe_render_callback: None,
+            maybe_input_callback: None,
+            render_notifies: Vec::new(),
+            removed_render_notifies: Vec::new(),
+            data_error_policy: Default::default(),
+            callback_panic: Default::default(),
         }
     }
 
+    /// The **Type** the unit was created with, or `None` if it is not known.
+    pub fn unit_type(&self) -> Option<Type> {
+        self.unit_type
+    }
+
     /// On successful initialization, the audio formats for input and output are valid
     /// and the audio unit is ready to render. During initialization, an audio unit
     /// allocates memory according to the maximum number of audio frames it can produce
@@ -186,9 +293,7 @@ impl AudioUnit {
     /// Usually, the state of an audio unit (such as its I/O formats and memory allocations)
     /// cannot be changed while an audio unit is initialized.
     pub fn initialize(&mut self) -> Result<(), Error> {
-        unsafe {
-            try_os_status!(AudioUnitInitialize(self.instance));
-        }
+        try_os_status!(self.backend.initialize());
         Ok(())
     }
 
@@ -199,9 +304,7 @@ impl AudioUnit {
     /// After calling this function, you can reconfigure the audio unit and then call
     /// AudioUnitInitialize to reinitialize it.
     pub fn uninitialize(&mut self) -> Result<(), Error> {
-        unsafe {
-            try_os_status!(AudioUnitUninitialize(self.instance));
-        }
+        try_os_status!(self.backend.uninitialize());
         Ok(())
     }
 
@@ -229,7 +332,7 @@ impl AudioUnit {
         elem: Element,
         maybe_data: Option<&T>,
     ) -> Result<(), Error> {
-        unsafe { set_property(self.instance, id, scope, elem, maybe_data) }
+        backend::set_property(&*self.backend, id, scope, elem, maybe_data)
     }
 
     /// Gets the value of an **AudioUnit** property.
@@ -243,7 +346,66 @@ impl AudioUnit {
     /// - **scope**: The audio unit scope for the property.
     /// - **elem**: The audio unit element for the property.
     pub fn get_property<T>(&self, id: u32, scope: Scope, elem: Element) -> Result<T, Error> {
-        unsafe { get_property(self.instance, id, scope, elem) }
+        unsafe { backend::get_property(&*self.backend, id, scope, elem) }
+    }
+
+    /// Gets the size in bytes of an **AudioUnit** property's value and whether it is writable.
+    ///
+    /// This is useful for properties whose values are of variable length.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn property_info(
+        &self,
+        id: u32,
+        scope: Scope,
+        elem: Element,
+    ) -> Result<(usize, bool), Error> {
+        let (size, writable) = backend::get_property_info(&*self.backend, id, scope, elem)?;
+        Ok((size as usize, writable))
+    }
+
+    /// Gets the value of a variable-length **AudioUnit** property as a list of `T`s, such as the
+    /// `AudioUnitParameterID`s of `kAudioUnitProperty_ParameterList`.
+    ///
+    /// The size of the value is queried first, so the returned `Vec` always holds the entire
+    /// value. Returns `Error::PropertySizeMismatch` if that size is not a multiple of the size of
+    /// `T`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_property_vec<T: Copy>(
+        &self,
+        id: u32,
+        scope: Scope,
+        elem: Element,
+    ) -> Result<Vec<T>, Error> {
+        unsafe { backend::get_property_vec(&*self.backend, id, scope, elem) }
+    }
+
+    /// Gets the value of an **AudioUnit** property as raw bytes.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_property_bytes(
+        &self,
+        id: u32,
+        scope: Scope,
+        elem: Element,
+    ) -> Result<Vec<u8>, Error> {
+        self.get_property_vec(id, scope, elem)
     }
 
     /// Starts an I/O **AudioUnit**, which in turn starts the audio unit processing graph that it is
@@ -251,9 +413,7 @@ impl AudioUnit {
     ///
     /// **Available** in OS X v10.0 and later.
     pub fn start(&mut self) -> Result<(), Error> {
-        unsafe {
-            try_os_status!(AudioOutputUnitStart(self.instance));
-        }
+        try_os_status!(self.backend.start());
         Ok(())
     }
 
@@ -262,9 +422,7 @@ impl AudioUnit {
     ///
     /// **Available** in OS X v10.0 and later.
     pub fn stop(&mut self) -> Result<(), Error> {
-        unsafe {
-            try_os_status!(AudioOutputUnitStop(self.instance));
-        }
+        try_os_status!(self.backend.stop());
         Ok(())
     }
 
@@ -341,21 +499,19 @@ unsafe impl Send for AudioUnit {}
 
 impl Drop for AudioUnit {
     fn drop(&mut self) {
-        unsafe {
-            use crate::error;
-
-            // We don't want to panic in `drop`, so we'll ignore returned errors.
-            //
-            // A user should explicitly terminate the `AudioUnit` if they want to handle errors (we
-            // still need to provide a way to actually do that).
-            self.stop().ok();
-            error::Error::from_os_status(AudioUnitUninitialize(self.instance)).ok();
-
-            self.free_render_callback();
-            self.free_input_callback();
-
-            error::Error::from_os_status(AudioComponentInstanceDispose(self.instance)).ok();
-        }
+        // We don't want to panic in `drop`, so we'll ignore returned errors.
+        //
+        // A user should explicitly terminate the `AudioUnit` if they want to handle errors (we
+        // still need to provide a way to actually do that).
+        self.stop().ok();
+        self.uninitialize().ok();
+
+        self.free_render_callback();
+        self.free_input_callback();
+        self.remove_render_notifies();
+
+        // The backend disposes of the underlying instance once the last reference to it is
+        // dropped along with `self`.
     }
 }
 
@@ -384,6 +540,7 @@ impl Drop for AudioUnit {
 /// The caller is responsible for ensuring this.
 /// For a safer alternative, consider using an [AudioUnit] instance
 /// and calling the associated [AudioUnit::set_property] method.
+#[cfg(target_vendor = "apple")]
 pub unsafe fn set_property<T>(
     au: InnerAudioUnit,
     id: u32,
@@ -399,7 +556,7 @@ pub unsafe fn set_property<T>(
         })
         .unwrap_or_else(|| (::std::ptr::null(), 0));
     let scope = scope as c_uint;
-    let elem = elem as c_uint;
+    let elem = elem.0 as c_uint;
     try_os_status!(AudioUnitSetProperty(au, id, scope, elem, data_ptr, size));
     Ok(())
 }
@@ -422,6 +579,7 @@ pub unsafe fn set_property<T>(
 /// The caller is responsible for ensuring this.
 /// For a safer alternative, consider using an [AudioUnit] instance
 /// and calling the associated [AudioUnit::get_property] method.
+#[cfg(target_vendor = "apple")]
 pub unsafe fn get_property<T>(
     au: InnerAudioUnit,
     id: u32,
@@ -429,7 +587,7 @@ pub unsafe fn get_property<T>(
     elem: Element,
 ) -> Result<T, Error> {
     let scope = scope as c_uint;
-    let elem = elem as c_uint;
+    let elem = elem.0 as c_uint;
     let mut size = ::std::mem::size_of::<T>() as u32;
     let mut data_uninit = ::std::mem::MaybeUninit::<T>::uninit();
     let data_ptr = NonNull::from(&mut data_uninit).cast::<c_void>();
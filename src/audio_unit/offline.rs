@@ -0,0 +1,212 @@
+//! Pulling audio from an **AudioUnit** manually, rather than having a device drive it.
+//!
+//! [**render**](../struct.AudioUnit.html#method.render) pulls a single block of audio from the
+//! output element of a unit, as `AudioUnitRender` does. A **GenericOutput** unit at the end of a
+//! chain of units can be rendered this way as fast as the units allow, which
+//! [**render_offline**](../struct.AudioUnit.html#method.render_offline) does for a given duration.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::{AudioUnit, IOType};
+//! use coreaudio::audio_unit::render_callback::{self, data};
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let mut audio_unit = AudioUnit::new(IOType::GenericOutput)?;
+//! type Args = render_callback::Args<data::NonInterleaved<f32>>;
+//! audio_unit.set_render_callback(|mut args: Args| {
+//!     for channel in args.data.channels_mut() {
+//!         channel.fill(0.25);
+//!     }
+//!     Ok(())
+//! })?;
+//! let rendered = audio_unit.render_offline(2.0)?;
+//! assert_eq!(rendered.len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::sys::{kAudioUnitProperty_MaximumFramesPerSlice, AudioUnitRenderActionFlags};
+use crate::sys::{AudioTimeStamp, AudioTimeStampFlags};
+
+use super::audio_format::LinearPcmFlags;
+use super::{AudioBufferListBuf, AudioUnit, Element, Scope, StreamFormat};
+use crate::error::Error;
+use std::mem;
+use std::ptr::NonNull;
+
+/// The number of frames rendered per call when the unit does not report
+/// `kAudioUnitProperty_MaximumFramesPerSlice`.
+const DEFAULT_FRAMES_PER_SLICE: u32 = 512;
+
+/// The number of buffers, and channels in each buffer, of the given format.
+fn buffer_layout(format: &StreamFormat) -> (u32, u32) {
+    if format.flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED) {
+        (format.channels, 1)
+    } else {
+        (1, format.channels)
+    }
+}
+
+/// A time stamp with a valid sample time.
+fn sample_time_stamp(sample_time: f64) -> AudioTimeStamp {
+    let mut time_stamp: AudioTimeStamp = unsafe { mem::zeroed() };
+    time_stamp.mSampleTime = sample_time;
+    time_stamp.mRateScalar = 1.0;
+    time_stamp.mFlags = AudioTimeStampFlags::SampleTimeValid | AudioTimeStampFlags::RateScalarValid;
+    time_stamp
+}
+
+impl AudioUnit {
+    /// Pulls `frames` frames of audio from the output element of the unit into `buffer`.
+    ///
+    /// The buffers of `buffer` are resized to hold `frames` frames of the unit's output stream
+    /// format, which only allocates if their capacity is exceeded. Returns
+    /// `Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat` if `buffer` does not
+    /// have one buffer per channel for a non-interleaved format, or a single buffer otherwise.
+    ///
+    /// `frames` must not exceed the unit's `kAudioUnitProperty_MaximumFramesPerSlice`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **frames**: The number of frames to render.
+    /// - **buffer**: The buffers to render into, e.g. from `AudioBufferListBuf::for_format`.
+    /// - **time_stamp**: The time of the first frame, which should advance by `frames` samples
+    ///   between calls.
+    pub fn render(
+        &mut self,
+        frames: u32,
+        buffer: &mut AudioBufferListBuf,
+        time_stamp: &AudioTimeStamp,
+    ) -> Result<(), Error> {
+        let format = self.stream_format(Scope::Output, Element::Output)?;
+        let (n_buffers, channels_per_buffer) = buffer_layout(&format);
+        let channels_match = buffer
+            .audio_buffers()
+            .iter()
+            .all(|b| b.mNumberChannels == channels_per_buffer);
+        if buffer.len() != n_buffers as usize || !channels_match {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
+        let sample_bytes = format.sample_format.size_in_bytes_with_flags(format.flags) as u32;
+        buffer.resize(frames * sample_bytes * channels_per_buffer);
+
+        let mut flags = AudioUnitRenderActionFlags(0);
+        let status = unsafe {
+            self.backend.render(
+                &mut flags,
+                NonNull::from(time_stamp),
+                Element::Output.0,
+                frames,
+                buffer.as_non_null(),
+            )
+        };
+        Error::from_os_status(status)
+    }
+
+    /// Renders `seconds` seconds of audio from the output element of the unit, as fast as the
+    /// unit allows.
+    ///
+    /// The audio is returned in the unit's output stream format, with one buffer per channel for
+    /// non-interleaved formats. Rendering proceeds in slices of
+    /// `kAudioUnitProperty_MaximumFramesPerSlice` frames, with sample times starting at `0`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **seconds**: The duration to render, at the sample rate of the output stream format.
+    pub fn render_offline(&mut self, seconds: f64) -> Result<AudioBufferListBuf, Error> {
+        let format = self.stream_format(Scope::Output, Element::Output)?;
+        let total_frames = (seconds * format.sample_rate).round() as u32;
+        let max_frames: u32 = self
+            .get_property(
+                kAudioUnitProperty_MaximumFramesPerSlice,
+                Scope::Global,
+                Element::Output,
+            )
+            .unwrap_or(DEFAULT_FRAMES_PER_SLICE);
+        let slice_frames = max_frames.max(1);
+
+        let mut output = AudioBufferListBuf::for_format(&format, total_frames);
+        let mut slice = AudioBufferListBuf::for_format(&format, slice_frames);
+        let (_, channels_per_buffer) = buffer_layout(&format);
+        let frame_bytes = format.sample_format.size_in_bytes_with_flags(format.flags)
+            * channels_per_buffer as usize;
+
+        let mut rendered = 0;
+        while rendered < total_frames {
+            let frames = slice_frames.min(total_frames - rendered);
+            let time_stamp = sample_time_stamp(rendered as f64);
+            self.render(frames, &mut slice, &time_stamp)?;
+            let offset = rendered as usize * frame_bytes;
+            for i in 0..slice.len() {
+                let (src, dst) = (slice.buffer(i), output.buffer_mut(i));
+                if let (Some(src), Some(dst)) = (src, dst) {
+                    dst[offset..offset + src.len()].copy_from_slice(src);
+                }
+            }
+            rendered += frames;
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio_unit::render_callback::{self, data};
+    use crate::audio_unit::simulated::SimulatedConfig;
+    use crate::audio_unit::{AudioUnit, Element, Scope};
+    use crate::sys::kAudioUnitProperty_MaximumFramesPerSlice;
+
+    #[test]
+    fn test_render_offline_is_deterministic() {
+        let config = SimulatedConfig {
+            sample_rate: 1_000.0,
+            realtime: false,
+            ..Default::default()
+        };
+        let mut audio_unit = AudioUnit::new_simulated(config).unwrap();
+        let max_frames: u32 = 300;
+        audio_unit
+            .set_property(
+                kAudioUnitProperty_MaximumFramesPerSlice,
+                Scope::Global,
+                Element::Output,
+                Some(&max_frames),
+            )
+            .unwrap();
+
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_render_callback(|args: Args| {
+                let Args {
+                    time_stamp,
+                    num_frames,
+                    mut data,
+                    ..
+                } = args;
+                assert!(num_frames <= 300);
+                for (i, channel) in data.channels_mut().enumerate() {
+                    for (frame, sample) in channel.iter_mut().enumerate() {
+                        let time = time_stamp.mSampleTime as usize + frame;
+                        *sample = (time * 2 + i) as f32;
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let rendered = audio_unit.render_offline(1.0).unwrap();
+        assert_eq!(rendered.len(), 2);
+        for channel in 0..2 {
+            let bytes = rendered.buffer(channel).unwrap();
+            assert_eq!(bytes.len(), 1_000 * 4);
+            let samples: Vec<f32> = bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            for (time, sample) in samples.iter().enumerate() {
+                assert_eq!(*sample, (time * 2 + channel) as f32);
+            }
+        }
+    }
+}
@@ -0,0 +1,642 @@
+//! Reading, writing and scheduling **AudioUnit** parameters.
+//!
+//! Parameters are the values a unit exposes for realtime control, such as the cutoff frequency of
+//! a filter or the volume of a mixer input. Each is identified by an `AudioUnitParameterID` along
+//! with the **Scope** and **Element** it applies to. The parameters a unit offers may be listed
+//! with [**parameter_list**](../struct.AudioUnit.html#method.parameter_list) and described with
+//! [**parameter_info**](../struct.AudioUnit.html#method.parameter_info).
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::{AudioUnit, Element, EffectType, Scope};
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let mut audio_unit = AudioUnit::new(EffectType::LowPassFilter)?;
+//! for info in audio_unit.parameters(Scope::Global)? {
+//!     println!("{}: {} ({}..={})", info.id, info.name, info.min_value, info.max_value);
+//! }
+//! audio_unit.set_parameter(0, Scope::Global, Element::Output, 2_000.0, 0)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::sys::{
+    kAudioUnitProperty_ParameterInfo, kAudioUnitProperty_ParameterList, AudioUnitParameterInfo,
+    AudioUnitParameterOptions, AudioUnitParameterUnit, CFString,
+};
+#[cfg(target_vendor = "apple")]
+use objc2_core_foundation::CFRetained;
+
+use super::{backend, AudioUnit, Element, Scope};
+use crate::error::Error;
+use std::os::raw::c_uint;
+use std::ptr::NonNull;
+
+/// The `eventType` of an `AudioUnitParameterEvent` that sets a value immediately.
+const EVENT_TYPE_IMMEDIATE: u32 = 1;
+/// The `eventType` of an `AudioUnitParameterEvent` that ramps between two values.
+const EVENT_TYPE_RAMPED: u32 = 2;
+
+/// The `ramp` variant of the `eventValues` union of an `AudioUnitParameterEvent`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct RawRamp {
+    start_buffer_offset: i32,
+    duration_in_frames: u32,
+    start_value: f32,
+    end_value: f32,
+}
+
+/// The `immediate` variant of the `eventValues` union of an `AudioUnitParameterEvent`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct RawImmediate {
+    buffer_offset: u32,
+    value: f32,
+}
+
+/// The `eventValues` union of an `AudioUnitParameterEvent`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) union RawEventValues {
+    ramp: RawRamp,
+    immediate: RawImmediate,
+}
+
+/// The layout of `AudioUnitParameterEvent`, as taken by `AudioUnitScheduleParameters`.
+///
+/// The objc2 bindings do not currently cover this type, so it is declared here.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct RawParameterEvent {
+    scope: c_uint,
+    element: c_uint,
+    parameter: u32,
+    event_type: u32,
+    event_values: RawEventValues,
+}
+
+impl RawParameterEvent {
+    /// The parameter, scope and element the event applies to.
+    pub(crate) fn target(&self) -> (u32, c_uint, c_uint) {
+        (self.parameter, self.scope, self.element)
+    }
+
+    /// The value the parameter holds once the event has completed.
+    pub(crate) fn final_value(&self) -> f32 {
+        unsafe {
+            match self.event_type {
+                EVENT_TYPE_RAMPED => self.event_values.ramp.end_value,
+                _ => self.event_values.immediate.value,
+            }
+        }
+    }
+}
+
+/// A change to a parameter, to be applied during the next render cycle by
+/// [**schedule_parameters**](../struct.AudioUnit.html#method.schedule_parameters).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParameterEvent {
+    /// The ID of the parameter to change.
+    pub id: u32,
+    /// The scope of the parameter.
+    pub scope: Scope,
+    /// The element of the parameter.
+    pub element: Element,
+    /// How the parameter changes.
+    pub kind: ParameterEventKind,
+}
+
+/// How a **ParameterEvent** changes the value of its parameter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParameterEventKind {
+    /// Set the parameter to `value`, `buffer_offset` frames into the next render cycle.
+    Immediate { buffer_offset: u32, value: f32 },
+    /// Move the parameter linearly from `start_value` to `end_value` over `duration_in_frames`
+    /// frames, starting `start_buffer_offset` frames into the next render cycle. A negative
+    /// offset continues a ramp that began in an earlier cycle.
+    ///
+    /// Only parameters with the `CAN_RAMP` flag support ramps.
+    Ramp {
+        start_buffer_offset: i32,
+        duration_in_frames: u32,
+        start_value: f32,
+        end_value: f32,
+    },
+}
+
+impl ParameterEvent {
+    /// Convert the event into the layout taken by `AudioUnitScheduleParameters`.
+    pub(crate) fn to_raw(self) -> RawParameterEvent {
+        let (event_type, event_values) = match self.kind {
+            ParameterEventKind::Immediate {
+                buffer_offset,
+                value,
+            } => (
+                EVENT_TYPE_IMMEDIATE,
+                RawEventValues {
+                    immediate: RawImmediate {
+                        buffer_offset,
+                        value,
+                    },
+                },
+            ),
+            ParameterEventKind::Ramp {
+                start_buffer_offset,
+                duration_in_frames,
+                start_value,
+                end_value,
+            } => (
+                EVENT_TYPE_RAMPED,
+                RawEventValues {
+                    ramp: RawRamp {
+                        start_buffer_offset,
+                        duration_in_frames,
+                        start_value,
+                        end_value,
+                    },
+                },
+            ),
+        };
+        RawParameterEvent {
+            scope: self.scope as c_uint,
+            element: self.element.0 as c_uint,
+            parameter: self.id,
+            event_type,
+            event_values,
+        }
+    }
+}
+
+/// The unit in which the value of a parameter is expressed.
+///
+/// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/audiounitparameterunit).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterUnit {
+    /// A value without a particular unit.
+    Generic,
+    /// An index into a list of named values.
+    Indexed,
+    /// `0.0` for off and `1.0` for on.
+    Boolean,
+    /// A percentage between `0.0` and `100.0`.
+    Percent,
+    /// A duration in seconds.
+    Seconds,
+    /// A number of sample frames.
+    SampleFrames,
+    /// A phase between `-180.0` and `180.0` degrees.
+    Phase,
+    /// A rate multiplier, where `1.0` is the original rate.
+    Rate,
+    /// A frequency in Hz.
+    Hertz,
+    /// An interval in cents, relative to a base pitch.
+    Cents,
+    /// An interval in semitones, relative to a base pitch.
+    RelativeSemiTones,
+    /// A MIDI note number between `0` and `127`.
+    MIDINoteNumber,
+    /// A MIDI controller value between `0` and `127`.
+    MIDIController,
+    /// A level in decibels.
+    Decibels,
+    /// A linear gain between `0.0` and `1.0`.
+    LinearGain,
+    /// An angle in degrees.
+    Degrees,
+    /// An equal power crossfade between two sources, from `0.0` to `100.0`.
+    EqualPowerCrossfade,
+    /// A mixer fader position between `0.0` and `1.0`.
+    MixerFaderCurve1,
+    /// A stereo pan position.
+    Pan,
+    /// A meter level in decibels.
+    Meters,
+    /// A pitch in absolute cents, where `0.0` is MIDI note 0.
+    AbsoluteCents,
+    /// An interval in octaves.
+    Octaves,
+    /// A tempo in beats per minute.
+    BPM,
+    /// A duration in beats.
+    Beats,
+    /// A duration in milliseconds.
+    Milliseconds,
+    /// A ratio between two values.
+    Ratio,
+    /// A unit named by the `unit_name` of the **ParameterInfo**.
+    CustomUnit,
+    /// A MIDI 2.0 controller value.
+    MIDI2Controller,
+}
+
+impl ParameterUnit {
+    /// Create a **ParameterUnit** from an `AudioUnitParameterUnit` value.
+    pub fn from_u32(u: u32) -> Option<ParameterUnit> {
+        let unit = match AudioUnitParameterUnit(u) {
+            AudioUnitParameterUnit::Generic => ParameterUnit::Generic,
+            AudioUnitParameterUnit::Indexed => ParameterUnit::Indexed,
+            AudioUnitParameterUnit::Boolean => ParameterUnit::Boolean,
+            AudioUnitParameterUnit::Percent => ParameterUnit::Percent,
+            AudioUnitParameterUnit::Seconds => ParameterUnit::Seconds,
+            AudioUnitParameterUnit::SampleFrames => ParameterUnit::SampleFrames,
+            AudioUnitParameterUnit::Phase => ParameterUnit::Phase,
+            AudioUnitParameterUnit::Rate => ParameterUnit::Rate,
+            AudioUnitParameterUnit::Hertz => ParameterUnit::Hertz,
+            AudioUnitParameterUnit::Cents => ParameterUnit::Cents,
+            AudioUnitParameterUnit::RelativeSemiTones => ParameterUnit::RelativeSemiTones,
+            AudioUnitParameterUnit::MIDINoteNumber => ParameterUnit::MIDINoteNumber,
+            AudioUnitParameterUnit::MIDIController => ParameterUnit::MIDIController,
+            AudioUnitParameterUnit::Decibels => ParameterUnit::Decibels,
+            AudioUnitParameterUnit::LinearGain => ParameterUnit::LinearGain,
+            AudioUnitParameterUnit::Degrees => ParameterUnit::Degrees,
+            AudioUnitParameterUnit::EqualPowerCrossfade => ParameterUnit::EqualPowerCrossfade,
+            AudioUnitParameterUnit::MixerFaderCurve1 => ParameterUnit::MixerFaderCurve1,
+            AudioUnitParameterUnit::Pan => ParameterUnit::Pan,
+            AudioUnitParameterUnit::Meters => ParameterUnit::Meters,
+            AudioUnitParameterUnit::AbsoluteCents => ParameterUnit::AbsoluteCents,
+            AudioUnitParameterUnit::Octaves => ParameterUnit::Octaves,
+            AudioUnitParameterUnit::BPM => ParameterUnit::BPM,
+            AudioUnitParameterUnit::Beats => ParameterUnit::Beats,
+            AudioUnitParameterUnit::Milliseconds => ParameterUnit::Milliseconds,
+            AudioUnitParameterUnit::Ratio => ParameterUnit::Ratio,
+            AudioUnitParameterUnit::CustomUnit => ParameterUnit::CustomUnit,
+            AudioUnitParameterUnit::MIDI2Controller => ParameterUnit::MIDI2Controller,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+bitflags! {
+    /// Flags describing how a parameter behaves and may be accessed.
+    ///
+    /// The display curve flags other than `DISPLAY_LOGARITHMIC` are not single bits and are
+    /// therefore omitted.
+    ///
+    /// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/audiounitparameteroptions).
+    pub struct ParameterFlags: u32 {
+        /// The host must release the name strings of the `AudioUnitParameterInfo`. This is
+        /// handled when the **ParameterInfo** is created.
+        const CF_NAME_RELEASE = AudioUnitParameterOptions::Flag_CFNameRelease.0;
+        /// The parameter should not be saved in presets.
+        const OMIT_FROM_PRESETS = AudioUnitParameterOptions::Flag_OmitFromPresets.0;
+        /// The history of the parameter's values may be plotted.
+        const PLOT_HISTORY = AudioUnitParameterOptions::Flag_PlotHistory.0;
+        /// The parameter is a read-only meter.
+        const METER_READ_ONLY = AudioUnitParameterOptions::Flag_MeterReadOnly.0;
+        /// The parameter belongs to the clump given by `clump_id`.
+        const HAS_CLUMP = AudioUnitParameterOptions::Flag_HasClump.0;
+        /// The values of the parameter have names.
+        const VALUES_HAVE_STRINGS = AudioUnitParameterOptions::Flag_ValuesHaveStrings.0;
+        /// The parameter should be displayed on a logarithmic scale.
+        const DISPLAY_LOGARITHMIC = AudioUnitParameterOptions::Flag_DisplayLogarithmic.0;
+        /// The parameter is sensitive to small changes.
+        const IS_HIGH_RESOLUTION = AudioUnitParameterOptions::Flag_IsHighResolution.0;
+        /// Changing the parameter is not realtime safe.
+        const NON_REAL_TIME = AudioUnitParameterOptions::Flag_NonRealTime.0;
+        /// The parameter supports ramped **ParameterEvent**s.
+        const CAN_RAMP = AudioUnitParameterOptions::Flag_CanRamp.0;
+        /// The parameter is intended for advanced users.
+        const EXPERT_MODE = AudioUnitParameterOptions::Flag_ExpertMode.0;
+        /// The name of the parameter is given as a `CFString`.
+        const HAS_CF_NAME_STRING = AudioUnitParameterOptions::Flag_HasCFNameString.0;
+        /// Changing the parameter in the global scope changes other parameters.
+        const IS_GLOBAL_META = AudioUnitParameterOptions::Flag_IsGlobalMeta.0;
+        /// Changing the parameter on an element changes other parameters of that element.
+        const IS_ELEMENT_META = AudioUnitParameterOptions::Flag_IsElementMeta.0;
+        /// The parameter may be read.
+        const IS_READABLE = AudioUnitParameterOptions::Flag_IsReadable.0;
+        /// The parameter may be written.
+        const IS_WRITABLE = AudioUnitParameterOptions::Flag_IsWritable.0;
+    }
+}
+
+/// A description of a parameter, as given by `kAudioUnitProperty_ParameterInfo`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterInfo {
+    /// The ID of the parameter.
+    pub id: u32,
+    /// The name of the parameter.
+    pub name: String,
+    /// The unit of the parameter's value, or `None` if the unit is not known to this crate.
+    pub unit: Option<ParameterUnit>,
+    /// The name of the unit when `unit` is **CustomUnit**.
+    pub unit_name: Option<String>,
+    /// The clump the parameter belongs to, if any.
+    pub clump_id: Option<u32>,
+    /// The minimum value of the parameter.
+    pub min_value: f32,
+    /// The maximum value of the parameter.
+    pub max_value: f32,
+    /// The default value of the parameter.
+    pub default_value: f32,
+    /// How the parameter behaves and may be accessed.
+    pub flags: ParameterFlags,
+}
+
+/// Copy the contents of the given `CFString`.
+#[cfg(target_vendor = "apple")]
+unsafe fn cf_string_to_string(string: NonNull<CFString>) -> String {
+    string.as_ref().to_string()
+}
+
+/// Release a `CFString` that was handed over to us.
+#[cfg(target_vendor = "apple")]
+unsafe fn release_cf_string(string: NonNull<CFString>) {
+    drop(CFRetained::from_raw(string));
+}
+
+// There are no `CFString`s outside Apple platforms, so the string pointers of a parameter info are
+// always null and these are never reached.
+#[cfg(not(target_vendor = "apple"))]
+unsafe fn cf_string_to_string(_string: NonNull<CFString>) -> String {
+    unreachable!("`CFString`s only exist on Apple platforms")
+}
+
+#[cfg(not(target_vendor = "apple"))]
+unsafe fn release_cf_string(_string: NonNull<CFString>) {
+    unreachable!("`CFString`s only exist on Apple platforms")
+}
+
+impl ParameterInfo {
+    /// Create a **ParameterInfo** from the `AudioUnitParameterInfo` of the parameter `id`,
+    /// releasing its strings if the `CF_NAME_RELEASE` flag is set.
+    ///
+    /// # Safety
+    ///
+    /// The string pointers of `raw` must be null or point to valid `CFString`s.
+    unsafe fn from_raw(id: u32, raw: &AudioUnitParameterInfo) -> ParameterInfo {
+        let flags = ParameterFlags::from_bits_truncate(raw.flags.0);
+        let unit = ParameterUnit::from_u32(raw.unit.0);
+        let cf_name = NonNull::new(raw.cfNameString as *mut CFString)
+            .filter(|_| flags.contains(ParameterFlags::HAS_CF_NAME_STRING));
+        let unit_name = NonNull::new(raw.unitName as *mut CFString)
+            .filter(|_| unit == Some(ParameterUnit::CustomUnit));
+
+        let name = match cf_name {
+            Some(name) => cf_string_to_string(name),
+            None => {
+                // The fixed-size name is not necessarily nul-terminated when it fills the array.
+                let bytes: Vec<u8> = raw
+                    .name
+                    .iter()
+                    .take_while(|&&c| c != 0)
+                    .map(|&c| c as u8)
+                    .collect();
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+        };
+        let unit_name_string = unit_name.map(|name| cf_string_to_string(name));
+
+        if flags.contains(ParameterFlags::CF_NAME_RELEASE) {
+            for string in cf_name.into_iter().chain(unit_name) {
+                release_cf_string(string);
+            }
+        }
+
+        ParameterInfo {
+            id,
+            name,
+            unit,
+            unit_name: unit_name_string,
+            clump_id: flags
+                .contains(ParameterFlags::HAS_CLUMP)
+                .then_some(raw.clumpID),
+            min_value: raw.minValue,
+            max_value: raw.maxValue,
+            default_value: raw.defaultValue,
+            flags,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// Gets the current value of a parameter.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The ID of the parameter.
+    /// - **scope**: The audio unit scope for the parameter.
+    /// - **elem**: The audio unit element for the parameter.
+    pub fn parameter(&self, id: u32, scope: Scope, elem: Element) -> Result<f32, Error> {
+        let mut value = 0.0;
+        let status = self
+            .backend
+            .get_parameter(id, scope as c_uint, elem.0 as c_uint, &mut value);
+        Error::from_os_status(status)?;
+        Ok(value)
+    }
+
+    /// Sets the value of a parameter.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The ID of the parameter.
+    /// - **scope**: The audio unit scope for the parameter.
+    /// - **elem**: The audio unit element for the parameter.
+    /// - **value**: The new value of the parameter.
+    /// - **buffer_offset**: The number of frames into the next render cycle at which the value
+    ///   takes effect. This should usually be `0`; use
+    ///   [**schedule_parameters**](#method.schedule_parameters) for sample-accurate changes.
+    pub fn set_parameter(
+        &mut self,
+        id: u32,
+        scope: Scope,
+        elem: Element,
+        value: f32,
+        buffer_offset: u32,
+    ) -> Result<(), Error> {
+        let status =
+            self.backend
+                .set_parameter(id, scope as c_uint, elem.0 as c_uint, value, buffer_offset);
+        Error::from_os_status(status)
+    }
+
+    /// Schedules changes to parameters for the next render cycle.
+    ///
+    /// The events are applied at their buffer offsets during the next render, so this should be
+    /// called shortly before it. This allocates, and requires `&mut self`, so it is called from
+    /// the thread that controls the unit rather than from a render callback or notification.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **events**: The changes to make.
+    pub fn schedule_parameters(&mut self, events: &[ParameterEvent]) -> Result<(), Error> {
+        let raw: Vec<RawParameterEvent> = events.iter().map(|event| event.to_raw()).collect();
+        Error::from_os_status(self.backend.schedule_parameters(&raw))
+    }
+
+    /// Schedules a linear ramp of a parameter from `start_value` to `end_value` over
+    /// `duration_in_frames` frames, starting at the beginning of the next render cycle.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The ID of the parameter.
+    /// - **scope**: The audio unit scope for the parameter.
+    /// - **elem**: The audio unit element for the parameter.
+    /// - **start_value**: The value at the start of the ramp.
+    /// - **end_value**: The value at the end of the ramp.
+    /// - **duration_in_frames**: The length of the ramp.
+    pub fn ramp_parameter(
+        &mut self,
+        id: u32,
+        scope: Scope,
+        elem: Element,
+        start_value: f32,
+        end_value: f32,
+        duration_in_frames: u32,
+    ) -> Result<(), Error> {
+        self.schedule_parameters(&[ParameterEvent {
+            id,
+            scope,
+            element: elem,
+            kind: ParameterEventKind::Ramp {
+                start_buffer_offset: 0,
+                duration_in_frames,
+                start_value,
+                end_value,
+            },
+        }])
+    }
+
+    /// Gets the IDs of the parameters in the given scope, via
+    /// `kAudioUnitProperty_ParameterList`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **scope**: The audio unit scope to list the parameters of.
+    pub fn parameter_list(&self, scope: Scope) -> Result<Vec<u32>, Error> {
+        self.get_property_vec(kAudioUnitProperty_ParameterList, scope, Element(0))
+    }
+
+    /// Gets the description of a parameter, via `kAudioUnitProperty_ParameterInfo`.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The ID of the parameter.
+    /// - **scope**: The audio unit scope of the parameter.
+    pub fn parameter_info(&self, id: u32, scope: Scope) -> Result<ParameterInfo, Error> {
+        // The parameter info is addressed by using the parameter ID as the element.
+        let raw: AudioUnitParameterInfo = unsafe {
+            backend::get_property(
+                &*self.backend,
+                kAudioUnitProperty_ParameterInfo,
+                scope,
+                Element(id),
+            )?
+        };
+        Ok(unsafe { ParameterInfo::from_raw(id, &raw) })
+    }
+
+    /// Gets the descriptions of all parameters in the given scope.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **scope**: The audio unit scope to describe the parameters of.
+    pub fn parameters(&self, scope: Scope) -> Result<Vec<ParameterInfo>, Error> {
+        self.parameter_list(scope)?
+            .into_iter()
+            .map(|id| self.parameter_info(id, scope))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::simulated::SimulatedConfig;
+    use crate::error::AudioUnitError;
+    use std::ptr;
+
+    fn parameter_info(name: &str, flags: ParameterFlags) -> AudioUnitParameterInfo {
+        let mut info = AudioUnitParameterInfo {
+            name: [0; 52],
+            unitName: ptr::null(),
+            clumpID: 7,
+            cfNameString: ptr::null(),
+            unit: AudioUnitParameterUnit::Hertz,
+            minValue: 10.0,
+            maxValue: 20_000.0,
+            defaultValue: 1_000.0,
+            flags: AudioUnitParameterOptions(flags.bits()),
+        };
+        for (dst, src) in info.name.iter_mut().zip(name.bytes()) {
+            *dst = src as _;
+        }
+        info
+    }
+
+    #[test]
+    fn test_set_and_schedule_parameters() {
+        let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+        match audio_unit.parameter(0, Scope::Global, Element::Output) {
+            Err(Error::AudioUnit(AudioUnitError::InvalidParameter)) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        audio_unit
+            .set_parameter(0, Scope::Global, Element::Output, 0.5, 0)
+            .unwrap();
+        let value = audio_unit.parameter(0, Scope::Global, Element::Output);
+        assert_eq!(value.unwrap(), 0.5);
+
+        audio_unit
+            .ramp_parameter(0, Scope::Global, Element::Output, 0.5, 1.0, 512)
+            .unwrap();
+        let value = audio_unit.parameter(0, Scope::Global, Element::Output);
+        assert_eq!(value.unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_parameter_info() {
+        let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+        let ids: [u32; 2] = [3, 9];
+        audio_unit
+            .set_property(
+                kAudioUnitProperty_ParameterList,
+                Scope::Global,
+                Element(0),
+                Some(&ids),
+            )
+            .unwrap();
+        let flags = ParameterFlags::IS_READABLE
+            | ParameterFlags::IS_WRITABLE
+            | ParameterFlags::CAN_RAMP
+            | ParameterFlags::HAS_CLUMP;
+        let cutoff = parameter_info("Cutoff Frequency", flags);
+        let resonance = parameter_info("Resonance", ParameterFlags::IS_READABLE);
+        for (id, info) in ids.iter().zip(&[cutoff, resonance]) {
+            audio_unit
+                .set_property(
+                    kAudioUnitProperty_ParameterInfo,
+                    Scope::Global,
+                    Element(*id),
+                    Some(info),
+                )
+                .unwrap();
+        }
+
+        let infos = audio_unit.parameters(Scope::Global).unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id, 3);
+        assert_eq!(infos[0].name, "Cutoff Frequency");
+        assert_eq!(infos[0].unit, Some(ParameterUnit::Hertz));
+        assert_eq!(infos[0].unit_name, None);
+        assert_eq!(infos[0].clump_id, Some(7));
+        assert_eq!(infos[0].min_value, 10.0);
+        assert_eq!(infos[0].max_value, 20_000.0);
+        assert_eq!(infos[0].default_value, 1_000.0);
+        assert_eq!(infos[0].flags, flags);
+        assert_eq!(infos[1].id, 9);
+        assert_eq!(infos[1].name, "Resonance");
+        assert_eq!(infos[1].clump_id, None);
+    }
+}
@@ -0,0 +1,633 @@
+//! Typed parameter IDs for the effect and mixer **AudioUnit**s provided by Apple.
+//!
+//! Each enum lists the parameters of one unit **Type**, along with the scope, unit and range of
+//! each parameter as documented by Apple. Ranges that depend on the sample rate are given in terms
+//! of it. The live range of any parameter may be queried with
+//! [**parameter_info**](../struct.AudioUnit.html#method.parameter_info).
+//!
+//! Passing a parameter to [**set_typed_parameter**](../struct.AudioUnit.html#method.set_typed_parameter)
+//! checks that it belongs to the **Type** the unit was created with, so that the parameter IDs of
+//! one unit are not accidentally applied to another.
+//!
+//! The **AUFilter**, **NetSend** and **RogerBeep** effects do not publish their parameter IDs and
+//! are not covered.
+//!
+//! Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/audio-unit-parameters).
+
+#![allow(deprecated)]
+
+use crate::sys::{
+    k3DMixerParam_Azimuth, k3DMixerParam_BusEnable, k3DMixerParam_Distance,
+    k3DMixerParam_DryWetReverbBlend, k3DMixerParam_Elevation, k3DMixerParam_Gain,
+    k3DMixerParam_GlobalReverbGainInDecibels, k3DMixerParam_MaxGainInDecibels,
+    k3DMixerParam_MinGainInDecibels, k3DMixerParam_ObstructionAttenuationInDecibels,
+    k3DMixerParam_OcclusionAttenuationInDecibels, k3DMixerParam_PlaybackRate,
+    k3DMixerParam_PostAveragePower, k3DMixerParam_PostPeakHoldLevel, k3DMixerParam_PreAveragePower,
+    k3DMixerParam_PrePeakHoldLevel, kAULowShelfParam_CutoffFrequency, kAULowShelfParam_Gain,
+    kAUNBandEQParam_Bandwidth, kAUNBandEQParam_BypassBand, kAUNBandEQParam_FilterType,
+    kAUNBandEQParam_Frequency, kAUNBandEQParam_Gain, kAUNBandEQParam_GlobalGain,
+    kBandpassParam_Bandwidth, kBandpassParam_CenterFrequency, kDelayParam_DelayTime,
+    kDelayParam_Feedback, kDelayParam_LopassCutoff, kDelayParam_WetDryMix,
+    kDistortionParam_CubicTerm, kDistortionParam_Decay, kDistortionParam_Decimation,
+    kDistortionParam_DecimationMix, kDistortionParam_Delay, kDistortionParam_DelayMix,
+    kDistortionParam_FinalMix, kDistortionParam_LinearTerm, kDistortionParam_PolynomialMix,
+    kDistortionParam_RingModBalance, kDistortionParam_RingModFreq1, kDistortionParam_RingModFreq2,
+    kDistortionParam_RingModMix, kDistortionParam_Rounding, kDistortionParam_SoftClipGain,
+    kDistortionParam_SquaredTerm, kDynamicsProcessorParam_AttackTime,
+    kDynamicsProcessorParam_CompressionAmount, kDynamicsProcessorParam_ExpansionRatio,
+    kDynamicsProcessorParam_ExpansionThreshold, kDynamicsProcessorParam_HeadRoom,
+    kDynamicsProcessorParam_InputAmplitude, kDynamicsProcessorParam_OutputAmplitude,
+    kDynamicsProcessorParam_OverallGain, kDynamicsProcessorParam_ReleaseTime,
+    kDynamicsProcessorParam_Threshold, kGraphicEQParam_NumberOfBands,
+    kHighShelfParam_CutOffFrequency, kHighShelfParam_Gain, kHipassParam_CutoffFrequency,
+    kHipassParam_Resonance, kLimiterParam_AttackTime, kLimiterParam_DecayTime,
+    kLimiterParam_PreGain, kLowPassParam_CutoffFrequency, kLowPassParam_Resonance,
+    kMatrixMixerParam_Enable, kMatrixMixerParam_PostAveragePower,
+    kMatrixMixerParam_PostPeakHoldLevel, kMatrixMixerParam_PreAveragePower,
+    kMatrixMixerParam_PrePeakHoldLevel, kMatrixMixerParam_Volume, kMultiChannelMixerParam_Enable,
+    kMultiChannelMixerParam_Pan, kMultiChannelMixerParam_PostAveragePower,
+    kMultiChannelMixerParam_PostPeakHoldLevel, kMultiChannelMixerParam_PreAveragePower,
+    kMultiChannelMixerParam_PrePeakHoldLevel, kMultiChannelMixerParam_Volume,
+    kMultibandCompressorParam_AttackTime, kMultibandCompressorParam_Crossover1,
+    kMultibandCompressorParam_Crossover2, kMultibandCompressorParam_Crossover3,
+    kMultibandCompressorParam_EQ1, kMultibandCompressorParam_EQ2, kMultibandCompressorParam_EQ3,
+    kMultibandCompressorParam_EQ4, kMultibandCompressorParam_Headroom1,
+    kMultibandCompressorParam_Headroom2, kMultibandCompressorParam_Headroom3,
+    kMultibandCompressorParam_Headroom4, kMultibandCompressorParam_Postgain,
+    kMultibandCompressorParam_Pregain, kMultibandCompressorParam_ReleaseTime,
+    kMultibandCompressorParam_Threshold1, kMultibandCompressorParam_Threshold2,
+    kMultibandCompressorParam_Threshold3, kMultibandCompressorParam_Threshold4,
+    kParametricEQParam_CenterFreq, kParametricEQParam_Gain, kParametricEQParam_Q,
+    kReverbParam_DryWetMix, kReverbParam_FilterBandwidth, kReverbParam_FilterEnable,
+    kReverbParam_FilterFrequency, kReverbParam_FilterGain, kReverbParam_FilterType,
+    kReverbParam_LargeBrightness, kReverbParam_LargeDelay, kReverbParam_LargeDelayRange,
+    kReverbParam_LargeDensity, kReverbParam_LargeSize, kReverbParam_ModulationDepth,
+    kReverbParam_ModulationRate, kReverbParam_PreDelay, kReverbParam_SmallBrightness,
+    kReverbParam_SmallDelayRange, kReverbParam_SmallDensity, kReverbParam_SmallLargeMix,
+    kReverbParam_SmallSize, kSampleDelayParam_DelayFrames, kTimePitchParam_EffectBlend,
+    kTimePitchParam_Pitch, kTimePitchParam_Rate,
+};
+
+use super::{AudioUnit, EffectType, Element, MixerType, Scope, Type};
+use crate::error::Error;
+
+/// A parameter of a particular **Type** of **AudioUnit**.
+pub trait UnitParameter: Copy {
+    /// The **Type** of unit that has this parameter.
+    const UNIT_TYPE: Type;
+    /// The `AudioUnitParameterID` of the parameter.
+    fn id(self) -> u32;
+}
+
+/// Implement **UnitParameter** for an enum whose discriminants are the parameter IDs.
+macro_rules! impl_unit_parameter {
+    ($Name:ident, $ty:expr) => {
+        impl UnitParameter for $Name {
+            const UNIT_TYPE: Type = $ty;
+            fn id(self) -> u32 {
+                self as u32
+            }
+        }
+    };
+}
+
+/// Parameters of the **LowPassFilter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LowPassFilterParameter {
+    /// Global, Hz, 10 -> sample rate / 2, default 6900.
+    CutoffFrequency = kLowPassParam_CutoffFrequency as isize,
+    /// Global, dB, -20 -> 40, default 0.
+    Resonance = kLowPassParam_Resonance as isize,
+}
+impl_unit_parameter!(
+    LowPassFilterParameter,
+    Type::Effect(EffectType::LowPassFilter)
+);
+
+/// Parameters of the **HighPassFilter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HighPassFilterParameter {
+    /// Global, Hz, 10 -> sample rate / 2, default 6900.
+    CutoffFrequency = kHipassParam_CutoffFrequency as isize,
+    /// Global, dB, -20 -> 40, default 0.
+    Resonance = kHipassParam_Resonance as isize,
+}
+impl_unit_parameter!(
+    HighPassFilterParameter,
+    Type::Effect(EffectType::HighPassFilter)
+);
+
+/// Parameters of the **BandPassFilter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BandPassFilterParameter {
+    /// Global, Hz, 20 -> sample rate / 2, default 5000.
+    CenterFrequency = kBandpassParam_CenterFrequency as isize,
+    /// Global, cents, 100 -> 12000, default 600.
+    Bandwidth = kBandpassParam_Bandwidth as isize,
+}
+impl_unit_parameter!(
+    BandPassFilterParameter,
+    Type::Effect(EffectType::BandPassFilter)
+);
+
+/// Parameters of the **HighShelfFilter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HighShelfFilterParameter {
+    /// Global, Hz, 10000 -> sample rate / 2, default 10000.
+    CutoffFrequency = kHighShelfParam_CutOffFrequency as isize,
+    /// Global, dB, -40 -> 40, default 0.
+    Gain = kHighShelfParam_Gain as isize,
+}
+impl_unit_parameter!(
+    HighShelfFilterParameter,
+    Type::Effect(EffectType::HighShelfFilter)
+);
+
+/// Parameters of the **LowShelfFilter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LowShelfFilterParameter {
+    /// Global, Hz, 10 -> 200, default 80.
+    CutoffFrequency = kAULowShelfParam_CutoffFrequency as isize,
+    /// Global, dB, -40 -> 40, default 0.
+    Gain = kAULowShelfParam_Gain as isize,
+}
+impl_unit_parameter!(
+    LowShelfFilterParameter,
+    Type::Effect(EffectType::LowShelfFilter)
+);
+
+/// Parameters of the **ParametricEQ** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParametricEQParameter {
+    /// Global, Hz, 20 -> sample rate / 2, default 2000.
+    CenterFrequency = kParametricEQParam_CenterFreq as isize,
+    /// Global, generic, 0.1 -> 20, default 1.0.
+    Q = kParametricEQParam_Q as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    Gain = kParametricEQParam_Gain as isize,
+}
+impl_unit_parameter!(
+    ParametricEQParameter,
+    Type::Effect(EffectType::ParametricEQ)
+);
+
+/// Parameters of the **PeakLimiter** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeakLimiterParameter {
+    /// Global, seconds, 0.001 -> 0.03, default 0.012.
+    AttackTime = kLimiterParam_AttackTime as isize,
+    /// Global, seconds, 0.001 -> 0.06, default 0.024.
+    DecayTime = kLimiterParam_DecayTime as isize,
+    /// Global, dB, -40 -> 40, default 0.
+    PreGain = kLimiterParam_PreGain as isize,
+}
+impl_unit_parameter!(PeakLimiterParameter, Type::Effect(EffectType::PeakLimiter));
+
+/// Parameters of the **DynamicsProcessor** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DynamicsProcessorParameter {
+    /// Global, dB, -40 -> 20, default -20.
+    Threshold = kDynamicsProcessorParam_Threshold as isize,
+    /// Global, dB, 0.1 -> 40, default 5.
+    HeadRoom = kDynamicsProcessorParam_HeadRoom as isize,
+    /// Global, rate, 1 -> 50, default 2.
+    ExpansionRatio = kDynamicsProcessorParam_ExpansionRatio as isize,
+    /// Global, dB.
+    ExpansionThreshold = kDynamicsProcessorParam_ExpansionThreshold as isize,
+    /// Global, seconds, 0.0001 -> 0.2, default 0.001.
+    AttackTime = kDynamicsProcessorParam_AttackTime as isize,
+    /// Global, seconds, 0.01 -> 3, default 0.05.
+    ReleaseTime = kDynamicsProcessorParam_ReleaseTime as isize,
+    /// Global, dB, -40 -> 40, default 0.
+    OverallGain = kDynamicsProcessorParam_OverallGain as isize,
+    /// Global, dB, read-only.
+    CompressionAmount = kDynamicsProcessorParam_CompressionAmount as isize,
+    /// Global, dB, read-only.
+    InputAmplitude = kDynamicsProcessorParam_InputAmplitude as isize,
+    /// Global, dB, read-only.
+    OutputAmplitude = kDynamicsProcessorParam_OutputAmplitude as isize,
+}
+impl_unit_parameter!(
+    DynamicsProcessorParameter,
+    Type::Effect(EffectType::DynamicsProcessor)
+);
+
+/// Parameters of the **Distortion** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DistortionParameter {
+    /// Global, milliseconds, 0.1 -> 500, default 0.1.
+    Delay = kDistortionParam_Delay as isize,
+    /// Global, rate, 0.1 -> 50, default 1.0.
+    Decay = kDistortionParam_Decay as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    DelayMix = kDistortionParam_DelayMix as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    Decimation = kDistortionParam_Decimation as isize,
+    /// Global, percent, 0 -> 100, default 0.
+    Rounding = kDistortionParam_Rounding as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    DecimationMix = kDistortionParam_DecimationMix as isize,
+    /// Global, linear gain, 0 -> 1, default 1.
+    LinearTerm = kDistortionParam_LinearTerm as isize,
+    /// Global, linear gain, 0 -> 20, default 0.
+    SquaredTerm = kDistortionParam_SquaredTerm as isize,
+    /// Global, linear gain, 0 -> 20, default 0.
+    CubicTerm = kDistortionParam_CubicTerm as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    PolynomialMix = kDistortionParam_PolynomialMix as isize,
+    /// Global, Hz, 0.5 -> 8000, default 100.
+    RingModFreq1 = kDistortionParam_RingModFreq1 as isize,
+    /// Global, Hz, 0.5 -> 8000, default 100.
+    RingModFreq2 = kDistortionParam_RingModFreq2 as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    RingModBalance = kDistortionParam_RingModBalance as isize,
+    /// Global, percent, 0 -> 100, default 0.
+    RingModMix = kDistortionParam_RingModMix as isize,
+    /// Global, dB, -80 -> 20, default -6.
+    SoftClipGain = kDistortionParam_SoftClipGain as isize,
+    /// Global, percent, 0 -> 100, default 50.
+    FinalMix = kDistortionParam_FinalMix as isize,
+}
+impl_unit_parameter!(DistortionParameter, Type::Effect(EffectType::Distortion));
+
+/// Parameters of the **Delay** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DelayParameter {
+    /// Global, equal power crossfade, 0 -> 100, default 50.
+    WetDryMix = kDelayParam_WetDryMix as isize,
+    /// Global, seconds, 0 -> 2, default 1.
+    DelayTime = kDelayParam_DelayTime as isize,
+    /// Global, percent, -100 -> 100, default 50.
+    Feedback = kDelayParam_Feedback as isize,
+    /// Global, Hz, 10 -> sample rate / 2, default 15000.
+    LopassCutoff = kDelayParam_LopassCutoff as isize,
+}
+impl_unit_parameter!(DelayParameter, Type::Effect(EffectType::Delay));
+
+/// Parameters of the **SampleDelay** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SampleDelayParameter {
+    /// Global, sample frames, 0 -> 512, default 0.
+    DelayFrames = kSampleDelayParam_DelayFrames as isize,
+}
+impl_unit_parameter!(SampleDelayParameter, Type::Effect(EffectType::SampleDelay));
+
+/// Parameters of the **MultiBandCompressor** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MultiBandCompressorParameter {
+    /// Global, dB, -40 -> 40, default 0.
+    Pregain = kMultibandCompressorParam_Pregain as isize,
+    /// Global, dB, -40 -> 40, default 0.
+    Postgain = kMultibandCompressorParam_Postgain as isize,
+    /// Global, Hz, 20 -> sample rate / 2, default 120.
+    Crossover1 = kMultibandCompressorParam_Crossover1 as isize,
+    /// Global, Hz, 20 -> sample rate / 2, default 700.
+    Crossover2 = kMultibandCompressorParam_Crossover2 as isize,
+    /// Global, Hz, 20 -> sample rate / 2, default 3000.
+    Crossover3 = kMultibandCompressorParam_Crossover3 as isize,
+    /// Global, dB, -100 -> 0, default -22.
+    Threshold1 = kMultibandCompressorParam_Threshold1 as isize,
+    /// Global, dB, -100 -> 0, default -32.
+    Threshold2 = kMultibandCompressorParam_Threshold2 as isize,
+    /// Global, dB, -100 -> 0, default -33.
+    Threshold3 = kMultibandCompressorParam_Threshold3 as isize,
+    /// Global, dB, -100 -> 0, default -36.
+    Threshold4 = kMultibandCompressorParam_Threshold4 as isize,
+    /// Global, dB, 0.1 -> 40, default 5.
+    Headroom1 = kMultibandCompressorParam_Headroom1 as isize,
+    /// Global, dB, 0.1 -> 40, default 12.
+    Headroom2 = kMultibandCompressorParam_Headroom2 as isize,
+    /// Global, dB, 0.1 -> 40, default 5.
+    Headroom3 = kMultibandCompressorParam_Headroom3 as isize,
+    /// Global, dB, 0.1 -> 40, default 7.5.
+    Headroom4 = kMultibandCompressorParam_Headroom4 as isize,
+    /// Global, seconds, 0.001 -> 0.2, default 0.08.
+    AttackTime = kMultibandCompressorParam_AttackTime as isize,
+    /// Global, seconds, 0.01 -> 3, default 0.12.
+    ReleaseTime = kMultibandCompressorParam_ReleaseTime as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    EQ1 = kMultibandCompressorParam_EQ1 as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    EQ2 = kMultibandCompressorParam_EQ2 as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    EQ3 = kMultibandCompressorParam_EQ3 as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    EQ4 = kMultibandCompressorParam_EQ4 as isize,
+}
+impl_unit_parameter!(
+    MultiBandCompressorParameter,
+    Type::Effect(EffectType::MultiBandCompressor)
+);
+
+/// Parameters of the **MatrixReverb** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MatrixReverbParameter {
+    /// Global, equal power crossfade, 0 -> 100, default 100.
+    DryWetMix = kReverbParam_DryWetMix as isize,
+    /// Global, equal power crossfade, 0 -> 100, default 50.
+    SmallLargeMix = kReverbParam_SmallLargeMix as isize,
+    /// Global, seconds, 0.005 -> 0.020.
+    SmallSize = kReverbParam_SmallSize as isize,
+    /// Global, seconds, 0.4 -> 10, default 3.07.
+    LargeSize = kReverbParam_LargeSize as isize,
+    /// Global, seconds, 0.001 -> 0.03, default 0.025.
+    PreDelay = kReverbParam_PreDelay as isize,
+    /// Global, seconds, 0.001 -> 0.1, default 0.035.
+    LargeDelay = kReverbParam_LargeDelay as isize,
+    /// Global, generic, 0 -> 1, default 0.28.
+    SmallDensity = kReverbParam_SmallDensity as isize,
+    /// Global, generic, 0 -> 1, default 0.82.
+    LargeDensity = kReverbParam_LargeDensity as isize,
+    /// Global, generic, 0 -> 1, default 0.3.
+    LargeDelayRange = kReverbParam_LargeDelayRange as isize,
+    /// Global, generic, 0.1 -> 1, default 0.96.
+    SmallBrightness = kReverbParam_SmallBrightness as isize,
+    /// Global, generic, 0.1 -> 1, default 0.49.
+    LargeBrightness = kReverbParam_LargeBrightness as isize,
+    /// Global, generic, 0 -> 1, default 0.5.
+    SmallDelayRange = kReverbParam_SmallDelayRange as isize,
+    /// Global, Hz, 0.001 -> 2, default 1.
+    ModulationRate = kReverbParam_ModulationRate as isize,
+    /// Global, generic, 0 -> 1, default 0.2.
+    ModulationDepth = kReverbParam_ModulationDepth as isize,
+    /// Global, Hz, 10 -> sample rate / 2, default 800.
+    FilterFrequency = kReverbParam_FilterFrequency as isize,
+    /// Global, octaves, 0.05 -> 4, default 3.
+    FilterBandwidth = kReverbParam_FilterBandwidth as isize,
+    /// Global, dB, -18 -> 18, default 0.
+    FilterGain = kReverbParam_FilterGain as isize,
+    /// Global, indexed.
+    FilterType = kReverbParam_FilterType as isize,
+    /// Global, boolean, default 0.
+    FilterEnable = kReverbParam_FilterEnable as isize,
+}
+impl_unit_parameter!(
+    MatrixReverbParameter,
+    Type::Effect(EffectType::MatrixReverb)
+);
+
+/// Parameters of the **Pitch** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PitchParameter {
+    /// Global, rate, 1/32 -> 32, default 1.
+    Rate = kTimePitchParam_Rate as isize,
+    /// Global, cents, -2400 -> 2400, default 0.
+    Pitch = kTimePitchParam_Pitch as isize,
+    /// Global, percent, 0 -> 100.
+    EffectBlend = kTimePitchParam_EffectBlend as isize,
+}
+impl_unit_parameter!(PitchParameter, Type::Effect(EffectType::Pitch));
+
+/// Parameters of the **GraphicEQ** effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GraphicEQParameter {
+    /// Global, indexed, `0` for 10 bands or `1` for 31 bands, default 0.
+    NumberOfBands,
+    /// Global, dB, the gain of the band with the given index.
+    BandGain(u32),
+}
+
+impl UnitParameter for GraphicEQParameter {
+    const UNIT_TYPE: Type = Type::Effect(EffectType::GraphicEQ);
+    fn id(self) -> u32 {
+        match self {
+            GraphicEQParameter::NumberOfBands => kGraphicEQParam_NumberOfBands,
+            GraphicEQParameter::BandGain(band) => band,
+        }
+    }
+}
+
+/// Parameters of the **NBandEQ** effect. The band-specific parameters take the index of the band.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NBandEQParameter {
+    /// Global, dB, -96 -> 24, default 0.
+    GlobalGain,
+    /// Global, boolean, default 1.
+    BypassBand(u32),
+    /// Global, indexed, 0 -> 10, default 0 (parametric).
+    FilterType(u32),
+    /// Global, Hz, 20 -> sample rate / 2.
+    Frequency(u32),
+    /// Global, dB, -96 -> 24, default 0.
+    Gain(u32),
+    /// Global, octaves, 0.05 -> 5, default 0.5.
+    Bandwidth(u32),
+}
+
+impl UnitParameter for NBandEQParameter {
+    const UNIT_TYPE: Type = Type::Effect(EffectType::NBandEQ);
+    fn id(self) -> u32 {
+        match self {
+            NBandEQParameter::GlobalGain => kAUNBandEQParam_GlobalGain,
+            NBandEQParameter::BypassBand(band) => kAUNBandEQParam_BypassBand + band,
+            NBandEQParameter::FilterType(band) => kAUNBandEQParam_FilterType + band,
+            NBandEQParameter::Frequency(band) => kAUNBandEQParam_Frequency + band,
+            NBandEQParameter::Gain(band) => kAUNBandEQParam_Gain + band,
+            NBandEQParameter::Bandwidth(band) => kAUNBandEQParam_Bandwidth + band,
+        }
+    }
+}
+
+/// Parameters of the **MultiChannelMixer**.
+///
+/// The metering parameters require metering to be enabled with
+/// `kAudioUnitProperty_MeteringMode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MultiChannelMixerParameter {
+    /// Input or Output, linear gain, 0 -> 1, default 1.
+    Volume = kMultiChannelMixerParam_Volume as isize,
+    /// Input or Output, boolean, default 1.
+    Enable = kMultiChannelMixerParam_Enable as isize,
+    /// Input or Output, pan, -1 -> 1, default 0.
+    Pan = kMultiChannelMixerParam_Pan as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PreAveragePower = kMultiChannelMixerParam_PreAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PrePeakHoldLevel = kMultiChannelMixerParam_PrePeakHoldLevel as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostAveragePower = kMultiChannelMixerParam_PostAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostPeakHoldLevel = kMultiChannelMixerParam_PostPeakHoldLevel as isize,
+}
+impl_unit_parameter!(
+    MultiChannelMixerParameter,
+    Type::Mixer(MixerType::MultiChannelMixer)
+);
+
+/// Parameters of the **StereoMixer**.
+///
+/// These IDs are not currently covered by the objc2 bindings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StereoMixerParameter {
+    /// Input or Output, linear gain, 0 -> 1, default 1.
+    Volume = 0,
+    /// Input, pan, 0 (left) -> 1 (right), default 0.5.
+    Pan = 1,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PreAveragePower = 1000,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PrePeakHoldLevel = 2000,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostAveragePower = 3000,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostPeakHoldLevel = 4000,
+}
+impl_unit_parameter!(StereoMixerParameter, Type::Mixer(MixerType::StereoMixer));
+
+/// Parameters of the deprecated **Mixer3D**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Mixer3DParameter {
+    /// Input, degrees, -180 -> 180, default 0.
+    Azimuth = k3DMixerParam_Azimuth as isize,
+    /// Input, degrees, -90 -> 90, default 0.
+    Elevation = k3DMixerParam_Elevation as isize,
+    /// Input, meters, 0 -> 10000, default 0.
+    Distance = k3DMixerParam_Distance as isize,
+    /// Input or Output, dB, -120 -> 20, default 0.
+    Gain = k3DMixerParam_Gain as isize,
+    /// Input, rate, 0.5 -> 2, default 1.
+    PlaybackRate = k3DMixerParam_PlaybackRate as isize,
+    /// Input, boolean, default 1.
+    BusEnable = k3DMixerParam_BusEnable as isize,
+    /// Input, dB, -100 -> 0, default -100.
+    MinGainInDecibels = k3DMixerParam_MinGainInDecibels as isize,
+    /// Input, dB, -100 -> 0, default 0.
+    MaxGainInDecibels = k3DMixerParam_MaxGainInDecibels as isize,
+    /// Input, percent, 0 -> 100, default 0.
+    DryWetReverbBlend = k3DMixerParam_DryWetReverbBlend as isize,
+    /// Global, dB, -20 -> 20, default 0.
+    GlobalReverbGainInDecibels = k3DMixerParam_GlobalReverbGainInDecibels as isize,
+    /// Input, dB, -100 -> 0, default 0.
+    OcclusionAttenuationInDecibels = k3DMixerParam_OcclusionAttenuationInDecibels as isize,
+    /// Input, dB, -100 -> 0, default 0.
+    ObstructionAttenuationInDecibels = k3DMixerParam_ObstructionAttenuationInDecibels as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PreAveragePower = k3DMixerParam_PreAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PrePeakHoldLevel = k3DMixerParam_PrePeakHoldLevel as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostAveragePower = k3DMixerParam_PostAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostPeakHoldLevel = k3DMixerParam_PostPeakHoldLevel as isize,
+}
+impl_unit_parameter!(Mixer3DParameter, Type::Mixer(MixerType::Mixer3D));
+
+/// Parameters of the **MatrixMixer**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MatrixMixerParameter {
+    /// Linear gain, 0 -> 1. In the Input and Output scopes this is the volume of a channel. In
+    /// the Global scope the element is `(input channel << 16) | output channel` for a crosspoint,
+    /// or `0xFFFFFFFF` for the master volume.
+    Volume = kMatrixMixerParam_Volume as isize,
+    /// Input or Output, boolean, default 1.
+    Enable = kMatrixMixerParam_Enable as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PreAveragePower = kMatrixMixerParam_PreAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PrePeakHoldLevel = kMatrixMixerParam_PrePeakHoldLevel as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostAveragePower = kMatrixMixerParam_PostAveragePower as isize,
+    /// Input or Output, dB, -120 -> 0, read-only.
+    PostPeakHoldLevel = kMatrixMixerParam_PostPeakHoldLevel as isize,
+}
+impl_unit_parameter!(MatrixMixerParameter, Type::Mixer(MixerType::MatrixMixer));
+
+impl AudioUnit {
+    /// Gets the current value of a typed parameter.
+    ///
+    /// Returns `Error::ParameterUnitTypeMismatch` if the parameter does not belong to the **Type**
+    /// of this unit.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **parameter**: The parameter to read, e.g. `LowPassFilterParameter::CutoffFrequency`.
+    /// - **scope**: The audio unit scope for the parameter.
+    /// - **elem**: The audio unit element for the parameter.
+    pub fn typed_parameter<P: UnitParameter>(
+        &self,
+        parameter: P,
+        scope: Scope,
+        elem: Element,
+    ) -> Result<f32, Error> {
+        self.check_unit_type::<P>()?;
+        self.parameter(parameter.id(), scope, elem)
+    }
+
+    /// Sets the value of a typed parameter.
+    ///
+    /// Returns `Error::ParameterUnitTypeMismatch` if the parameter does not belong to the **Type**
+    /// of this unit.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **parameter**: The parameter to write, e.g. `LowPassFilterParameter::CutoffFrequency`.
+    /// - **scope**: The audio unit scope for the parameter.
+    /// - **elem**: The audio unit element for the parameter.
+    /// - **value**: The new value of the parameter.
+    pub fn set_typed_parameter<P: UnitParameter>(
+        &mut self,
+        parameter: P,
+        scope: Scope,
+        elem: Element,
+        value: f32,
+    ) -> Result<(), Error> {
+        self.check_unit_type::<P>()?;
+        self.set_parameter(parameter.id(), scope, elem, value, 0)
+    }
+
+    fn check_unit_type<P: UnitParameter>(&self) -> Result<(), Error> {
+        if self.unit_type() == Some(P::UNIT_TYPE) {
+            Ok(())
+        } else {
+            Err(Error::ParameterUnitTypeMismatch {
+                expected: P::UNIT_TYPE,
+                actual: self.unit_type(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::simulated::SimulatedConfig;
+
+    #[test]
+    fn test_parameter_ids() {
+        assert_eq!(LowPassFilterParameter::Resonance.id(), 1);
+        assert_eq!(DelayParameter::Feedback.id(), 2);
+        assert_eq!(MultiChannelMixerParameter::PostPeakHoldLevel.id(), 4000);
+        assert_eq!(NBandEQParameter::Frequency(3).id(), 3003);
+        assert_eq!(GraphicEQParameter::BandGain(30).id(), 30);
+        assert_eq!(GraphicEQParameter::NumberOfBands.id(), 10000);
+    }
+
+    #[test]
+    fn test_set_typed_parameter_checks_unit_type() {
+        let config = SimulatedConfig {
+            unit_type: Some(Type::Effect(EffectType::LowPassFilter)),
+            ..Default::default()
+        };
+        let mut audio_unit = AudioUnit::new_simulated(config).unwrap();
+        let cutoff = LowPassFilterParameter::CutoffFrequency;
+        audio_unit
+            .set_typed_parameter(cutoff, Scope::Global, Element::Output, 1_000.0)
+            .unwrap();
+        let value = audio_unit.typed_parameter(cutoff, Scope::Global, Element::Output);
+        assert_eq!(value.unwrap(), 1_000.0);
+
+        let wet_dry = DelayParameter::WetDryMix;
+        match audio_unit.set_typed_parameter(wet_dry, Scope::Global, Element::Output, 50.0) {
+            Err(Error::ParameterUnitTypeMismatch { expected, actual }) => {
+                assert_eq!(expected, Type::Effect(EffectType::Delay));
+                assert_eq!(actual, Some(Type::Effect(EffectType::LowPassFilter)));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        // The mismatched parameter ID must not have been applied.
+        let value = audio_unit.parameter(0, Scope::Global, Element::Output);
+        assert_eq!(value.unwrap(), 1_000.0);
+    }
+}
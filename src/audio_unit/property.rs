@@ -0,0 +1,374 @@
+//! Typed access to **AudioUnit** properties.
+//!
+//! Each property is described by a type implementing **Property**, which ties the property ID to
+//! the type of its value. The **Readable** and **Writable** traits mark how a property may be
+//! accessed and **InScope** marks the scopes in which it is available, so that reading a property
+//! as the wrong type, writing a read-only property or using it in the wrong scope fails to compile.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::property::{scope, MaximumFramesPerSlice, SampleRate};
+//! use coreaudio::audio_unit::{AudioUnit, Element, IOType};
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let mut audio_unit = AudioUnit::new(IOType::DefaultOutput)?;
+//! let sample_rate = audio_unit.get_typed_property(SampleRate, scope::Input, Element::Output)?;
+//! audio_unit.set_typed_property(MaximumFramesPerSlice, scope::Global, Element::Output, &4096)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::sys::AudioStreamBasicDescription;
+use crate::sys::{
+    kAudioOutputUnitProperty_IsRunning, kAudioUnitProperty_BypassEffect,
+    kAudioUnitProperty_CPULoad, kAudioUnitProperty_ElementCount,
+    kAudioUnitProperty_LastRenderError, kAudioUnitProperty_Latency,
+    kAudioUnitProperty_MaximumFramesPerSlice, kAudioUnitProperty_SampleRate,
+    kAudioUnitProperty_ShouldAllocateBuffer, kAudioUnitProperty_StreamFormat,
+    kAudioUnitProperty_TailTime,
+};
+
+use super::stream_format;
+use super::{AudioUnit, Element, Scope};
+use crate::error::Error;
+use crate::OSStatus;
+
+/// A property of an **AudioUnit** with a known value type.
+pub trait Property {
+    /// The property ID, e.g. `kAudioUnitProperty_SampleRate`.
+    const ID: u32;
+    /// The type through which the property is read and written.
+    type Value;
+    /// The type the host uses to represent the property.
+    type Raw: Copy;
+    /// Convert the host representation into the value.
+    fn from_raw(raw: Self::Raw) -> Result<Self::Value, Error>;
+    /// Convert the value into the host representation.
+    fn to_raw(value: &Self::Value) -> Self::Raw;
+}
+
+/// A **Property** that may be read.
+pub trait Readable: Property {}
+
+/// A **Property** that may be written.
+pub trait Writable: Property {}
+
+/// A type-level **Scope**, used to constrain the scopes a **Property** is available in.
+pub trait PropertyScope {
+    /// The scope this type stands for.
+    const SCOPE: Scope;
+}
+
+/// Marks that a **Property** is available in the scope `S`.
+pub trait InScope<S: PropertyScope>: Property {}
+
+/// Type-level **Scope**s for use with typed properties.
+pub mod scope {
+    use super::PropertyScope;
+    use crate::audio_unit::Scope;
+
+    /// The global scope.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct Global;
+    /// The input scope.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct Input;
+    /// The output scope.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct Output;
+
+    impl PropertyScope for Global {
+        const SCOPE: Scope = Scope::Global;
+    }
+
+    impl PropertyScope for Input {
+        const SCOPE: Scope = Scope::Input;
+    }
+
+    impl PropertyScope for Output {
+        const SCOPE: Scope = Scope::Output;
+    }
+}
+
+/// Declare a property whose value is passed to and from the host unchanged.
+macro_rules! plain_property {
+    ($(#[$attr:meta])* $Name:ident = $id:expr, $Value:ty) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Default)]
+        pub struct $Name;
+
+        impl Property for $Name {
+            const ID: u32 = $id;
+            type Value = $Value;
+            type Raw = $Value;
+            fn from_raw(raw: $Value) -> Result<$Value, Error> {
+                Ok(raw)
+            }
+            fn to_raw(value: &$Value) -> $Value {
+                *value
+            }
+        }
+    };
+}
+
+/// Declare a property whose `UInt32` host value is exposed as a `bool`.
+macro_rules! bool_property {
+    ($(#[$attr:meta])* $Name:ident = $id:expr) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Default)]
+        pub struct $Name;
+
+        impl Property for $Name {
+            const ID: u32 = $id;
+            type Value = bool;
+            type Raw = u32;
+            fn from_raw(raw: u32) -> Result<bool, Error> {
+                Ok(raw != 0)
+            }
+            fn to_raw(value: &bool) -> u32 {
+                *value as u32
+            }
+        }
+    };
+}
+
+/// Implement the access and scope marker traits for a property.
+macro_rules! impl_markers {
+    ($Name:ident: [$($Access:ident),*], [$($Scope:ident),*]) => {
+        $(impl $Access for $Name {})*
+        $(impl InScope<scope::$Scope> for $Name {})*
+    };
+}
+
+/// The format of the audio on an element, as a
+/// [**StreamFormat**](../stream_format/struct.StreamFormat.html).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StreamFormat;
+
+impl Property for StreamFormat {
+    const ID: u32 = kAudioUnitProperty_StreamFormat;
+    type Value = stream_format::StreamFormat;
+    type Raw = AudioStreamBasicDescription;
+    fn from_raw(raw: AudioStreamBasicDescription) -> Result<Self::Value, Error> {
+        stream_format::StreamFormat::from_asbd(raw)
+    }
+    fn to_raw(value: &Self::Value) -> AudioStreamBasicDescription {
+        value.to_asbd()
+    }
+}
+impl_markers!(StreamFormat: [Readable, Writable], [Input, Output]);
+
+plain_property! {
+    /// The sample rate of an element in Hz.
+    SampleRate = kAudioUnitProperty_SampleRate, f64
+}
+impl_markers!(SampleRate: [Readable, Writable], [Input, Output]);
+
+plain_property! {
+    /// The maximum number of frames the unit is asked to render in a single call.
+    MaximumFramesPerSlice = kAudioUnitProperty_MaximumFramesPerSlice, u32
+}
+impl_markers!(MaximumFramesPerSlice: [Readable, Writable], [Global]);
+
+plain_property! {
+    /// The processing latency of the unit in seconds.
+    Latency = kAudioUnitProperty_Latency, f64
+}
+impl_markers!(Latency: [Readable], [Global]);
+
+plain_property! {
+    /// How long in seconds the unit keeps producing output after its input becomes silent.
+    TailTime = kAudioUnitProperty_TailTime, f64
+}
+impl_markers!(TailTime: [Readable], [Global]);
+
+bool_property! {
+    /// Whether an effect unit passes its input through unprocessed.
+    BypassEffect = kAudioUnitProperty_BypassEffect
+}
+impl_markers!(BypassEffect: [Readable, Writable], [Global]);
+
+plain_property! {
+    /// The number of elements (buses) in a scope. Only writable for units with a variable number
+    /// of elements, such as mixers.
+    ElementCount = kAudioUnitProperty_ElementCount, u32
+}
+impl_markers!(ElementCount: [Readable, Writable], [Global, Input, Output]);
+
+plain_property! {
+    /// The fraction of the render deadline the unit spends rendering, between `0.0` and `1.0`.
+    CPULoad = kAudioUnitProperty_CPULoad, f64
+}
+impl_markers!(CPULoad: [Readable], [Global]);
+
+bool_property! {
+    /// Whether the unit allocates the buffers it renders into on an element.
+    ShouldAllocateBuffer = kAudioUnitProperty_ShouldAllocateBuffer
+}
+impl_markers!(ShouldAllocateBuffer: [Readable, Writable], [Input, Output]);
+
+bool_property! {
+    /// Whether an I/O unit has been started.
+    IsRunning = kAudioOutputUnitProperty_IsRunning
+}
+impl_markers!(IsRunning: [Readable], [Global]);
+
+/// The error returned by the most recent render, or `None` if it succeeded.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LastRenderError;
+
+impl Property for LastRenderError {
+    const ID: u32 = kAudioUnitProperty_LastRenderError;
+    type Value = Option<Error>;
+    type Raw = OSStatus;
+    fn from_raw(raw: OSStatus) -> Result<Option<Error>, Error> {
+        Ok(Error::from_os_status(raw).err())
+    }
+    fn to_raw(value: &Option<Error>) -> OSStatus {
+        value.map_or(0, |err| err.as_os_status())
+    }
+}
+impl_markers!(LastRenderError: [Readable], [Global]);
+
+impl AudioUnit {
+    /// Gets the value of a typed **AudioUnit** property.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **property**: The property to read, e.g. `property::SampleRate`.
+    /// - **scope**: The type-level scope for the property, e.g. `property::scope::Input`.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_typed_property<P, S>(
+        &self,
+        _property: P,
+        _scope: S,
+        elem: Element,
+    ) -> Result<P::Value, Error>
+    where
+        P: Readable + InScope<S>,
+        S: PropertyScope,
+    {
+        let raw: P::Raw = self.get_property(P::ID, S::SCOPE, elem)?;
+        P::from_raw(raw)
+    }
+
+    /// Sets the value of a typed **AudioUnit** property.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **property**: The property to write, e.g. `property::SampleRate`.
+    /// - **scope**: The type-level scope for the property, e.g. `property::scope::Input`.
+    /// - **elem**: The audio unit element for the property.
+    /// - **value**: The value that you want to apply to the property.
+    pub fn set_typed_property<P, S>(
+        &mut self,
+        _property: P,
+        _scope: S,
+        elem: Element,
+        value: &P::Value,
+    ) -> Result<(), Error>
+    where
+        P: Writable + InScope<S>,
+        S: PropertyScope,
+    {
+        let raw = P::to_raw(value);
+        self.set_property(P::ID, S::SCOPE, elem, Some(&raw))
+    }
+}
+
+// The markers are checked by the compiler, so the misuses they rule out are tested as doctests.
+/// Writing a read-only property fails to compile.
+///
+/// ```compile_fail,E0277
+/// use coreaudio::audio_unit::property::{scope, Latency};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.set_typed_property(Latency, scope::Global, Element::Output, &0.0);
+/// ```
+///
+/// ```compile_fail,E0277
+/// use coreaudio::audio_unit::property::{scope, CPULoad};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.set_typed_property(CPULoad, scope::Global, Element::Output, &0.0);
+/// ```
+///
+/// ```compile_fail,E0277
+/// use coreaudio::audio_unit::property::{scope, IsRunning};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.set_typed_property(IsRunning, scope::Global, Element::Output, &true);
+/// ```
+///
+/// ```compile_fail,E0277
+/// use coreaudio::audio_unit::property::{scope, LastRenderError};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.set_typed_property(LastRenderError, scope::Global, Element::Output, &None);
+/// ```
+///
+/// Using a property in a scope it is not available in fails to compile.
+///
+/// ```compile_fail,E0277
+/// use coreaudio::audio_unit::property::{scope, SampleRate};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.get_typed_property(SampleRate, scope::Global, Element::Output);
+/// ```
+///
+/// ```compile_fail,E0308
+/// use coreaudio::audio_unit::property::{scope, MaximumFramesPerSlice};
+/// use coreaudio::audio_unit::simulated::SimulatedConfig;
+/// use coreaudio::audio_unit::{AudioUnit, Element};
+///
+/// let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+/// audio_unit.set_typed_property(MaximumFramesPerSlice, scope::Input, Element::Output, &512);
+/// ```
+#[cfg(doctest)]
+pub struct MarkerMisuse;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::simulated::SimulatedConfig;
+
+    #[test]
+    fn test_typed_properties() {
+        let mut audio_unit = AudioUnit::new_simulated(SimulatedConfig::default()).unwrap();
+        audio_unit
+            .set_typed_property(SampleRate, scope::Input, Element::Output, &48_000.0)
+            .unwrap();
+        let sample_rate = audio_unit
+            .get_typed_property(SampleRate, scope::Input, Element::Output)
+            .unwrap();
+        assert_eq!(sample_rate, 48_000.0);
+
+        let format = audio_unit
+            .get_typed_property(StreamFormat, scope::Input, Element::Output)
+            .unwrap();
+        assert_eq!(format.sample_rate, 48_000.0);
+
+        audio_unit
+            .set_typed_property(BypassEffect, scope::Global, Element::Output, &true)
+            .unwrap();
+        let bypass = audio_unit
+            .get_typed_property(BypassEffect, scope::Global, Element::Output)
+            .unwrap();
+        assert!(bypass);
+        let raw: u32 = audio_unit
+            .get_property(BypassEffect::ID, Scope::Global, Element::Output)
+            .unwrap();
+        assert_eq!(raw, 1);
+    }
+}
@@ -0,0 +1,333 @@
+//! Notifications of changes to the properties of an **AudioUnit**.
+//!
+//! The host may change the properties of a unit at any time, for example renegotiating the stream
+//! format when the output device changes. A **PropertyListener** calls a closure, or sends to a
+//! channel, with the new value of a typed [**Property**](../property/trait.Property.html) each
+//! time it changes on any element, until the listener is dropped.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::property::StreamFormat;
+//! use coreaudio::audio_unit::{AudioUnit, IOType};
+//! use std::sync::mpsc;
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let audio_unit = AudioUnit::new(IOType::DefaultOutput)?;
+//! let (sender, receiver) = mpsc::channel();
+//! let _listener = audio_unit.add_property_sender(StreamFormat, sender)?;
+//! for change in receiver {
+//!     println!("{:?} {:?}: {:?}", change.scope, change.element, change.value);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::sys::AudioUnit as InnerAudioUnit;
+
+use super::backend::{self, Backend};
+use super::property::Readable;
+use super::{AudioUnit, Element, Scope};
+use crate::error::Error;
+use std::os::raw::{c_uint, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::NonNull;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A change to a property of an **AudioUnit**, as delivered to a **PropertyListener**.
+#[derive(Debug)]
+pub struct PropertyChange<T> {
+    /// The scope of the property that changed.
+    pub scope: Scope,
+    /// The element of the property that changed.
+    pub element: Element,
+    /// The new value of the property, or the error returned while reading it.
+    pub value: Result<T, Error>,
+}
+
+/// The closure called with the scope and element of each change.
+type ListenerFn = dyn FnMut(Scope, Element) + Send;
+
+struct ListenerFnWrapper {
+    // The host may call the listener from several threads at once.
+    callback: Mutex<Box<ListenerFn>>,
+}
+
+/// Listens to a property of an **AudioUnit** until dropped.
+///
+/// Created by [**add_property_listener**](../struct.AudioUnit.html#method.add_property_listener)
+/// or [**add_property_sender**](../struct.AudioUnit.html#method.add_property_sender). The
+/// listener keeps the underlying audio unit instance alive, so it may outlive the **AudioUnit**
+/// it was created from.
+pub struct PropertyListener {
+    backend: Arc<dyn Backend>,
+    id: u32,
+    // The reference held by the host, as returned by `Arc::into_raw`.
+    callback: *const ListenerFnWrapper,
+}
+
+// The closure is `Send`, and the host may call it from any thread.
+unsafe impl Send for PropertyListener {}
+
+impl PropertyListener {
+    /// The ID of the property being listened to.
+    pub fn property_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for PropertyListener {
+    fn drop(&mut self) {
+        let callback = self.callback as *mut c_void;
+        let status = self
+            .backend
+            .remove_property_listener(self.id, Some(listener_proc), callback);
+        // If the host may still call the listener, leaking it is the only safe option. Otherwise
+        // a call may still be on its way into `listener_proc` on another thread, so the host's
+        // reference is handed to the backend, which keeps it until the instance is disposed of.
+        if status == 0 {
+            let wrapper = unsafe { Arc::from_raw(self.callback) };
+            self.backend.retire(Box::new(wrapper));
+        }
+    }
+}
+
+impl AudioUnit {
+    /// Call `f` each time the given property changes on any element of the **AudioUnit**.
+    ///
+    /// `f` is called on a thread of the host's choosing with the new value of the property. The
+    /// listener is removed when the returned **PropertyListener** is dropped.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **property**: The property to listen to, e.g. `property::StreamFormat`.
+    /// - **f**: The closure to call with each **PropertyChange**.
+    pub fn add_property_listener<P, F>(
+        &self,
+        _property: P,
+        mut f: F,
+    ) -> Result<PropertyListener, Error>
+    where
+        P: Readable + 'static,
+        F: FnMut(PropertyChange<P::Value>) + Send + 'static,
+    {
+        // The backend may retire the closure, so it must not keep the backend alive itself.
+        let backend = Arc::downgrade(&self.backend);
+        let callback = move |scope, element| {
+            let backend = match backend.upgrade() {
+                Some(backend) => backend,
+                None => return,
+            };
+            let raw = unsafe { backend::get_property::<P::Raw>(&*backend, P::ID, scope, element) };
+            let value = raw.and_then(P::from_raw);
+            f(PropertyChange {
+                scope,
+                element,
+                value,
+            });
+        };
+        let wrapper = Arc::new(ListenerFnWrapper {
+            callback: Mutex::new(Box::new(callback)),
+        });
+        // The host holds a reference of its own, which is reclaimed when the `PropertyListener` is
+        // dropped.
+        let wrapper_ptr = Arc::into_raw(wrapper);
+        let status = unsafe {
+            self.backend.add_property_listener(
+                P::ID,
+                Some(listener_proc),
+                wrapper_ptr as *mut c_void,
+            )
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            drop(unsafe { Arc::from_raw(wrapper_ptr) });
+            return Err(err);
+        }
+        Ok(PropertyListener {
+            backend: self.backend.clone(),
+            id: P::ID,
+            callback: wrapper_ptr,
+        })
+    }
+
+    /// Send a **PropertyChange** through `sender` each time the given property changes on any
+    /// element of the **AudioUnit**.
+    ///
+    /// Changes are no longer sent once the returned **PropertyListener** is dropped, or if the
+    /// receiver is disconnected.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **property**: The property to listen to, e.g. `property::StreamFormat`.
+    /// - **sender**: The channel to send each **PropertyChange** through.
+    pub fn add_property_sender<P>(
+        &self,
+        property: P,
+        sender: Sender<PropertyChange<P::Value>>,
+    ) -> Result<PropertyListener, Error>
+    where
+        P: Readable + 'static,
+        P::Value: Send,
+    {
+        self.add_property_listener(property, move |change| {
+            sender.send(change).ok();
+        })
+    }
+}
+
+/// Convert a raw scope as given by the host.
+fn scope_from_raw(scope: c_uint) -> Option<Scope> {
+    [
+        Scope::Global,
+        Scope::Input,
+        Scope::Output,
+        Scope::Group,
+        Scope::Part,
+        Scope::Note,
+        Scope::Layer,
+        Scope::LayerItem,
+    ]
+    .iter()
+    .copied()
+    .find(|s| *s as c_uint == scope)
+}
+
+/// Listener procedure that will be called each time a property that we listen to changes.
+extern "C-unwind" fn listener_proc(
+    user_data: NonNull<c_void>,
+    _unit: InnerAudioUnit,
+    _id: u32,
+    scope: c_uint,
+    element: c_uint,
+) {
+    // The closure may drop its own `PropertyListener`, which gives up the host's reference, so
+    // the wrapper is kept alive by a reference of our own until the call returns.
+    let wrapper = unsafe {
+        let ptr = user_data.cast::<ListenerFnWrapper>().as_ptr() as *const ListenerFnWrapper;
+        Arc::increment_strong_count(ptr);
+        Arc::from_raw(ptr)
+    };
+    let scope = match scope_from_raw(scope) {
+        Some(scope) => scope,
+        None => return,
+    };
+    // Unwinding into the host would take the whole process down with it, and there is nobody to
+    // hand the panic to, so it is dropped.
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        // A panic in an earlier call poisons the lock, but leaves the closure callable.
+        let mut callback = wrapper
+            .callback
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        callback(scope, Element(element))
+    }))
+    .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PropertyListener;
+    use crate::audio_unit::property::{IsRunning, LastRenderError, StreamFormat};
+    use crate::audio_unit::render_callback::{self, data};
+    use crate::audio_unit::simulated::SimulatedConfig;
+    use crate::audio_unit::{AudioUnit, Element, Scope};
+    use crate::error::Error;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    fn config() -> SimulatedConfig {
+        SimulatedConfig {
+            buffer_frames: 64,
+            realtime: false,
+            ..SimulatedConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_stream_format_changes_are_sent() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let listener = audio_unit
+            .add_property_sender(StreamFormat, sender)
+            .unwrap();
+        let format = audio_unit.output_stream_format().unwrap();
+        let format = crate::audio_unit::StreamFormat {
+            channels: 1,
+            ..format
+        };
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element::Output)
+            .unwrap();
+        let change = receiver.try_recv().unwrap();
+        assert_eq!(change.scope, Scope::Input);
+        assert_eq!(change.element, Element::Output);
+        assert_eq!(change.value.unwrap(), format);
+
+        drop(listener);
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element::Output)
+            .unwrap();
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(mpsc::TryRecvError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn test_listener_may_drop_itself() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let slot = Arc::new(Mutex::new(None));
+        let (sender, receiver) = mpsc::channel();
+        let listener = {
+            let slot = slot.clone();
+            audio_unit
+                .add_property_listener(StreamFormat, move |change| {
+                    let listener: Option<PropertyListener> = slot.lock().unwrap().take();
+                    drop(listener);
+                    sender.send(change.value.is_ok()).ok();
+                })
+                .unwrap()
+        };
+        *slot.lock().unwrap() = Some(listener);
+        let format = audio_unit.output_stream_format().unwrap();
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element::Output)
+            .unwrap();
+        assert!(receiver.try_recv().unwrap());
+        assert!(slot.lock().unwrap().is_none());
+
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element::Output)
+            .unwrap();
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(mpsc::TryRecvError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn test_running_and_render_errors_are_notified() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (running_sender, running) = mpsc::channel();
+        let _running_listener = audio_unit
+            .add_property_listener(IsRunning, move |change| {
+                running_sender.send(change.value.unwrap()).ok();
+            })
+            .unwrap();
+        let (error_sender, errors) = mpsc::channel();
+        let _error_listener = audio_unit
+            .add_property_sender(LastRenderError, error_sender)
+            .unwrap();
+
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit.set_render_callback(|_: Args| Err(())).unwrap();
+        audio_unit.start().unwrap();
+        assert!(running.try_recv().unwrap());
+        let change = errors.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(change.scope, Scope::Global);
+        assert!(matches!(change.value, Ok(Some(Error::Unspecified))));
+        audio_unit.stop().unwrap();
+        assert!(!running.try_recv().unwrap());
+    }
+}
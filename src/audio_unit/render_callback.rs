@@ -1,22 +1,33 @@
-use objc2_audio_toolbox::{
-    kAudioOutputUnitProperty_SetInputCallback, kAudioUnitProperty_SetRenderCallback,
-    kAudioUnitProperty_StreamFormat, AURenderCallbackStruct, AudioUnitRender,
-    AudioUnitRenderActionFlags,
+use crate::sys::{
+    kAudioOutputUnitProperty_SetInputCallback, kAudioUnitProperty_MaximumFramesPerSlice,
+    kAudioUnitProperty_SetRenderCallback, AURenderCallbackStruct, AudioUnitRenderActionFlags,
 };
-use objc2_core_audio_types::{AudioBuffer, AudioBufferList, AudioTimeStamp};
+use crate::sys::{AudioBufferList, AudioTimeStamp};
 
+use super::audio_buffer_list::AudioBufferListBuf;
 use super::audio_format::LinearPcmFlags;
-use super::{AudioUnit, Element, Scope};
-use crate::error::{self, Error};
+use super::property;
+use super::property_listener::PropertyListener;
+use super::{AudioUnit, Element, Scope, StreamFormat};
+use crate::error::Error;
 use crate::OSStatus;
-use std::mem;
+use std::any::Any;
 use std::os::raw::c_void;
-use std::ptr::NonNull;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::ptr::{self, NonNull};
 use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError, TryLockError};
 
 pub use self::action_flags::ActionFlags;
+use self::data::Context;
 pub use self::data::Data;
 
+/// The number of frames assumed per render when a unit does not report
+/// `kAudioUnitProperty_MaximumFramesPerSlice`.
+pub(crate) const DEFAULT_FRAMES_PER_SLICE: u32 = 4096;
+
 /// When `set_render_callback` is called, a closure of this type will be used to wrap the given
 /// render callback function.
 ///
@@ -33,8 +44,71 @@ pub type InputProcFn = dyn FnMut(
 /// This type allows us to safely wrap a boxed `RenderCallback` to use within the input proc.
 pub struct InputProcFnWrapper {
     callback: Box<InputProcFn>,
+    panic: CallbackPanic,
+    /// Whether the callback is a render notification, whose buffers hold audio the unit has
+    /// already rendered and are left untouched when it panics.
+    is_notify: bool,
+    /// Prepares the callback for each new stream format, if its **Data** type follows it. Only
+    /// held so that it keeps listening for as long as the callback is set.
+    #[allow(dead_code)]
+    format_listener: Option<PropertyListener>,
+}
+
+/// The state a callback needs for the latest stream format, prepared by a **PropertyListener** on
+/// the thread notifying the change so that the callback only has to swap it in.
+struct FormatChanges<T> {
+    /// Whether `prepared` holds state the callback has not swapped in yet.
+    changed: AtomicBool,
+    /// The state for the latest format, or `None` if the format does not match the callback's
+    /// **Data** type. Once swapped in it holds the state it replaced, which is dropped when the
+    /// next format is prepared rather than on the audio thread.
+    prepared: Mutex<Option<T>>,
 }
 
+/// The **FormatChanges** of a callback, along with the listener that prepares them.
+type FormatFollower<T> = (Arc<FormatChanges<T>>, PropertyListener);
+
+impl<T> FormatChanges<T> {
+    fn new() -> Self {
+        FormatChanges {
+            changed: AtomicBool::new(false),
+            prepared: Mutex::new(None),
+        }
+    }
+
+    /// Make `state` the state for the latest format.
+    fn prepare(&self, state: Option<T>) {
+        let mut prepared = self.prepared.lock().unwrap_or_else(PoisonError::into_inner);
+        *prepared = state;
+        self.changed.store(true, Ordering::Release);
+    }
+
+    /// Swap the state for the latest format into `current` if it changed, without blocking or
+    /// freeing anything.
+    fn swap_into(&self, current: &mut Option<T>) {
+        if !self.changed.load(Ordering::Acquire) {
+            return;
+        }
+        // The listener only holds the lock briefly, so the swap is retried on the next cycle.
+        let mut prepared = match self.prepared.try_lock() {
+            Ok(prepared) => prepared,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        ::std::mem::swap(&mut *prepared, current);
+        self.changed.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The payload of a panic caught within a callback, shared between the callbacks of an
+/// **AudioUnit** and the unit itself.
+pub(crate) type CallbackPanic = Arc<Mutex<Option<Box<dyn Any + Send + 'static>>>>;
+
+/// Identifies a render notification added with
+/// [**add_render_notify**](../struct.AudioUnit.html#method.add_render_notify).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderNotifyId(usize);
+
 /// Arguments given to the render callback function.
 #[derive(Debug)]
 pub struct Args<D> {
@@ -55,25 +129,291 @@ pub struct Args<D> {
     pub flags: action_flags::Handle,
 }
 
+/// The outcome of a render or input callback.
+///
+/// Callbacks may return a **CallbackOutcome** directly, or any other type implementing
+/// **IntoCallbackOutcome** such as `Result<(), ()>`.
+#[derive(Copy, Clone, Debug)]
+pub enum CallbackOutcome {
+    /// The callback completed successfully.
+    Ok,
+    /// The callback has no audio to provide. The buffers are filled with zeros and the
+    /// `OUTPUT_IS_SILENCE` flag is inserted.
+    Silence,
+    /// The callback failed. The error is reported to the host as its `OSStatus`.
+    Err(Error),
+    /// The callback completed with the given raw `OSStatus`, which is reported to the host as is.
+    Status(OSStatus),
+}
+
+impl CallbackOutcome {
+    /// Apply the outcome to the callback's buffers, returning the status to report to the host.
+    unsafe fn into_os_status(
+        self,
+        io_action_flags: NonNull<AudioUnitRenderActionFlags>,
+        io_data: *mut AudioBufferList,
+    ) -> OSStatus {
+        match self {
+            CallbackOutcome::Ok => 0,
+            CallbackOutcome::Silence => {
+                write_silence(io_action_flags, io_data);
+                0
+            }
+            CallbackOutcome::Err(err) => err.as_os_status(),
+            CallbackOutcome::Status(status) => status,
+        }
+    }
+
+    /// The status to report to the host for a render notification, which never touches the
+    /// buffers, so `Silence` is treated as `Ok`.
+    fn into_notify_status(self) -> OSStatus {
+        match self {
+            CallbackOutcome::Ok | CallbackOutcome::Silence => 0,
+            CallbackOutcome::Err(err) => err.as_os_status(),
+            CallbackOutcome::Status(status) => status,
+        }
+    }
+}
+
+impl From<Result<(), Error>> for CallbackOutcome {
+    fn from(result: Result<(), Error>) -> Self {
+        match result {
+            Ok(()) => CallbackOutcome::Ok,
+            Err(err) => CallbackOutcome::Err(err),
+        }
+    }
+}
+
+impl From<Error> for CallbackOutcome {
+    fn from(err: Error) -> Self {
+        CallbackOutcome::Err(err)
+    }
+}
+
+/// Types that may be returned from a render or input callback.
+///
+/// This is implemented for **CallbackOutcome** itself and for `Result<(), ()>`, where `Err(())`
+/// is reported to the host as `Error::Unspecified`. It is deliberately not implemented for other
+/// `Result` types so that a closure returning `Ok(())` still infers `Result<(), ()>`; use
+/// `CallbackOutcome::from` to return a `Result<(), Error>`.
+pub trait IntoCallbackOutcome {
+    /// Convert `self` into a **CallbackOutcome**.
+    fn into_callback_outcome(self) -> CallbackOutcome;
+}
+
+impl IntoCallbackOutcome for CallbackOutcome {
+    fn into_callback_outcome(self) -> CallbackOutcome {
+        self
+    }
+}
+
+impl IntoCallbackOutcome for Result<(), ()> {
+    fn into_callback_outcome(self) -> CallbackOutcome {
+        match self {
+            Ok(()) => CallbackOutcome::Ok,
+            Err(()) => CallbackOutcome::Err(Error::Unspecified),
+        }
+    }
+}
+
+/// What to do when the buffers handed to a callback cannot be presented as the callback's
+/// **Data** type, for example because their size does not match the number of frames.
+///
+/// The user's callback is not called for that cycle, and unless the process is aborted the error's
+/// `OSStatus` is returned to the host.
+///
+/// The policy is set with `AudioUnit::set_data_error_policy` and defaults to `Silence`.
+#[derive(Clone, Debug, Default)]
+pub enum DataErrorPolicy {
+    /// Fill the buffers with zeros and insert the `OUTPUT_IS_SILENCE` flag.
+    #[default]
+    Silence,
+    /// As `Silence`, and also send the error through the given channel.
+    ///
+    /// The error is sent with `try_send` so that the audio thread never blocks. If the channel is
+    /// full or disconnected the error is dropped.
+    Report(mpsc::SyncSender<Error>),
+    /// Abort the process.
+    Abort,
+}
+
+impl DataErrorPolicy {
+    /// Apply the policy to the given error, returning the status to report to the host.
+    unsafe fn handle(
+        &self,
+        err: Error,
+        io_action_flags: NonNull<AudioUnitRenderActionFlags>,
+        io_data: *mut AudioBufferList,
+    ) -> OSStatus {
+        if let DataErrorPolicy::Abort = *self {
+            process::abort();
+        }
+        write_silence(io_action_flags, io_data);
+        if let DataErrorPolicy::Report(ref sender) = *self {
+            sender.try_send(err).ok();
+        }
+        err.as_os_status()
+    }
+}
+
+/// Zero every buffer in `io_data` and insert the `OUTPUT_IS_SILENCE` flag.
+unsafe fn write_silence(
+    io_action_flags: NonNull<AudioUnitRenderActionFlags>,
+    io_data: *mut AudioBufferList,
+) {
+    if !io_data.is_null() {
+        let ptr = (*io_data).mBuffers.as_ptr();
+        let len = (*io_data).mNumberBuffers as usize;
+        for buffer in slice::from_raw_parts(ptr, len) {
+            if !buffer.mData.is_null() {
+                ptr::write_bytes(buffer.mData as *mut u8, 0, buffer.mDataByteSize as usize);
+            }
+        }
+    }
+    action_flags::Handle::from_ptr(io_action_flags.as_ptr()).insert(ActionFlags::OUTPUT_IS_SILENCE);
+}
+
+/// Whether `io_data` holds buffers that the host has allocated.
+unsafe fn has_buffers(io_data: *mut AudioBufferList) -> bool {
+    if io_data.is_null() || (*io_data).mNumberBuffers == 0 {
+        return false;
+    }
+    let ptr = (*io_data).mBuffers.as_ptr();
+    let len = (*io_data).mNumberBuffers as usize;
+    slice::from_raw_parts(ptr, len)
+        .iter()
+        .all(|buffer| !buffer.mData.is_null())
+}
+
 /// Format specific render callback data.
 pub mod data {
-    use objc2_core_audio_types::AudioBuffer;
-    use objc2_core_audio_types::AudioBufferList;
+    use crate::sys::AudioBuffer;
+    use crate::sys::AudioBufferList;
 
     use super::super::Sample;
     use super::super::StreamFormat;
     use crate::audio_unit::audio_format::LinearPcmFlags;
+    use crate::audio_unit::conversion;
+    use crate::error::Error;
+    use std::iter;
     use std::marker::PhantomData;
+    use std::ops::{Index, IndexMut, Range};
     use std::slice;
 
     /// Audio data wrappers specific to the `AudioUnit`'s `AudioFormat`.
-    pub trait Data {
+    pub trait Data: Sized {
         /// Check whether the stream format matches this type of data.
         fn does_stream_format_match(stream_format: &StreamFormat) -> bool;
         /// We must be able to construct Self from arguments given to the `input_proc`.
+        ///
+        /// Returns `Error::BufferSizeMismatch` if the buffers in `io_data` are not of the size
+        /// required to hold `num_frames` frames, and `Error::BufferCountMismatch` if there are
+        /// too few of them.
+        ///
         /// # Safety
         /// TODO document how to use this function safely.
-        unsafe fn from_input_proc_args(num_frames: u32, io_data: *mut AudioBufferList) -> Self;
+        unsafe fn from_input_proc_args(
+            num_frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error>;
+
+        /// The number of `f32` samples of scratch space the **Context** of a callback should hold
+        /// for buffers of up to `max_frames` frames in the given stream format.
+        ///
+        /// Defaults to none.
+        fn scratch_len(_stream_format: &StreamFormat, _max_frames: u32) -> usize {
+            0
+        }
+
+        /// Whether a callback should follow changes of the stream format made after it was set,
+        /// rather than expect buffers in the format it was set with.
+        ///
+        /// Defaults to `false`.
+        fn follows_stream_format() -> bool {
+            false
+        }
+
+        /// Construct Self from the arguments given to the `input_proc` and the **Context** of the
+        /// callback.
+        ///
+        /// Defaults to `from_input_proc_args`, for types that do not depend on the context.
+        ///
+        /// # Safety
+        /// As for `from_input_proc_args`. Self must not outlive `context`.
+        unsafe fn from_input_proc_args_with_context(
+            num_frames: u32,
+            io_data: *mut AudioBufferList,
+            _context: &mut Context,
+        ) -> Result<Self, Error> {
+            Self::from_input_proc_args(num_frames, io_data)
+        }
+    }
+
+    /// State kept by a callback for its **Data** type, created when the callback is set.
+    ///
+    /// Holds the stream format of the buffers given to the callback, along with the scratch space
+    /// requested by `Data::scratch_len`.
+    #[derive(Debug)]
+    pub struct Context {
+        stream_format: StreamFormat,
+        scratch: Vec<f32>,
+        write_back: bool,
+    }
+
+    impl Context {
+        /// Create the context of a callback whose **Data** type is `D`.
+        ///
+        /// `write_back` is `false` for input callbacks, whose buffers are not read once the
+        /// callback returns.
+        pub(crate) fn new<D: Data>(
+            stream_format: StreamFormat,
+            max_frames: u32,
+            write_back: bool,
+        ) -> Self {
+            Context {
+                scratch: vec![0.0; D::scratch_len(&stream_format, max_frames)],
+                stream_format,
+                write_back,
+            }
+        }
+
+        /// The stream format of the buffers given to the callback.
+        pub fn stream_format(&self) -> &StreamFormat {
+            &self.stream_format
+        }
+
+        /// The scratch space requested by `Data::scratch_len`.
+        pub fn scratch_mut(&mut self) -> &mut Vec<f32> {
+            &mut self.scratch
+        }
+
+        /// Whether changes made by the callback to its data are read back by the host.
+        pub fn writes_back(&self) -> bool {
+            self.write_back
+        }
+    }
+
+    /// Check that `io_data` holds at least one buffer, returning the first.
+    unsafe fn first_buffer(io_data: *mut AudioBufferList) -> Result<AudioBuffer, Error> {
+        if io_data.is_null() || (*io_data).mNumberBuffers == 0 {
+            return Err(Error::BufferCountMismatch {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        Ok((*io_data).mBuffers[0])
+    }
+
+    /// Check that a buffer holding `buffer_len` samples of type `S` has the given byte size.
+    fn check_size<S>(buffer_len: usize, data_byte_size: u32) -> Result<(), Error> {
+        let expected = (::std::mem::size_of::<S>() * buffer_len) as u32;
+        if data_byte_size != expected {
+            return Err(Error::BufferSizeMismatch {
+                expected,
+                actual: data_byte_size,
+            });
+        }
+        Ok(())
     }
 
     /// A raw pointer to the audio data so that the user may handle it themselves.
@@ -86,8 +426,11 @@ pub mod data {
         fn does_stream_format_match(_: &StreamFormat) -> bool {
             true
         }
-        unsafe fn from_input_proc_args(_num_frames: u32, io_data: *mut AudioBufferList) -> Self {
-            Raw { data: io_data }
+        unsafe fn from_input_proc_args(
+            _num_frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error> {
+            Ok(Raw { data: io_data })
         }
     }
 
@@ -178,14 +521,509 @@ pub mod data {
             }
         }
 
-        /// An iterator yielding a mutable reference to each channel in the array.
-        pub fn channels_mut(&mut self) -> ChannelsMut<'_, S> {
-            ChannelsMut {
-                buffers: self.buffers.iter_mut(),
-                frames: self.frames,
-                sample_format: PhantomData,
+        /// An iterator yielding a mutable reference to each channel in the array.
+        pub fn channels_mut(&mut self) -> ChannelsMut<'_, S> {
+            ChannelsMut {
+                buffers: self.buffers.iter_mut(),
+                frames: self.frames,
+                sample_format: PhantomData,
+            }
+        }
+    }
+
+    impl<S> NonInterleaved<S> {
+        /// The number of channels, one per buffer.
+        pub fn num_channels(&self) -> usize {
+            self.buffers.len()
+        }
+
+        /// The number of frames in each channel.
+        pub fn num_frames(&self) -> usize {
+            self.frames
+        }
+
+        /// The samples of the given channel, or `None` if there is no such channel.
+        pub fn channel(&self, channel: usize) -> Option<&[S]> {
+            self.channels().nth(channel)
+        }
+
+        /// The samples of the given channel, or `None` if there is no such channel.
+        pub fn channel_mut(&mut self, channel: usize) -> Option<&mut [S]> {
+            self.channels_mut().nth(channel)
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample(&self, channel: usize, frame: usize) -> Option<&S> {
+            self.channel(channel)?.get(frame)
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample_mut(&mut self, channel: usize, frame: usize) -> Option<&mut S> {
+            self.channel_mut(channel)?.get_mut(frame)
+        }
+
+        /// An iterator yielding a view of the samples of each frame across the channels.
+        pub fn frames(&self) -> Frames<'_, S> {
+            Frames {
+                buffers: self.buffers,
+                range: 0..self.frames,
+                sample_format: PhantomData,
+            }
+        }
+
+        /// An iterator yielding a mutable view of the samples of each frame across the channels.
+        pub fn frames_mut(&mut self) -> FramesMut<'_, S> {
+            FramesMut {
+                buffers: self.buffers,
+                range: 0..self.frames,
+                sample_format: PhantomData,
+            }
+        }
+    }
+
+    impl<S: Copy> NonInterleaved<S> {
+        /// Copy the channels into `dst` as interleaved frames.
+        ///
+        /// Panics if `dst` does not hold exactly `num_channels() * num_frames()` samples.
+        pub fn copy_to_interleaved(&self, dst: &mut [S]) {
+            interleave(self.channels(), self.num_channels(), dst);
+        }
+
+        /// Copy the interleaved frames of `src` into the channels.
+        ///
+        /// Panics if `src` does not hold exactly `num_channels() * num_frames()` samples.
+        pub fn copy_from_interleaved(&mut self, src: &[S]) {
+            let num_channels = self.num_channels();
+            deinterleave(src, num_channels, self.channels_mut());
+        }
+    }
+
+    impl<S> Index<(usize, usize)> for NonInterleaved<S> {
+        type Output = S;
+        /// The sample at `(channel, frame)`.
+        fn index(&self, (channel, frame): (usize, usize)) -> &S {
+            self.sample(channel, frame)
+                .expect("channel or frame out of range")
+        }
+    }
+
+    impl<S> IndexMut<(usize, usize)> for NonInterleaved<S> {
+        fn index_mut(&mut self, (channel, frame): (usize, usize)) -> &mut S {
+            self.sample_mut(channel, frame)
+                .expect("channel or frame out of range")
+        }
+    }
+
+    /// An iterator produced by a `NonInterleaved`, yielding a view of each frame.
+    pub struct Frames<'a, S: 'a> {
+        buffers: &'a [AudioBuffer],
+        range: Range<usize>,
+        sample_format: PhantomData<&'a S>,
+    }
+
+    /// An iterator produced by a `NonInterleaved`, yielding a mutable view of each frame.
+    pub struct FramesMut<'a, S: 'a> {
+        buffers: &'a [AudioBuffer],
+        range: Range<usize>,
+        sample_format: PhantomData<&'a mut S>,
+    }
+
+    /// The samples of a single frame of a `NonInterleaved`, one from each channel.
+    pub struct Frame<'a, S: 'a> {
+        buffers: &'a [AudioBuffer],
+        frame: usize,
+        sample_format: PhantomData<&'a S>,
+    }
+
+    /// The mutable samples of a single frame of a `NonInterleaved`, one from each channel.
+    pub struct FrameMut<'a, S: 'a> {
+        buffers: &'a [AudioBuffer],
+        frame: usize,
+        sample_format: PhantomData<&'a mut S>,
+    }
+
+    /// A pointer to the sample at `frame` in the buffer of a single channel.
+    ///
+    /// # Safety
+    /// `frame` must be less than the number of frames in the buffer.
+    unsafe fn sample_ptr<S>(buffer: &AudioBuffer, frame: usize) -> *mut S {
+        (buffer.mData as *mut S).add(frame)
+    }
+
+    impl<'a, S> Iterator for Frames<'a, S> {
+        type Item = Frame<'a, S>;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.range.next().map(|frame| Frame {
+                buffers: self.buffers,
+                frame,
+                sample_format: PhantomData,
+            })
+        }
+    }
+
+    impl<'a, S> Iterator for FramesMut<'a, S> {
+        type Item = FrameMut<'a, S>;
+        fn next(&mut self) -> Option<Self::Item> {
+            // Each frame is yielded once, so the samples of the views never overlap.
+            self.range.next().map(|frame| FrameMut {
+                buffers: self.buffers,
+                frame,
+                sample_format: PhantomData,
+            })
+        }
+    }
+
+    impl<'a, S> Frame<'a, S> {
+        /// The number of samples in the frame.
+        pub fn len(&self) -> usize {
+            self.buffers.len()
+        }
+
+        /// Returns `true` if the frame has no channels.
+        pub fn is_empty(&self) -> bool {
+            self.buffers.is_empty()
+        }
+
+        /// The sample of the given channel, if any.
+        pub fn get(&self, channel: usize) -> Option<&'a S> {
+            let buffer = self.buffers.get(channel)?;
+            Some(unsafe { &*sample_ptr(buffer, self.frame) })
+        }
+
+        /// An iterator yielding the sample of each channel.
+        pub fn iter(&self) -> impl Iterator<Item = &'a S> + 'a {
+            let frame = self.frame;
+            self.buffers
+                .iter()
+                .map(move |buffer| unsafe { &*sample_ptr::<S>(buffer, frame) })
+        }
+    }
+
+    impl<'a, S> FrameMut<'a, S> {
+        /// The number of samples in the frame.
+        pub fn len(&self) -> usize {
+            self.buffers.len()
+        }
+
+        /// Returns `true` if the frame has no channels.
+        pub fn is_empty(&self) -> bool {
+            self.buffers.is_empty()
+        }
+
+        /// The sample of the given channel, if any.
+        pub fn get(&self, channel: usize) -> Option<&S> {
+            let buffer = self.buffers.get(channel)?;
+            Some(unsafe { &*sample_ptr(buffer, self.frame) })
+        }
+
+        /// The sample of the given channel, if any.
+        pub fn get_mut(&mut self, channel: usize) -> Option<&mut S> {
+            let buffer = self.buffers.get(channel)?;
+            Some(unsafe { &mut *sample_ptr(buffer, self.frame) })
+        }
+
+        /// An iterator yielding the sample of each channel.
+        pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut S> + '_ {
+            let frame = self.frame;
+            self.buffers
+                .iter()
+                .map(move |buffer| unsafe { &mut *sample_ptr::<S>(buffer, frame) })
+        }
+
+        /// Copy one sample per channel from `samples`.
+        ///
+        /// Panics if the length of `samples` is not the number of channels.
+        pub fn copy_from_slice(&mut self, samples: &[S])
+        where
+            S: Copy,
+        {
+            assert_eq!(samples.len(), self.len(), "not a single frame");
+            for (dst, &src) in self.iter_mut().zip(samples) {
+                *dst = src;
+            }
+        }
+    }
+
+    impl<'a, S> Index<usize> for Frame<'a, S> {
+        type Output = S;
+        fn index(&self, channel: usize) -> &S {
+            self.get(channel).expect("channel out of range")
+        }
+    }
+
+    impl<'a, S> Index<usize> for FrameMut<'a, S> {
+        type Output = S;
+        fn index(&self, channel: usize) -> &S {
+            self.get(channel).expect("channel out of range")
+        }
+    }
+
+    impl<'a, S> IndexMut<usize> for FrameMut<'a, S> {
+        fn index_mut(&mut self, channel: usize) -> &mut S {
+            self.get_mut(channel).expect("channel out of range")
+        }
+    }
+
+    impl<S> Interleaved<S> {
+        /// The number of frames in the buffer.
+        pub fn num_frames(&self) -> usize {
+            self.buffer.len().checked_div(self.channels).unwrap_or(0)
+        }
+
+        /// An iterator yielding the samples of each frame, which is empty if there are no
+        /// channels.
+        pub fn frames(&self) -> slice::ChunksExact<'_, S> {
+            let len = self.frames_len();
+            self.buffer[..len].chunks_exact(self.channels.max(1))
+        }
+
+        /// An iterator yielding the samples of each frame, which is empty if there are no
+        /// channels.
+        pub fn frames_mut(&mut self) -> slice::ChunksExactMut<'_, S> {
+            let len = self.frames_len();
+            self.buffer[..len].chunks_exact_mut(self.channels.max(1))
+        }
+
+        /// The number of samples in whole frames.
+        fn frames_len(&self) -> usize {
+            self.num_frames() * self.channels
+        }
+
+        /// A view of the samples of the given channel, which are `channels` samples apart.
+        ///
+        /// Panics if there is no such channel.
+        pub fn channel(&self, channel: usize) -> Strided<'_, S> {
+            assert!(channel < self.channels, "channel out of range");
+            Strided {
+                samples: &self.buffer[channel..],
+                stride: self.channels,
+            }
+        }
+
+        /// A mutable view of the samples of the given channel, which are `channels` samples
+        /// apart.
+        ///
+        /// Panics if there is no such channel.
+        pub fn channel_mut(&mut self, channel: usize) -> StridedMut<'_, S> {
+            assert!(channel < self.channels, "channel out of range");
+            StridedMut {
+                samples: &mut self.buffer[channel..],
+                stride: self.channels,
+            }
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample(&self, channel: usize, frame: usize) -> Option<&S> {
+            if channel >= self.channels {
+                return None;
+            }
+            self.buffer.get(frame * self.channels + channel)
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample_mut(&mut self, channel: usize, frame: usize) -> Option<&mut S> {
+            if channel >= self.channels {
+                return None;
+            }
+            self.buffer.get_mut(frame * self.channels + channel)
+        }
+    }
+
+    impl<S: Copy> Interleaved<S> {
+        /// Copy the frames into one slice per channel.
+        ///
+        /// Panics if the number of slices is not the number of channels, or if any of them does
+        /// not hold exactly `num_frames()` samples.
+        pub fn copy_to_channels<'a, I>(&self, channels: I)
+        where
+            I: IntoIterator<Item = &'a mut [S]>,
+            S: 'a,
+        {
+            deinterleave(self.buffer, self.channels, channels);
+        }
+
+        /// Copy the frames from one slice per channel.
+        ///
+        /// Panics if the number of slices is not the number of channels, or if any of them does
+        /// not hold exactly `num_frames()` samples.
+        pub fn copy_from_channels<'a, I>(&mut self, channels: I)
+        where
+            I: IntoIterator<Item = &'a [S]>,
+            S: 'a,
+        {
+            interleave(channels, self.channels, self.buffer);
+        }
+    }
+
+    impl<S> Index<(usize, usize)> for Interleaved<S> {
+        type Output = S;
+        /// The sample at `(channel, frame)`.
+        fn index(&self, (channel, frame): (usize, usize)) -> &S {
+            self.sample(channel, frame)
+                .expect("channel or frame out of range")
+        }
+    }
+
+    impl<S> IndexMut<(usize, usize)> for Interleaved<S> {
+        fn index_mut(&mut self, (channel, frame): (usize, usize)) -> &mut S {
+            self.sample_mut(channel, frame)
+                .expect("channel or frame out of range")
+        }
+    }
+
+    /// The samples of one channel of an `Interleaved` buffer.
+    pub struct Strided<'a, S: 'a> {
+        // The buffer from the first sample of the channel.
+        samples: &'a [S],
+        stride: usize,
+    }
+
+    /// The mutable samples of one channel of an `Interleaved` buffer.
+    pub struct StridedMut<'a, S: 'a> {
+        // The buffer from the first sample of the channel.
+        samples: &'a mut [S],
+        stride: usize,
+    }
+
+    impl<'a, S> Strided<'a, S> {
+        /// The number of samples in the channel.
+        pub fn len(&self) -> usize {
+            self.samples.len().div_ceil(self.stride)
+        }
+
+        /// Returns `true` if the channel has no samples.
+        pub fn is_empty(&self) -> bool {
+            self.samples.is_empty()
+        }
+
+        /// The sample at the given frame, if any.
+        pub fn get(&self, frame: usize) -> Option<&'a S> {
+            self.samples.get(frame * self.stride)
+        }
+
+        /// An iterator yielding each sample of the channel.
+        pub fn iter(&self) -> iter::StepBy<slice::Iter<'a, S>> {
+            self.samples.iter().step_by(self.stride)
+        }
+    }
+
+    impl<'a, S> StridedMut<'a, S> {
+        /// The number of samples in the channel.
+        pub fn len(&self) -> usize {
+            self.samples.len().div_ceil(self.stride)
+        }
+
+        /// Returns `true` if the channel has no samples.
+        pub fn is_empty(&self) -> bool {
+            self.samples.is_empty()
+        }
+
+        /// The sample at the given frame, if any.
+        pub fn get(&self, frame: usize) -> Option<&S> {
+            self.samples.get(frame * self.stride)
+        }
+
+        /// The sample at the given frame, if any.
+        pub fn get_mut(&mut self, frame: usize) -> Option<&mut S> {
+            self.samples.get_mut(frame * self.stride)
+        }
+
+        /// An iterator yielding each sample of the channel.
+        pub fn iter(&self) -> iter::StepBy<slice::Iter<'_, S>> {
+            self.samples.iter().step_by(self.stride)
+        }
+
+        /// An iterator yielding each sample of the channel.
+        pub fn iter_mut(&mut self) -> iter::StepBy<slice::IterMut<'_, S>> {
+            self.samples.iter_mut().step_by(self.stride)
+        }
+
+        /// Set every sample of the channel to `value`.
+        pub fn fill(&mut self, value: S)
+        where
+            S: Copy,
+        {
+            for sample in self.iter_mut() {
+                *sample = value;
+            }
+        }
+
+        /// Copy the samples of the channel from `src`.
+        ///
+        /// Panics if the length of `src` is not the number of samples in the channel.
+        pub fn copy_from_slice(&mut self, src: &[S])
+        where
+            S: Copy,
+        {
+            assert_eq!(src.len(), self.len(), "the lengths differ");
+            for (dst, &src) in self.iter_mut().zip(src) {
+                *dst = src;
+            }
+        }
+    }
+
+    impl<'a, S> Index<usize> for Strided<'a, S> {
+        type Output = S;
+        fn index(&self, frame: usize) -> &S {
+            self.get(frame).expect("frame out of range")
+        }
+    }
+
+    impl<'a, S> Index<usize> for StridedMut<'a, S> {
+        type Output = S;
+        fn index(&self, frame: usize) -> &S {
+            self.get(frame).expect("frame out of range")
+        }
+    }
+
+    impl<'a, S> IndexMut<usize> for StridedMut<'a, S> {
+        fn index_mut(&mut self, frame: usize) -> &mut S {
+            self.get_mut(frame).expect("frame out of range")
+        }
+    }
+
+    /// Interleave `num_channels` channels, given as one slice each, into `dst`.
+    ///
+    /// Panics if the number of slices is not `num_channels`, or if `dst` does not hold exactly
+    /// `num_channels` times the length of each slice.
+    pub fn interleave<'a, S, I>(channels: I, num_channels: usize, dst: &mut [S])
+    where
+        S: Copy + 'a,
+        I: IntoIterator<Item = &'a [S]>,
+    {
+        let frames = dst.len().checked_div(num_channels).unwrap_or(0);
+        let mut count = 0;
+        for (channel, samples) in channels.into_iter().enumerate() {
+            assert!(channel < num_channels, "too many channels");
+            assert_eq!(samples.len(), frames, "the lengths differ");
+            for (frame, &sample) in samples.iter().enumerate() {
+                dst[frame * num_channels + channel] = sample;
+            }
+            count += 1;
+        }
+        assert_eq!(count, num_channels, "too few channels");
+    }
+
+    /// Deinterleave the frames of `num_channels` channels in `src` into one slice per channel.
+    ///
+    /// Panics if the number of slices is not `num_channels`, or if `src` does not hold exactly
+    /// `num_channels` times the length of each slice.
+    pub fn deinterleave<'a, S, I>(src: &[S], num_channels: usize, channels: I)
+    where
+        S: Copy + 'a,
+        I: IntoIterator<Item = &'a mut [S]>,
+    {
+        let frames = src.len().checked_div(num_channels).unwrap_or(0);
+        let mut count = 0;
+        for (channel, samples) in channels.into_iter().enumerate() {
+            assert!(channel < num_channels, "too many channels");
+            assert_eq!(samples.len(), frames, "the lengths differ");
+            for (frame, sample) in samples.iter_mut().enumerate() {
+                *sample = src[frame * num_channels + channel];
             }
+            count += 1;
         }
+        assert_eq!(count, num_channels, "too few channels");
     }
 
     // Implementation for a non-interleaved linear PCM audio format.
@@ -197,19 +1035,30 @@ pub mod data {
             stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && S::does_match_flags(stream_format.flags)
         }
 
         #[allow(non_snake_case)]
-        unsafe fn from_input_proc_args(frames: u32, io_data: *mut AudioBufferList) -> Self {
+        unsafe fn from_input_proc_args(
+            frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error> {
+            first_buffer(io_data)?;
             let ptr = (*io_data).mBuffers.as_ptr() as *mut AudioBuffer;
             let len = (*io_data).mNumberBuffers as usize;
             let buffers = slice::from_raw_parts_mut(ptr, len);
-            NonInterleaved {
+            // Each buffer is viewed as the `frames` samples of a single channel.
+            for buffer in buffers.iter() {
+                if buffer.mNumberChannels != 1 {
+                    return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+                }
+                check_size::<S>(frames as usize, buffer.mDataByteSize)?;
+            }
+            Ok(NonInterleaved {
                 buffers,
                 frames: frames as usize,
                 sample_format: PhantomData,
-            }
+            })
         }
     }
 
@@ -222,35 +1071,35 @@ pub mod data {
             !stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && S::does_match_flags(stream_format.flags)
         }
 
         #[allow(non_snake_case)]
-        unsafe fn from_input_proc_args(frames: u32, io_data: *mut AudioBufferList) -> Self {
-            // // We're expecting a single interleaved buffer which will be the first in the array.
+        unsafe fn from_input_proc_args(
+            frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error> {
+            // We're expecting a single interleaved buffer which will be the first in the array.
             let AudioBuffer {
                 mNumberChannels,
                 mDataByteSize,
                 mData,
-            } = (*io_data).mBuffers[0];
-            // // Ensure that the size of the data matches the size of the sample format
-            // // multiplied by the number of frames.
-            // //
-            // // TODO: Return an Err instead of `panic`ing.
+            } = first_buffer(io_data)?;
+            // Ensure that the size of the data matches the size of the sample format
+            // multiplied by the number of frames.
             let buffer_len = frames as usize * mNumberChannels as usize;
-            let expected_size = ::std::mem::size_of::<S>() * buffer_len;
-            assert!(mDataByteSize as usize == expected_size);
+            check_size::<S>(buffer_len, mDataByteSize)?;
 
             let buffer: &mut [S] = {
                 let buffer_ptr = mData as *mut S;
                 slice::from_raw_parts_mut(buffer_ptr, buffer_len)
             };
 
-            Interleaved {
+            Ok(Interleaved {
                 buffer,
                 channels: mNumberChannels as usize,
                 sample_format: PhantomData,
-            }
+            })
         }
     }
 
@@ -263,41 +1112,339 @@ pub mod data {
             !stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && S::does_match_flags(stream_format.flags)
         }
 
         #[allow(non_snake_case)]
-        unsafe fn from_input_proc_args(frames: u32, io_data: *mut AudioBufferList) -> Self {
-            // // We're expecting a single interleaved buffer which will be the first in the array.
+        unsafe fn from_input_proc_args(
+            frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error> {
+            // We're expecting a single interleaved buffer which will be the first in the array.
             let AudioBuffer {
                 mNumberChannels,
                 mDataByteSize,
                 mData,
-            } = (*io_data).mBuffers[0];
-            // // Ensure that the size of the data matches the size of the sample format
-            // // multiplied by the number of frames.
-            // //
-            // // TODO: Return an Err instead of `panic`ing.
+            } = first_buffer(io_data)?;
+            // Ensure that the size of the data matches the size of the sample format
+            // multiplied by the number of frames.
             let buffer_len = frames as usize * mNumberChannels as usize;
-            let expected_size = ::std::mem::size_of::<S>() * buffer_len;
-            assert!(mDataByteSize as usize == expected_size);
+            check_size::<S>(buffer_len, mDataByteSize)?;
 
             let buffer: &mut [u8] = {
                 let buffer_ptr = mData as *mut u8;
                 slice::from_raw_parts_mut(buffer_ptr, mDataByteSize as usize)
             };
 
-            InterleavedBytes {
+            Ok(InterleavedBytes {
                 buffer,
                 channels: mNumberChannels as usize,
                 sample_format: PhantomData,
+            })
+        }
+    }
+
+    /// Linear PCM buffers in any **StreamFormat**, presented as non-interleaved `f32` channels.
+    ///
+    /// Unlike the other **Data** types, **Dynamic** matches every stream format, so a callback
+    /// using it keeps working when the host renegotiates the format, e.g. from interleaved `i16`
+    /// to non-interleaved `f32`.
+    ///
+    /// Buffers already holding native-endian non-interleaved `f32` samples are used in place.
+    /// Others are converted into the scratch space of the callback's **Context** before the
+    /// callback is called, one channel after another, and converted back into the buffers when the
+    /// **Dynamic** is dropped if any of its samples were mutably borrowed. Integer samples are
+    /// scaled to and from `-1.0..1.0` as in the [**conversion**](../../conversion/index.html)
+    /// module.
+    pub struct Dynamic {
+        channels: usize,
+        frames: usize,
+        storage: Storage,
+        /// Whether the samples were mutably borrowed, and may need to be written back.
+        modified: bool,
+    }
+
+    /// Where the samples of a **Dynamic** live.
+    enum Storage {
+        /// In the buffers themselves, one channel per buffer.
+        InPlace(*mut AudioBuffer),
+        /// In scratch space, converted from and to the buffers of `io_data`.
+        Converted {
+            scratch: *mut f32,
+            io_data: *mut AudioBufferList,
+            stream_format: StreamFormat,
+            write_back: bool,
+        },
+    }
+
+    impl Dynamic {
+        /// The number of channels.
+        pub fn num_channels(&self) -> usize {
+            self.channels
+        }
+
+        /// The number of frames in each channel.
+        pub fn num_frames(&self) -> usize {
+            self.frames
+        }
+
+        /// The stream format of the underlying buffers, or `None` if they are used in place.
+        pub fn stream_format(&self) -> Option<&StreamFormat> {
+            match self.storage {
+                Storage::InPlace(_) => None,
+                Storage::Converted {
+                    ref stream_format, ..
+                } => Some(stream_format),
+            }
+        }
+
+        /// The samples of the given channel, or `None` if there is no such channel.
+        pub fn channel(&self, channel: usize) -> Option<&[f32]> {
+            if channel >= self.channels {
+                return None;
+            }
+            unsafe {
+                Some(slice::from_raw_parts(
+                    self.channel_ptr(channel),
+                    self.frames,
+                ))
+            }
+        }
+
+        /// The samples of the given channel, or `None` if there is no such channel.
+        pub fn channel_mut(&mut self, channel: usize) -> Option<&mut [f32]> {
+            if channel >= self.channels {
+                return None;
+            }
+            self.modified = true;
+            unsafe {
+                Some(slice::from_raw_parts_mut(
+                    self.channel_ptr(channel),
+                    self.frames,
+                ))
+            }
+        }
+
+        /// An iterator yielding a reference to each channel.
+        pub fn channels(&self) -> impl Iterator<Item = &[f32]> + '_ {
+            (0..self.channels).filter_map(move |channel| self.channel(channel))
+        }
+
+        /// An iterator yielding a mutable reference to each channel.
+        pub fn channels_mut(&mut self) -> impl Iterator<Item = &mut [f32]> + '_ {
+            self.modified = true;
+            let frames = self.frames;
+            let this = &*self;
+            // Each channel is a distinct run of samples, so the slices never overlap.
+            (0..this.channels).map(move |channel| unsafe {
+                slice::from_raw_parts_mut(this.channel_ptr(channel), frames)
+            })
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample(&self, channel: usize, frame: usize) -> Option<&f32> {
+            self.channel(channel)?.get(frame)
+        }
+
+        /// The sample of the given channel at the given frame, if any.
+        pub fn sample_mut(&mut self, channel: usize, frame: usize) -> Option<&mut f32> {
+            self.channel_mut(channel)?.get_mut(frame)
+        }
+
+        /// A pointer to the first sample of the given channel.
+        fn channel_ptr(&self, channel: usize) -> *mut f32 {
+            unsafe {
+                match self.storage {
+                    Storage::InPlace(buffers) => (*buffers.add(channel)).mData as *mut f32,
+                    Storage::Converted { scratch, .. } => scratch.add(channel * self.frames),
+                }
+            }
+        }
+    }
+
+    impl Index<(usize, usize)> for Dynamic {
+        type Output = f32;
+        /// The sample at `(channel, frame)`.
+        fn index(&self, (channel, frame): (usize, usize)) -> &f32 {
+            &self.channel(channel).expect("channel out of range")[frame]
+        }
+    }
+
+    impl IndexMut<(usize, usize)> for Dynamic {
+        fn index_mut(&mut self, (channel, frame): (usize, usize)) -> &mut f32 {
+            &mut self.channel_mut(channel).expect("channel out of range")[frame]
+        }
+    }
+
+    /// The buffers of `io_data`, and the bytes of each buffer.
+    unsafe fn buffers<'a>(io_data: *mut AudioBufferList) -> &'a mut [AudioBuffer] {
+        let ptr = (*io_data).mBuffers.as_mut_ptr();
+        slice::from_raw_parts_mut(ptr, (*io_data).mNumberBuffers as usize)
+    }
+
+    unsafe fn bytes<'a>(buffer: &AudioBuffer) -> &'a mut [u8] {
+        slice::from_raw_parts_mut(buffer.mData as *mut u8, buffer.mDataByteSize as usize)
+    }
+
+    impl Data for Dynamic {
+        fn does_stream_format_match(_: &StreamFormat) -> bool {
+            true
+        }
+
+        /// Without a **Context** the stream format is unknown, so the buffers are expected to
+        /// hold non-interleaved `f32` samples, which are used in place.
+        unsafe fn from_input_proc_args(
+            frames: u32,
+            io_data: *mut AudioBufferList,
+        ) -> Result<Self, Error> {
+            let data = NonInterleaved::<f32>::from_input_proc_args(frames, io_data)?;
+            Ok(Dynamic {
+                channels: data.num_channels(),
+                frames: frames as usize,
+                storage: Storage::InPlace(data.buffers.as_mut_ptr()),
+                modified: false,
+            })
+        }
+
+        fn scratch_len(stream_format: &StreamFormat, max_frames: u32) -> usize {
+            if NonInterleaved::<f32>::does_stream_format_match(stream_format) {
+                0
+            } else {
+                stream_format.channels as usize * max_frames as usize
+            }
+        }
+
+        fn follows_stream_format() -> bool {
+            true
+        }
+
+        unsafe fn from_input_proc_args_with_context(
+            frames: u32,
+            io_data: *mut AudioBufferList,
+            context: &mut Context,
+        ) -> Result<Self, Error> {
+            let stream_format = *context.stream_format();
+            if NonInterleaved::<f32>::does_stream_format_match(&stream_format) {
+                return Self::from_input_proc_args(frames, io_data);
+            }
+            first_buffer(io_data)?;
+            let channels = stream_format.channels as usize;
+            // The number of bytes holding the samples of one channel.
+            let channel_bytes = stream_format
+                .sample_format
+                .size_in_bytes_with_flags(stream_format.flags)
+                * frames as usize;
+            let buffers = buffers(io_data);
+            let non_interleaved = stream_format
+                .flags
+                .contains(LinearPcmFlags::IS_NON_INTERLEAVED);
+            // One buffer per channel, or a single buffer holding every channel.
+            let (expected_buffers, buffer_size) = if non_interleaved {
+                (channels, channel_bytes)
+            } else {
+                (1, channel_bytes * channels)
+            };
+            if buffers.len() < expected_buffers {
+                return Err(Error::BufferCountMismatch {
+                    expected: expected_buffers as u32,
+                    actual: buffers.len() as u32,
+                });
+            }
+            for buffer in &buffers[..expected_buffers] {
+                if buffer.mDataByteSize as usize != buffer_size {
+                    return Err(Error::BufferSizeMismatch {
+                        expected: buffer_size as u32,
+                        actual: buffer.mDataByteSize,
+                    });
+                }
+            }
+
+            // Growing the scratch space allocates, which only happens if the host renders more
+            // frames than it said it would.
+            let frames = frames as usize;
+            let scratch = context.scratch_mut();
+            if scratch.len() < channels * frames {
+                scratch.resize(channels * frames, 0.0);
+            }
+            for (channel, dst) in scratch
+                .chunks_exact_mut(frames.max(1))
+                .take(channels)
+                .enumerate()
+            {
+                if non_interleaved {
+                    let src = bytes(&buffers[channel]);
+                    conversion::convert_channel_from_bytes(src, &stream_format, 0, 1, dst, None);
+                } else {
+                    let src = bytes(&buffers[0]);
+                    conversion::convert_channel_from_bytes(
+                        src,
+                        &stream_format,
+                        channel,
+                        channels,
+                        dst,
+                        None,
+                    );
+                }
+            }
+            Ok(Dynamic {
+                channels,
+                frames,
+                storage: Storage::Converted {
+                    scratch: scratch.as_mut_ptr(),
+                    io_data,
+                    stream_format,
+                    write_back: context.writes_back(),
+                },
+                modified: false,
+            })
+        }
+    }
+
+    impl Drop for Dynamic {
+        fn drop(&mut self) {
+            // Converting unmodified samples back would only lose precision, e.g. of `i32` samples.
+            if !self.modified {
+                return;
+            }
+            let (scratch, io_data, stream_format) = match self.storage {
+                Storage::Converted {
+                    scratch,
+                    io_data,
+                    ref stream_format,
+                    write_back: true,
+                } => (scratch, io_data, stream_format),
+                _ => return,
+            };
+            let non_interleaved = stream_format
+                .flags
+                .contains(LinearPcmFlags::IS_NON_INTERLEAVED);
+            unsafe {
+                let buffers = buffers(io_data);
+                for channel in 0..self.channels {
+                    let src =
+                        slice::from_raw_parts(scratch.add(channel * self.frames), self.frames);
+                    if non_interleaved {
+                        let dst = bytes(&buffers[channel]);
+                        conversion::convert_channel_to_bytes(src, dst, stream_format, 0, 1, None);
+                    } else {
+                        let dst = bytes(&buffers[0]);
+                        conversion::convert_channel_to_bytes(
+                            src,
+                            dst,
+                            stream_format,
+                            channel,
+                            self.channels,
+                            None,
+                        );
+                    }
+                }
             }
         }
     }
 }
 
 pub mod action_flags {
-    use objc2_audio_toolbox::AudioUnitRenderActionFlags;
+    use crate::sys::AudioUnitRenderActionFlags;
 
     use std::fmt;
 
@@ -471,12 +1618,40 @@ pub mod action_flags {
 }
 
 impl AudioUnit {
+    /// Set the **DataErrorPolicy** applied when the buffers given to a callback do not match its
+    /// **Data** type.
+    ///
+    /// The policy is captured when a callback is set, so it only applies to callbacks set after
+    /// this call.
+    pub fn set_data_error_policy(&mut self, policy: DataErrorPolicy) {
+        self.data_error_policy = policy;
+    }
+
+    /// Take the payload of a panic caught within one of this unit's callbacks, if any.
+    ///
+    /// When a callback panics its buffers are filled with silence, the `OUTPUT_IS_SILENCE` flag is
+    /// inserted and an error is returned to the host instead of unwinding into it. Only the first
+    /// panic is kept until it is taken.
+    pub fn take_callback_panic(&self) -> Option<Box<dyn Any + Send + 'static>> {
+        self.callback_panic.lock().ok()?.take()
+    }
+
     /// Pass a render callback (aka "Input Procedure") to the **AudioUnit**.
-    pub fn set_render_callback<F, D>(&mut self, mut f: F) -> Result<(), Error>
+    pub fn set_render_callback<F, R, D>(&mut self, mut f: F) -> Result<(), Error>
     where
-        F: FnMut(Args<D>) -> Result<(), ()> + 'static,
+        F: FnMut(Args<D>) -> R + 'static,
+        R: IntoCallbackOutcome,
         D: Data,
     {
+        // Changes of the stream format are listened to before it is read, so that none is missed.
+        let max_frames = self.max_frames_per_slice();
+        let address = (Scope::Input, Element::Output);
+        let (format_changes, format_listener) = self
+            .follow_stream_format::<D, _, _>(address, move |stream_format| {
+                Context::new::<D>(*stream_format, max_frames, true)
+            })?
+            .unzip();
+
         // First, we'll retrieve the stream format so that we can ensure that the given callback
         // format matches the audio unit's format.
         let stream_format = self.output_stream_format()?;
@@ -491,14 +1666,33 @@ impl AudioUnit {
         //
         // This allows us to take advantage of rust's type system and provide format-specific
         // `Args` types which can be checked at compile time.
+        let policy = self.data_error_policy.clone();
+        let mut context = Some(Context::new::<D>(stream_format, max_frames, true));
         let input_proc_fn = move |io_action_flags: NonNull<AudioUnitRenderActionFlags>,
                                   in_time_stamp: NonNull<AudioTimeStamp>,
                                   in_bus_number: u32,
                                   in_number_frames: u32,
                                   io_data: *mut AudioBufferList|
               -> OSStatus {
+            if let Some(ref changes) = format_changes {
+                changes.swap_into(&mut context);
+            }
+            let context = match context.as_mut() {
+                Some(context) => context,
+                None => {
+                    let err = Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat;
+                    return unsafe { policy.handle(err, io_action_flags, io_data) };
+                }
+            };
             let args = unsafe {
-                let data = D::from_input_proc_args(in_number_frames, io_data);
+                let data = match D::from_input_proc_args_with_context(
+                    in_number_frames,
+                    io_data,
+                    context,
+                ) {
+                    Ok(data) => data,
+                    Err(err) => return policy.handle(err, io_action_flags, io_data),
+                };
                 let flags = action_flags::Handle::from_ptr(io_action_flags.as_ptr());
                 Args {
                     data,
@@ -509,14 +1703,15 @@ impl AudioUnit {
                 }
             };
 
-            match f(args) {
-                Ok(()) => 0,
-                Err(()) => error::Error::Unspecified.as_os_status(),
-            }
+            let outcome = f(args).into_callback_outcome();
+            unsafe { outcome.into_os_status(io_action_flags, io_data) }
         };
 
         let input_proc_fn_wrapper = Box::new(InputProcFnWrapper {
             callback: Box::new(input_proc_fn),
+            panic: self.callback_panic.clone(),
+            is_notify: false,
+            format_listener,
         });
 
         // Setup render callback. Notice that we relinquish ownership of the Callback
@@ -543,128 +1738,93 @@ impl AudioUnit {
     }
 
     /// Pass an input callback (aka "Input Procedure") to the **AudioUnit**.
-    pub fn set_input_callback<F, D>(&mut self, mut f: F) -> Result<(), Error>
+    pub fn set_input_callback<F, R, D>(&mut self, mut f: F) -> Result<(), Error>
     where
-        F: FnMut(Args<D>) -> Result<(), ()> + 'static,
+        F: FnMut(Args<D>) -> R + 'static,
+        R: IntoCallbackOutcome,
         D: Data,
     {
-        // First, we'll retrieve the stream format so that we can ensure that the given callback
-        // format matches the audio unit's format.
-        let stream_format = self.input_stream_format()?;
-
-        // If the stream format does not match, return an error indicating this.
-        if !D::does_stream_format_match(&stream_format) {
-            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
-        }
-
-        // Interleaved or non-interleaved?
-        let non_interleaved = stream_format
-            .flags
-            .contains(LinearPcmFlags::IS_NON_INTERLEAVED);
-
         // Pre-allocate a buffer list for input stream.
         //
         // First, get the current buffer size for pre-allocating the `AudioBuffer`s.
-        #[cfg(target_os = "macos")]
-        let mut buffer_frame_size: u32 = {
-            let id = objc2_core_audio::kAudioDevicePropertyBufferFrameSize;
+        #[cfg(not(target_os = "ios"))]
+        let buffer_frame_size: u32 = {
+            let id = crate::sys::kAudioDevicePropertyBufferFrameSize;
             let buffer_frame_size: u32 = self.get_property(id, Scope::Global, Element::Output)?;
             buffer_frame_size
         };
         #[cfg(target_os = "ios")]
-        let mut buffer_frame_size: u32 = {
-            let id = objc2_audio_toolbox::kAudioSessionProperty_CurrentHardwareIOBufferDuration;
+        let buffer_frame_size: u32 = {
+            let id = crate::sys::kAudioSessionProperty_CurrentHardwareIOBufferDuration;
             let seconds: f32 = super::audio_session_get_property(id)?;
-            let id = objc2_audio_toolbox::kAudioSessionProperty_CurrentHardwareSampleRate;
+            let id = crate::sys::kAudioSessionProperty_CurrentHardwareSampleRate;
             let sample_rate: f64 = super::audio_session_get_property(id)?;
             (sample_rate * seconds as f64).round() as u32
         };
-        let sample_bytes = stream_format.sample_format.size_in_bytes();
-        let n_channels = stream_format.channels;
-        if non_interleaved && n_channels > 1 {
-            return Err(Error::NonInterleavedInputOnlySupportsMono);
-        }
-
-        let data_byte_size = buffer_frame_size * sample_bytes as u32 * n_channels;
-        let mut data = vec![0u8; data_byte_size as usize];
-        let mut buffer_capacity = data_byte_size as usize;
-        let audio_buffer = AudioBuffer {
-            mDataByteSize: data_byte_size,
-            mNumberChannels: n_channels,
-            mData: data.as_mut_ptr() as *mut _,
+        // The buffers are sized for the largest render, so that following changes of the buffer
+        // size never reallocates them.
+        let max_frames = buffer_frame_size.max(self.max_frames_per_slice());
+        // The host does not read the captured buffers back, so there is nothing to write back.
+        let prepare = move |stream_format: &StreamFormat| {
+            (
+                Context::new::<D>(*stream_format, max_frames, false),
+                AudioBufferListBuf::for_format(stream_format, max_frames),
+            )
         };
-        // Relieve ownership of the `Vec` until we're ready to drop the `AudioBufferList`.
-        // TODO: This leaks the len & capacity fields, since only the buffer pointer is released
-        mem::forget(data);
 
-        let audio_buffer_list = Box::new(AudioBufferList {
-            mNumberBuffers: 1,
-            mBuffers: [audio_buffer],
-        });
+        // Changes of the stream format are listened to before it is read, so that none is missed.
+        let address = (Scope::Output, Element::Input);
+        let (format_changes, format_listener) = self
+            .follow_stream_format::<D, _, _>(address, prepare)?
+            .unzip();
+
+        // Next, we'll retrieve the stream format so that we can ensure that the given callback
+        // format matches the audio unit's format.
+        let stream_format = self.input_stream_format()?;
 
-        // Relinquish ownership of the audio buffer list. Instead, we'll store a raw pointer and
-        // convert it back into a `Box` when `free_input_callback` is next called.
-        let audio_buffer_list_ptr = Box::into_raw(audio_buffer_list);
+        // If the stream format does not match, return an error indicating this.
+        if !D::does_stream_format_match(&stream_format) {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
 
         // Here, we call the given input callback function within a closure that matches the
         // arguments of the required coreaudio "input_proc".
         //
         // This allows us to take advantage of rust's type system and provide format-specific
         // `Args` types which can be checked at compile time.
-        let audio_unit = self.instance;
+        let backend = self.backend.clone();
+        let policy = self.data_error_policy.clone();
+        let mut state = Some(prepare(&stream_format));
         let input_proc_fn = move |io_action_flags: NonNull<AudioUnitRenderActionFlags>,
                                   in_time_stamp: NonNull<AudioTimeStamp>,
                                   in_bus_number: u32,
                                   in_number_frames: u32,
-                                  _io_data: *mut AudioBufferList|
+                                  io_data: *mut AudioBufferList|
               -> OSStatus {
-            // If the buffer size has changed, ensure the AudioBuffer is the correct size.
-            if buffer_frame_size != in_number_frames {
-                unsafe {
-                    // Retrieve the up-to-date stream format.
-                    let id = kAudioUnitProperty_StreamFormat;
-                    let asbd =
-                        match super::get_property(audio_unit, id, Scope::Output, Element::Input) {
-                            Err(err) => return err.as_os_status(),
-                            Ok(asbd) => asbd,
-                        };
-                    let stream_format = match super::StreamFormat::from_asbd(asbd) {
-                        Err(err) => return err.as_os_status(),
-                        Ok(fmt) => fmt,
-                    };
-                    let sample_bytes = stream_format.sample_format.size_in_bytes();
-                    let n_channels = stream_format.channels;
-                    let data_byte_size =
-                        in_number_frames as usize * sample_bytes * n_channels as usize;
-                    let ptr = (*audio_buffer_list_ptr).mBuffers.as_ptr() as *mut AudioBuffer;
-                    let len = (*audio_buffer_list_ptr).mNumberBuffers as usize;
-
-                    let buffers: &mut [AudioBuffer] = slice::from_raw_parts_mut(ptr, len);
-                    let old_capacity = buffer_capacity;
-                    for buffer in buffers {
-                        let current_len = buffer.mDataByteSize as usize;
-                        let audio_buffer_ptr = buffer.mData as *mut u8;
-                        let mut vec: Vec<u8> =
-                            Vec::from_raw_parts(audio_buffer_ptr, current_len, old_capacity);
-                        vec.resize(data_byte_size, 0u8);
-
-                        buffer_capacity = vec.capacity();
-                        buffer.mData = vec.as_mut_ptr() as *mut _;
-                        buffer.mDataByteSize = data_byte_size as u32;
-                        mem::forget(vec);
-                    }
+            if let Some(ref changes) = format_changes {
+                changes.swap_into(&mut state);
+            }
+            let (context, buffer_list) = match state.as_mut() {
+                Some((context, buffer_list)) => (context, buffer_list),
+                None => {
+                    let err = Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat;
+                    return unsafe { policy.handle(err, io_action_flags, io_data) };
                 }
-                buffer_frame_size = in_number_frames;
+            };
+            // Follow changes of the buffer size, which only reallocates if the host renders more
+            // frames than it said it would.
+            let byte_size = buffer_byte_size(context.stream_format(), in_number_frames);
+            if buffer_list.audio_buffers().first().map(|b| b.mDataByteSize) != Some(byte_size) {
+                buffer_list.resize(byte_size);
             }
 
             unsafe {
-                let status = AudioUnitRender(
-                    audio_unit,
+                let status = backend.render(
                     io_action_flags.as_ptr(),
                     in_time_stamp,
                     in_bus_number,
                     in_number_frames,
-                    NonNull::new(audio_buffer_list_ptr).unwrap(),
+                    buffer_list.as_non_null(),
                 );
                 if status != 0 {
                     return status;
@@ -672,7 +1832,15 @@ impl AudioUnit {
             }
 
             let args = unsafe {
-                let data = D::from_input_proc_args(in_number_frames, audio_buffer_list_ptr);
+                let io_data = buffer_list.as_mut_ptr();
+                let data = match D::from_input_proc_args_with_context(
+                    in_number_frames,
+                    io_data,
+                    context,
+                ) {
+                    Ok(data) => data,
+                    Err(err) => return policy.handle(err, io_action_flags, io_data),
+                };
                 let flags = action_flags::Handle::from_ptr(io_action_flags.as_ptr());
                 Args {
                     data,
@@ -683,14 +1851,15 @@ impl AudioUnit {
                 }
             };
 
-            match f(args) {
-                Ok(()) => 0,
-                Err(()) => error::Error::Unspecified.as_os_status(),
-            }
+            let outcome = f(args).into_callback_outcome();
+            unsafe { outcome.into_os_status(io_action_flags, buffer_list.as_mut_ptr()) }
         };
 
         let input_proc_fn_wrapper = Box::new(InputProcFnWrapper {
             callback: Box::new(input_proc_fn),
+            panic: self.callback_panic.clone(),
+            is_notify: false,
+            format_listener,
         });
 
         // Setup input callback. Notice that we relinquish ownership of the Callback
@@ -712,7 +1881,6 @@ impl AudioUnit {
         )?;
 
         let input_callback = super::InputCallback {
-            buffer_list: audio_buffer_list_ptr,
             callback: input_proc_fn_wrapper_ptr as *mut InputProcFnWrapper,
         };
         self.free_input_callback();
@@ -720,6 +1888,152 @@ impl AudioUnit {
         Ok(())
     }
 
+    /// Add a closure that is notified before and after each render of the **AudioUnit**'s output
+    /// element, such as to meter, tap or record what the unit produces.
+    ///
+    /// The closure is called twice per render. First with the `PRE_RENDER` flag, before the unit
+    /// has rendered, when the contents of the buffers are undefined. Then with the `POST_RENDER`
+    /// flag, when the buffers hold the rendered audio, along with `POST_RENDER_ERROR` if rendering
+    /// failed. Calls in which the host has not yet provided the buffers, or in which the data does
+    /// not match the stream format, are skipped without touching the output. Changes made to
+    /// converted data, such as that of **Dynamic**, are not written back, and returning
+    /// `CallbackOutcome::Silence` does not silence the output.
+    ///
+    /// The data is checked against the output stream format of I/O units, as given to the render
+    /// callback, and against the `Scope::Output` stream format of the output element otherwise.
+    /// Any number of notifications may be added, and they are removed when the **AudioUnit** is
+    /// dropped.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **f**: The closure to notify, which returns the identifier to pass to
+    ///   [**remove_render_notify**](./struct.AudioUnit.html#method.remove_render_notify).
+    pub fn add_render_notify<F, R, D>(&mut self, mut f: F) -> Result<RenderNotifyId, Error>
+    where
+        F: FnMut(Args<D>) -> R + 'static,
+        R: IntoCallbackOutcome,
+        D: Data,
+    {
+        let address = match self.unit_type {
+            Some(super::Type::IO(_)) => (Scope::Input, Element::Output),
+            _ => (Scope::Output, Element::Output),
+        };
+        // Changes of the stream format are listened to before it is read, so that none is missed.
+        // A notification observes the render, so converted data is not written back.
+        let max_frames = self.max_frames_per_slice();
+        let (format_changes, format_listener) = self
+            .follow_stream_format::<D, _, _>(address, move |stream_format| {
+                Context::new::<D>(*stream_format, max_frames, false)
+            })?
+            .unzip();
+        let stream_format = self.stream_format(address.0, address.1)?;
+        if !D::does_stream_format_match(&stream_format) {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
+
+        let mut context = Some(Context::new::<D>(stream_format, max_frames, false));
+        let notify_fn = move |io_action_flags: NonNull<AudioUnitRenderActionFlags>,
+                              in_time_stamp: NonNull<AudioTimeStamp>,
+                              in_bus_number: u32,
+                              in_number_frames: u32,
+                              io_data: *mut AudioBufferList|
+              -> OSStatus {
+            // I/O units also notify renders of their input element, which we are not watching.
+            if in_bus_number != Element::Output.0 || !unsafe { has_buffers(io_data) } {
+                return 0;
+            }
+            if let Some(ref changes) = format_changes {
+                changes.swap_into(&mut context);
+            }
+            let context = match context.as_mut() {
+                Some(context) => context,
+                None => return 0,
+            };
+            let args = unsafe {
+                let data = match D::from_input_proc_args_with_context(
+                    in_number_frames,
+                    io_data,
+                    context,
+                ) {
+                    Ok(data) => data,
+                    // Unlike a render callback, the rendered output is left untouched.
+                    Err(_) => return 0,
+                };
+                let flags = action_flags::Handle::from_ptr(io_action_flags.as_ptr());
+                Args {
+                    data,
+                    time_stamp: in_time_stamp.read(),
+                    flags,
+                    bus_number: in_bus_number,
+                    num_frames: in_number_frames as usize,
+                }
+            };
+
+            f(args).into_callback_outcome().into_notify_status()
+        };
+
+        let notify_fn_wrapper = Box::new(InputProcFnWrapper {
+            callback: Box::new(notify_fn),
+            panic: self.callback_panic.clone(),
+            is_notify: true,
+            format_listener,
+        });
+        // As with the render callback, ownership is relinquished while the host holds the
+        // pointer, and reclaimed when the notification is removed.
+        let notify_fn_wrapper_ptr = Box::into_raw(notify_fn_wrapper);
+        let status = unsafe {
+            self.backend
+                .add_render_notify(Some(input_proc), notify_fn_wrapper_ptr as *mut c_void)
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            drop(unsafe { Box::from_raw(notify_fn_wrapper_ptr) });
+            return Err(err);
+        }
+        self.render_notifies.push(notify_fn_wrapper_ptr);
+        Ok(RenderNotifyId(notify_fn_wrapper_ptr as usize))
+    }
+
+    /// Remove a render notification added with
+    /// [**add_render_notify**](./struct.AudioUnit.html#method.add_render_notify).
+    ///
+    /// The host may still be running the closure on the render thread when this returns, so the
+    /// closure is only dropped along with the **AudioUnit**, after it has stopped. Returns
+    /// `Error::Unspecified` if `id` does not identify a notification of this unit.
+    pub fn remove_render_notify(&mut self, id: RenderNotifyId) -> Result<(), Error> {
+        let index = self
+            .render_notifies
+            .iter()
+            .position(|&ptr| ptr as usize == id.0)
+            .ok_or(Error::Unspecified)?;
+        let ptr = self.render_notifies[index];
+        let status = self
+            .backend
+            .remove_render_notify(Some(input_proc), ptr as *mut c_void);
+        Error::from_os_status(status)?;
+        self.render_notifies.swap_remove(index);
+        self.removed_render_notifies.push(ptr);
+        Ok(())
+    }
+
+    /// Remove all render notifications, ignoring errors, and free those removed earlier.
+    ///
+    /// Only called once the unit has stopped rendering.
+    pub(crate) fn remove_render_notifies(&mut self) {
+        for ptr in self.removed_render_notifies.drain(..) {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+        for ptr in self.render_notifies.drain(..) {
+            let status = self
+                .backend
+                .remove_render_notify(Some(input_proc), ptr as *mut c_void);
+            // If the host may still call the notification, leaking it is the only safe option.
+            if status == 0 {
+                drop(unsafe { Box::from_raw(ptr) });
+            }
+        }
+    }
+
     /// Retrieves ownership over the render callback and returns it where it can be re-used or
     /// safely dropped.
     pub fn free_render_callback(&mut self) -> Option<Box<InputProcFnWrapper>> {
@@ -736,30 +2050,79 @@ impl AudioUnit {
     /// safely dropped.
     pub fn free_input_callback(&mut self) -> Option<Box<InputProcFnWrapper>> {
         if let Some(input_callback) = self.maybe_input_callback.take() {
-            let super::InputCallback {
-                buffer_list,
-                callback,
-            } = input_callback;
-            unsafe {
-                // Take ownership over the AudioBufferList in order to safely free it.
-                let buffer_list: Box<AudioBufferList> = Box::from_raw(buffer_list);
-                // Free the allocated data from the individual audio buffers.
-                let ptr = buffer_list.mBuffers.as_ptr();
-                let len = buffer_list.mNumberBuffers as usize;
-                let buffers: &[AudioBuffer] = slice::from_raw_parts(ptr, len);
-                for &buffer in buffers {
-                    let ptr = buffer.mData as *mut u8;
-                    let len = buffer.mDataByteSize as usize;
-                    let cap = len;
-                    let _ = Vec::from_raw_parts(ptr, len, cap);
-                }
-                // Take ownership over the callback so that it can be freed.
-                let callback: Box<InputProcFnWrapper> = Box::from_raw(callback);
-                return Some(callback);
-            }
+            let super::InputCallback { callback } = input_callback;
+            // Take ownership over the callback so that it can be freed. The buffer list that
+            // input is rendered to is owned by the callback and is freed along with it.
+            let callback: Box<InputProcFnWrapper> = unsafe { Box::from_raw(callback) };
+            return Some(callback);
         }
         None
     }
+
+    /// The largest number of frames the unit renders at once, or `DEFAULT_FRAMES_PER_SLICE` if
+    /// the unit does not report `kAudioUnitProperty_MaximumFramesPerSlice`.
+    pub(crate) fn max_frames_per_slice(&self) -> u32 {
+        self.get_property(
+            kAudioUnitProperty_MaximumFramesPerSlice,
+            Scope::Global,
+            Element::Output,
+        )
+        .unwrap_or(DEFAULT_FRAMES_PER_SLICE)
+    }
+
+    /// Listen to changes of the stream format at `address` on behalf of a callback whose
+    /// **Data** type is `D`, preparing the callback's state for each new format with `prepare`.
+    ///
+    /// Returns `None` if `D` does not follow the stream format.
+    fn follow_stream_format<D, T, P>(
+        &self,
+        (scope, element): (Scope, Element),
+        prepare: P,
+    ) -> Result<Option<FormatFollower<T>>, Error>
+    where
+        D: Data,
+        T: Send + 'static,
+        P: Fn(&StreamFormat) -> T + Send + 'static,
+    {
+        if !D::follows_stream_format() {
+            return Ok(None);
+        }
+        let changes = Arc::new(FormatChanges::new());
+        let listener = {
+            let changes = changes.clone();
+            self.add_property_listener(property::StreamFormat, move |change| {
+                if change.scope != scope || change.element != element {
+                    return;
+                }
+                // A format that cannot be read is ignored, leaving the callback as it was.
+                if let Ok(stream_format) = change.value {
+                    let state = if D::does_stream_format_match(&stream_format) {
+                        Some(prepare(&stream_format))
+                    } else {
+                        None
+                    };
+                    changes.prepare(state);
+                }
+            })?
+        };
+        Ok(Some((changes, listener)))
+    }
+}
+
+/// The byte size of each buffer of `AudioBufferListBuf::for_format` holding `frames` frames.
+fn buffer_byte_size(stream_format: &StreamFormat, frames: u32) -> u32 {
+    let channels_per_buffer = if stream_format
+        .flags
+        .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
+    {
+        1
+    } else {
+        stream_format.channels
+    };
+    let sample_bytes = stream_format
+        .sample_format
+        .size_in_bytes_with_flags(stream_format.flags);
+    frames * sample_bytes as u32 * channels_per_buffer
 }
 
 /// Callback procedure that will be called each time our audio_unit requests audio.
@@ -772,11 +2135,381 @@ extern "C-unwind" fn input_proc(
     io_data: *mut AudioBufferList,
 ) -> OSStatus {
     let wrapper = unsafe { in_ref_con.cast::<InputProcFnWrapper>().as_mut() };
-    (wrapper.callback)(
-        io_action_flags,
-        in_time_stamp,
-        in_bus_number,
-        in_number_frames,
-        io_data,
-    )
+    // Unwinding into the host's audio thread would take the whole process down with it, so
+    // panics are caught here and handed to the `AudioUnit` instead.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        (wrapper.callback)(
+            io_action_flags,
+            in_time_stamp,
+            in_bus_number,
+            in_number_frames,
+            io_data,
+        )
+    }));
+    match result {
+        Ok(status) => status,
+        Err(payload) => {
+            if !wrapper.is_notify {
+                unsafe { write_silence(io_action_flags, io_data) };
+            }
+            // Keep the first panic until it is taken, as later ones are likely repeats of it.
+            if let Ok(mut slot) = wrapper.panic.lock() {
+                slot.get_or_insert(payload);
+            }
+            Error::Unspecified.as_os_status()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::data::Interleaved;
+    use super::*;
+    use crate::sys::AudioBuffer;
+
+    #[test]
+    fn test_format_changes_are_swapped_in() {
+        let changes = FormatChanges::new();
+        let mut current = Some(1);
+        changes.swap_into(&mut current);
+        assert_eq!(current, Some(1));
+
+        changes.prepare(Some(2));
+        changes.swap_into(&mut current);
+        assert_eq!(current, Some(2));
+        // The replaced state is left to be dropped when the next format is prepared.
+        assert_eq!(*changes.prepared.lock().unwrap(), Some(1));
+        changes.swap_into(&mut current);
+        assert_eq!(current, Some(2));
+
+        changes.prepare(None);
+        changes.swap_into(&mut current);
+        assert_eq!(current, None);
+    }
+
+    #[test]
+    fn test_buffer_size_mismatch() {
+        let mut samples = [1.0f32; 8];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 32,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let result = unsafe { Interleaved::<f32>::from_input_proc_args(4, &mut list) };
+        assert!(result.is_ok());
+        let result = unsafe { Interleaved::<f32>::from_input_proc_args(4, ptr::null_mut()) };
+        assert!(matches!(
+            result,
+            Err(Error::BufferCountMismatch {
+                expected: 1,
+                actual: 0
+            })
+        ));
+        let result = unsafe { Interleaved::<f32>::from_input_proc_args(8, &mut list) };
+        let err = match result {
+            Err(err @ Error::BufferSizeMismatch { .. }) => err,
+            _ => panic!("expected a buffer size mismatch"),
+        };
+        assert!(matches!(
+            err,
+            Error::BufferSizeMismatch {
+                expected: 64,
+                actual: 32
+            }
+        ));
+
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let mut flags = AudioUnitRenderActionFlags(0);
+        let policy = DataErrorPolicy::Report(sender);
+        let status = unsafe { policy.handle(err, NonNull::from(&mut flags), &mut list) };
+        assert_eq!(status, err.as_os_status());
+        assert_eq!(samples, [0.0; 8]);
+        let flags = ActionFlags::from_bits_truncate(flags.0);
+        assert!(flags.contains(ActionFlags::OUTPUT_IS_SILENCE));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(Error::BufferSizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_input_proc_catches_panic() {
+        let panic = CallbackPanic::default();
+        let mut wrapper = InputProcFnWrapper {
+            callback: Box::new(|_, _, _, _, _| panic!("callback panicked")),
+            panic: panic.clone(),
+            is_notify: false,
+            format_listener: None,
+        };
+        let mut samples = [1.0f32; 4];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 16,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let mut flags = AudioUnitRenderActionFlags(0);
+        let mut time_stamp: AudioTimeStamp = unsafe { std::mem::zeroed() };
+        let status = input_proc(
+            NonNull::from(&mut wrapper).cast(),
+            NonNull::from(&mut flags),
+            NonNull::from(&mut time_stamp),
+            0,
+            2,
+            &mut list,
+        );
+        assert_eq!(status, Error::Unspecified.as_os_status());
+        assert_eq!(samples, [0.0; 4]);
+        let flags = ActionFlags::from_bits_truncate(flags.0);
+        assert!(flags.contains(ActionFlags::OUTPUT_IS_SILENCE));
+        let payload = panic.lock().unwrap().take().unwrap();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"callback panicked"));
+
+        // A panicking notification leaves the rendered audio in place.
+        wrapper.is_notify = true;
+        samples.fill(1.0);
+        let mut flags = AudioUnitRenderActionFlags(0);
+        let status = input_proc(
+            NonNull::from(&mut wrapper).cast(),
+            NonNull::from(&mut flags),
+            NonNull::from(&mut time_stamp),
+            0,
+            2,
+            &mut list,
+        );
+        assert_eq!(status, Error::Unspecified.as_os_status());
+        assert_eq!(samples, [1.0; 4]);
+        assert_eq!(flags.0, 0);
+        assert!(panic.lock().unwrap().take().is_some());
+    }
+
+    #[test]
+    fn test_callback_outcome() {
+        let mut samples = [1i16; 4];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 1,
+                mDataByteSize: 8,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let mut flags = AudioUnitRenderActionFlags(0);
+        let flags_ptr = NonNull::from(&mut flags);
+        unsafe {
+            let outcome = Ok(()).into_callback_outcome();
+            assert_eq!(outcome.into_os_status(flags_ptr, &mut list), 0);
+            let outcome = Err(()).into_callback_outcome();
+            let status = outcome.into_os_status(flags_ptr, &mut list);
+            assert_eq!(status, Error::Unspecified.as_os_status());
+            let outcome = CallbackOutcome::from(Err(Error::Unknown(-1)));
+            assert_eq!(outcome.into_os_status(flags_ptr, &mut list), -1);
+            let outcome = CallbackOutcome::Status(-2);
+            assert_eq!(outcome.into_os_status(flags_ptr, &mut list), -2);
+            assert_eq!(samples, [1; 4]);
+            let outcome = CallbackOutcome::Silence;
+            assert_eq!(outcome.into_os_status(flags_ptr, &mut list), 0);
+        }
+        assert_eq!(samples, [0; 4]);
+        let flags = ActionFlags::from_bits_truncate(flags.0);
+        assert!(flags.contains(ActionFlags::OUTPUT_IS_SILENCE));
+    }
+
+    #[test]
+    fn test_interleaved_views() {
+        let mut samples = [0i16, 1, 10, 11, 20, 21];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 12,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let mut data = unsafe { Interleaved::<i16>::from_input_proc_args(3, &mut list) }.unwrap();
+        assert_eq!(data.num_frames(), 3);
+        let frames: Vec<&[i16]> = data.frames().collect();
+        assert_eq!(frames, [[0, 1], [10, 11], [20, 21]]);
+        let right = data.channel(1);
+        assert_eq!(right.len(), 3);
+        assert_eq!(right.get(2), Some(&21));
+        assert_eq!(right.iter().copied().collect::<Vec<_>>(), [1, 11, 21]);
+        assert_eq!(data[(0, 1)], 10);
+        assert_eq!(data.sample(2, 0), None);
+
+        data.channel_mut(0).copy_from_slice(&[5, 15, 25]);
+        data[(1, 2)] = -1;
+        for frame in data.frames_mut() {
+            frame[1] += 100;
+        }
+        let (mut left, mut right) = ([0; 3], [0; 3]);
+        data.copy_to_channels(vec![&mut left[..], &mut right[..]]);
+        assert_eq!((left, right), ([5, 15, 25], [101, 111, 99]));
+        data.copy_from_channels(vec![&right[..], &left[..]]);
+        assert_eq!(samples, [101, 5, 111, 15, 99, 25]);
+
+        list.mBuffers[0].mNumberChannels = 0;
+        list.mBuffers[0].mDataByteSize = 0;
+        let mut data = unsafe { Interleaved::<i16>::from_input_proc_args(3, &mut list) }.unwrap();
+        assert_eq!(data.num_frames(), 0);
+        assert_eq!(data.frames().count(), 0);
+        assert_eq!(data.frames_mut().count(), 0);
+    }
+
+    #[test]
+    fn test_non_interleaved_views() {
+        use super::data::NonInterleaved;
+        use crate::audio_unit::AudioBufferListBuf;
+
+        let mut list = AudioBufferListBuf::new(2, 1, 12);
+        let mut data =
+            unsafe { NonInterleaved::<f32>::from_input_proc_args(3, list.as_mut_ptr()) }.unwrap();
+        assert_eq!((data.num_channels(), data.num_frames()), (2, 3));
+        data.copy_from_interleaved(&[0.0, 1.0, 10.0, 11.0, 20.0, 21.0]);
+        assert_eq!(data.channel(1), Some(&[1.0, 11.0, 21.0][..]));
+        assert_eq!(data[(0, 2)], 20.0);
+        assert_eq!(data.sample(0, 3), None);
+
+        let frames: Vec<Vec<f32>> = data.frames().map(|f| f.iter().copied().collect()).collect();
+        assert_eq!(frames, [[0.0, 1.0], [10.0, 11.0], [20.0, 21.0]]);
+        for mut frame in data.frames_mut() {
+            let left = frame[0];
+            frame[1] -= left;
+        }
+        data.frames_mut()
+            .nth(1)
+            .unwrap()
+            .copy_from_slice(&[-1.0, -2.0]);
+        let mut interleaved = [0.0; 6];
+        data.copy_to_interleaved(&mut interleaved);
+        assert_eq!(interleaved, [0.0, 1.0, -1.0, -2.0, 20.0, 1.0]);
+
+        // Each buffer must hold a single channel.
+        let mut samples = [0.0f32; 6];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 24,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        assert!(matches!(
+            unsafe { NonInterleaved::<f32>::from_input_proc_args(3, &mut list) },
+            Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat)
+        ));
+    }
+
+    #[test]
+    fn test_dynamic() {
+        use super::data::{Context, Dynamic, NonInterleaved};
+        use crate::audio_unit::{AudioBufferListBuf, SampleFormat, StreamFormat};
+
+        let mut samples = [0i16, 16384, -16384, 8192];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 8,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let format = StreamFormat {
+            sample_rate: 44_100.0,
+            sample_format: SampleFormat::I16,
+            flags: LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED,
+            channels: 2,
+        };
+        let mut context = Context::new::<Dynamic>(format, 2, true);
+        let mut data =
+            unsafe { Dynamic::from_input_proc_args_with_context(2, &mut list, &mut context) }
+                .unwrap();
+        assert_eq!((data.num_channels(), data.num_frames()), (2, 2));
+        assert_eq!(data.channel(0), Some(&[0.0, -0.5][..]));
+        assert_eq!(data.channel(1), Some(&[0.5, 0.25][..]));
+        for channel in data.channels_mut() {
+            channel[0] = -channel[0];
+        }
+        data[(0, 1)] = 1.0;
+        drop(data);
+        assert_eq!(samples, [0, -16384, i16::MAX, 8192]);
+
+        // Input callbacks leave the buffers untouched.
+        let mut context = Context::new::<Dynamic>(format, 2, false);
+        let mut data =
+            unsafe { Dynamic::from_input_proc_args_with_context(2, &mut list, &mut context) }
+                .unwrap();
+        data[(1, 1)] = 0.0;
+        drop(data);
+        assert_eq!(samples, [0, -16384, i16::MAX, 8192]);
+
+        // Samples that are only read are not written back, which would lose precision.
+        let mut samples = [123_456_789i32, -987_654_321];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 1,
+                mDataByteSize: 8,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let i32_format = StreamFormat {
+            sample_format: SampleFormat::I32,
+            channels: 1,
+            ..format
+        };
+        let mut context = Context::new::<Dynamic>(i32_format, 2, true);
+        let data =
+            unsafe { Dynamic::from_input_proc_args_with_context(2, &mut list, &mut context) }
+                .unwrap();
+        assert!(data[(0, 0)] > 0.0);
+        drop(data);
+        assert_eq!(samples, [123_456_789, -987_654_321]);
+
+        // Buffers of the wrong size are rejected.
+        let mut samples = [0i16; 4];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 8,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let mut context = Context::new::<Dynamic>(format, 4, true);
+        let result =
+            unsafe { Dynamic::from_input_proc_args_with_context(4, &mut list, &mut context) };
+        assert!(matches!(
+            result,
+            Err(Error::BufferSizeMismatch {
+                expected: 16,
+                actual: 8
+            })
+        ));
+
+        // Non-interleaved native f32 buffers are used in place, without scratch space.
+        let format = StreamFormat {
+            sample_format: SampleFormat::F32,
+            flags: LinearPcmFlags::IS_FLOAT
+                | LinearPcmFlags::IS_PACKED
+                | LinearPcmFlags::IS_NON_INTERLEAVED,
+            ..format
+        };
+        assert_eq!(Dynamic::scratch_len(&format, 512), 0);
+        let mut list = AudioBufferListBuf::new(2, 1, 8);
+        let mut context = Context::new::<Dynamic>(format, 2, true);
+        let mut data = unsafe {
+            Dynamic::from_input_proc_args_with_context(2, list.as_mut_ptr(), &mut context)
+        }
+        .unwrap();
+        assert_eq!(data.stream_format(), None);
+        data[(1, 0)] = 0.5;
+        let data = unsafe { NonInterleaved::<f32>::from_input_proc_args(2, list.as_mut_ptr()) };
+        assert_eq!(data.unwrap().channel(1), Some(&[0.5, 0.0][..]));
+    }
 }
@@ -0,0 +1,611 @@
+//! A wait-free single-producer, single-consumer ring buffer for moving audio between threads,
+//! such as from an input callback to a render callback.
+//!
+//! [**interleaved**](./fn.interleaved.html) and [**non_interleaved**](./fn.non_interleaved.html)
+//! create a **Producer** and **Consumer** pair sharing a buffer of a fixed number of frames. The
+//! layout only determines how the frames are stored, so that copies in the same layout are
+//! contiguous. Either end may push or pop interleaved samples or per-channel slices, and the
+//! `data` of callback **Args** may be pushed or popped directly.
+//!
+//! Neither end ever blocks or allocates. Pushing to a full buffer stores what fits and counts an
+//! overrun, and popping from a buffer holding too few frames pops what is there and counts an
+//! underrun.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::render_callback::{self, data};
+//! use coreaudio::audio_unit::ring_buffer;
+//! use coreaudio::audio_unit::{AudioUnit, IOType};
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! let mut input = AudioUnit::new(IOType::HalOutput)?;
+//! let mut output = AudioUnit::new(IOType::DefaultOutput)?;
+//! let (mut producer, mut consumer) = ring_buffer::non_interleaved::<f32>(2, 4096);
+//! type Args = render_callback::Args<data::NonInterleaved<f32>>;
+//! input.set_input_callback(move |args: Args| {
+//!     producer.push_data(&args.data);
+//!     Ok(())
+//! })?;
+//! output.set_render_callback(move |mut args: Args| {
+//!     let popped = consumer.pop_data(&mut args.data);
+//!     for channel in args.data.channels_mut() {
+//!         channel[popped..].fill(0.0);
+//!     }
+//!     Ok(())
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::render_callback::data::{Interleaved, NonInterleaved};
+use super::Sample;
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// How the frames of a ring buffer are stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Layout {
+    /// The samples of each frame are stored next to each other.
+    Interleaved,
+    /// The samples of each channel are stored next to each other.
+    NonInterleaved,
+}
+
+/// The state shared between a **Producer** and its **Consumer**.
+struct Shared<S> {
+    slots: Box<[UnsafeCell<MaybeUninit<S>>]>,
+    layout: Layout,
+    channels: usize,
+    capacity: usize,
+    // The total number of frames read and written. Only the consumer stores to `read` and only
+    // the producer stores to `write`.
+    read: AtomicUsize,
+    write: AtomicUsize,
+    underruns: AtomicUsize,
+    overruns: AtomicUsize,
+}
+
+// Each slot is only accessed by one end at a time, as determined by `read` and `write`.
+unsafe impl<S: Send> Send for Shared<S> {}
+unsafe impl<S: Send> Sync for Shared<S> {}
+
+impl<S: Copy> Shared<S> {
+    fn new(layout: Layout, channels: usize, capacity: usize) -> Self {
+        assert!(channels > 0, "a ring buffer needs at least one channel");
+        assert!(
+            capacity > 0,
+            "a ring buffer needs room for at least one frame"
+        );
+        let slots = (0..channels * capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        Shared {
+            slots,
+            layout,
+            channels,
+            capacity,
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            underruns: AtomicUsize::new(0),
+            overruns: AtomicUsize::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        let read = self.read.load(Ordering::Acquire);
+        write.wrapping_sub(read)
+    }
+
+    fn slot(&self, index: usize) -> *mut S {
+        self.slots[index].get() as *mut S
+    }
+
+    /// The index of the slot holding the given channel of the frame at position `frame`.
+    fn index(&self, frame: usize, channel: usize) -> usize {
+        match self.layout {
+            Layout::Interleaved => frame * self.channels + channel,
+            Layout::NonInterleaved => channel * self.capacity + frame,
+        }
+    }
+
+    /// Call `f` with the contiguous runs of frame positions making up `len` frames from `pos`,
+    /// along with the offset of each run from `pos`.
+    fn for_each_run(&self, pos: usize, len: usize, mut f: impl FnMut(usize, usize, usize)) {
+        let start = pos % self.capacity;
+        let first = len.min(self.capacity - start);
+        f(start, first, 0);
+        if first < len {
+            f(0, len - first, first);
+        }
+    }
+
+    /// Copy `frames` frames of interleaved samples from `src` into the slots from `pos`.
+    ///
+    /// # Safety
+    ///
+    /// The slots must be vacant and `src` must hold `frames * channels` samples.
+    unsafe fn write_interleaved(&self, pos: usize, frames: usize, src: &[S]) {
+        let channels = self.channels;
+        self.for_each_run(pos, frames, |start, len, offset| {
+            let src = &src[offset * channels..(offset + len) * channels];
+            if self.layout == Layout::Interleaved {
+                let dst = self.slot(self.index(start, 0));
+                ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+            } else {
+                for (frame, samples) in src.chunks_exact(channels).enumerate() {
+                    for (channel, &sample) in samples.iter().enumerate() {
+                        *self.slot(self.index(start + frame, channel)) = sample;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Copy `frames` frames of interleaved samples from the slots from `pos` into `dst`.
+    ///
+    /// # Safety
+    ///
+    /// The slots must be occupied and `dst` must hold `frames * channels` samples.
+    unsafe fn read_interleaved(&self, pos: usize, frames: usize, dst: &mut [S]) {
+        let channels = self.channels;
+        self.for_each_run(pos, frames, |start, len, offset| {
+            let dst = &mut dst[offset * channels..(offset + len) * channels];
+            if self.layout == Layout::Interleaved {
+                let src = self.slot(self.index(start, 0));
+                ptr::copy_nonoverlapping(src, dst.as_mut_ptr(), dst.len());
+            } else {
+                for (frame, samples) in dst.chunks_exact_mut(channels).enumerate() {
+                    for (channel, sample) in samples.iter_mut().enumerate() {
+                        *sample = *self.slot(self.index(start + frame, channel));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Copy the first `frames` samples of `src` into the given channel of the slots from `pos`.
+    ///
+    /// # Safety
+    ///
+    /// The slots must be vacant.
+    unsafe fn write_channel(&self, pos: usize, frames: usize, channel: usize, src: &[S]) {
+        self.for_each_run(pos, frames, |start, len, offset| {
+            let src = &src[offset..offset + len];
+            if self.layout == Layout::NonInterleaved {
+                let dst = self.slot(self.index(start, channel));
+                ptr::copy_nonoverlapping(src.as_ptr(), dst, len);
+            } else {
+                for (frame, &sample) in src.iter().enumerate() {
+                    *self.slot(self.index(start + frame, channel)) = sample;
+                }
+            }
+        });
+    }
+
+    /// Copy `frames` samples of the given channel of the slots from `pos` into `dst`.
+    ///
+    /// # Safety
+    ///
+    /// The slots must be occupied.
+    unsafe fn read_channel(&self, pos: usize, frames: usize, channel: usize, dst: &mut [S]) {
+        self.for_each_run(pos, frames, |start, len, offset| {
+            let dst = &mut dst[offset..offset + len];
+            if self.layout == Layout::NonInterleaved {
+                let src = self.slot(self.index(start, channel));
+                ptr::copy_nonoverlapping(src, dst.as_mut_ptr(), len);
+            } else {
+                for (frame, sample) in dst.iter_mut().enumerate() {
+                    *sample = *self.slot(self.index(start + frame, channel));
+                }
+            }
+        });
+    }
+}
+
+/// The writing end of a ring buffer.
+pub struct Producer<S> {
+    shared: Arc<Shared<S>>,
+}
+
+/// The reading end of a ring buffer.
+pub struct Consumer<S> {
+    shared: Arc<Shared<S>>,
+}
+
+/// Create a ring buffer holding up to `capacity` frames of `channels` channels, stored
+/// interleaved.
+///
+/// Interleaved samples are pushed and popped with contiguous copies.
+pub fn interleaved<S>(channels: usize, capacity: usize) -> (Producer<S>, Consumer<S>)
+where
+    S: Sample + Copy + Send,
+{
+    split(Shared::new(Layout::Interleaved, channels, capacity))
+}
+
+/// Create a ring buffer holding up to `capacity` frames of `channels` channels, with the samples
+/// of each channel stored together.
+///
+/// Per-channel slices are pushed and popped with contiguous copies.
+pub fn non_interleaved<S>(channels: usize, capacity: usize) -> (Producer<S>, Consumer<S>)
+where
+    S: Sample + Copy + Send,
+{
+    split(Shared::new(Layout::NonInterleaved, channels, capacity))
+}
+
+fn split<S>(shared: Shared<S>) -> (Producer<S>, Consumer<S>) {
+    let shared = Arc::new(shared);
+    let producer = Producer {
+        shared: shared.clone(),
+    };
+    (producer, Consumer { shared })
+}
+
+/// Implement the fill level and counter queries shared by both ends.
+macro_rules! impl_queries {
+    ($($End:ident),*) => {$(
+        impl<S: Copy> $End<S> {
+            /// The number of channels in each frame.
+            pub fn channels(&self) -> usize {
+                self.shared.channels
+            }
+
+            /// The maximum number of frames the buffer holds.
+            pub fn capacity(&self) -> usize {
+                self.shared.capacity
+            }
+
+            /// The number of frames in the buffer, waiting to be popped.
+            pub fn len(&self) -> usize {
+                self.shared.len()
+            }
+
+            /// The number of frames that may be pushed before the buffer is full.
+            pub fn free_len(&self) -> usize {
+                self.shared.capacity - self.shared.len()
+            }
+
+            /// Returns `true` if there are no frames in the buffer.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Returns `true` if no more frames may be pushed.
+            pub fn is_full(&self) -> bool {
+                self.free_len() == 0
+            }
+
+            /// The fraction of the capacity that is filled, between `0.0` and `1.0`.
+            pub fn fill_level(&self) -> f64 {
+                self.len() as f64 / self.shared.capacity as f64
+            }
+
+            /// The number of pops that could not be completed because too few frames were in
+            /// the buffer.
+            pub fn underruns(&self) -> usize {
+                self.shared.underruns.load(Ordering::Relaxed)
+            }
+
+            /// The number of pushes that could not be completed because the buffer was full.
+            pub fn overruns(&self) -> usize {
+                self.shared.overruns.load(Ordering::Relaxed)
+            }
+        }
+    )*};
+}
+
+impl_queries!(Producer, Consumer);
+
+impl<S: Copy> Producer<S> {
+    /// Reserve room for up to `frames` frames, counting an overrun if there is not room for all
+    /// of them. Returns the write position and the number of frames that fit.
+    fn reserve(&self, frames: usize) -> (usize, usize) {
+        let shared = &*self.shared;
+        let write = shared.write.load(Ordering::Relaxed);
+        let read = shared.read.load(Ordering::Acquire);
+        let free = shared.capacity - write.wrapping_sub(read);
+        if frames > free {
+            shared.overruns.fetch_add(1, Ordering::Relaxed);
+        }
+        (write, frames.min(free))
+    }
+
+    fn commit(&self, write: usize, frames: usize) {
+        let write = write.wrapping_add(frames);
+        self.shared.write.store(write, Ordering::Release);
+    }
+
+    /// Push interleaved frames, returning the number of frames pushed.
+    ///
+    /// Panics if the length of `samples` is not a multiple of the number of channels.
+    pub fn push_interleaved(&mut self, samples: &[S]) -> usize {
+        let channels = self.shared.channels;
+        assert_eq!(
+            samples.len() % channels,
+            0,
+            "the samples must hold whole frames of {} channels",
+            channels
+        );
+        let (write, frames) = self.reserve(samples.len() / channels);
+        unsafe { self.shared.write_interleaved(write, frames, samples) };
+        self.commit(write, frames);
+        frames
+    }
+
+    /// Push frames given as one slice per channel, returning the number of frames pushed.
+    ///
+    /// Panics if the number of slices is not the number of channels, or if they differ in length.
+    pub fn push_channels<'a, I>(&mut self, channels: I) -> usize
+    where
+        I: IntoIterator<Item = &'a [S]>,
+        S: 'a,
+    {
+        let mut channels = channels.into_iter().peekable();
+        let requested = channels.peek().map_or(0, |channel| channel.len());
+        let (write, frames) = self.reserve(requested);
+        let mut count = 0;
+        for (channel, samples) in channels.enumerate() {
+            assert!(
+                channel < self.shared.channels,
+                "too many channels were pushed"
+            );
+            assert_eq!(samples.len(), requested, "the channels differ in length");
+            unsafe { self.shared.write_channel(write, frames, channel, samples) };
+            count += 1;
+        }
+        assert_eq!(count, self.shared.channels, "too few channels were pushed");
+        self.commit(write, frames);
+        frames
+    }
+
+    /// Push a single interleaved frame, returning `false` if the buffer is full.
+    ///
+    /// Panics if the length of `frame` is not the number of channels.
+    pub fn push_frame(&mut self, frame: &[S]) -> bool {
+        assert_eq!(frame.len(), self.shared.channels, "not a single frame");
+        self.push_interleaved(frame) == 1
+    }
+
+    /// Push the `data` of the **Args** given to a callback, returning the number of frames
+    /// pushed.
+    pub fn push_data<D: RingData<S>>(&mut self, data: &D) -> usize {
+        data.push_to(self)
+    }
+}
+
+impl<S: Copy> Consumer<S> {
+    /// Claim up to `frames` frames, counting an underrun if fewer are in the buffer. Returns the
+    /// read position and the number of frames available.
+    fn claim(&self, frames: usize) -> (usize, usize) {
+        let shared = &*self.shared;
+        let read = shared.read.load(Ordering::Relaxed);
+        let write = shared.write.load(Ordering::Acquire);
+        let len = write.wrapping_sub(read);
+        if frames > len {
+            shared.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+        (read, frames.min(len))
+    }
+
+    fn release(&self, read: usize, frames: usize) {
+        let read = read.wrapping_add(frames);
+        self.shared.read.store(read, Ordering::Release);
+    }
+
+    /// Pop interleaved frames into `samples`, returning the number of frames popped.
+    ///
+    /// If fewer frames are in the buffer than `samples` holds, the remaining samples are left
+    /// unchanged so that the caller may fill them, e.g. with silence.
+    ///
+    /// Panics if the length of `samples` is not a multiple of the number of channels.
+    pub fn pop_interleaved(&mut self, samples: &mut [S]) -> usize {
+        let channels = self.shared.channels;
+        assert_eq!(
+            samples.len() % channels,
+            0,
+            "the samples must hold whole frames of {} channels",
+            channels
+        );
+        let (read, frames) = self.claim(samples.len() / channels);
+        unsafe { self.shared.read_interleaved(read, frames, samples) };
+        self.release(read, frames);
+        frames
+    }
+
+    /// Pop frames into one slice per channel, returning the number of frames popped.
+    ///
+    /// If fewer frames are in the buffer than the slices hold, the remaining samples are left
+    /// unchanged so that the caller may fill them, e.g. with silence.
+    ///
+    /// Panics if the number of slices is not the number of channels, or if they differ in length.
+    pub fn pop_channels<'a, I>(&mut self, channels: I) -> usize
+    where
+        I: IntoIterator<Item = &'a mut [S]>,
+        S: 'a,
+    {
+        let mut channels = channels.into_iter().peekable();
+        let requested = channels.peek().map_or(0, |channel| channel.len());
+        let (read, frames) = self.claim(requested);
+        let mut count = 0;
+        for (channel, samples) in channels.enumerate() {
+            assert!(
+                channel < self.shared.channels,
+                "too many channels were popped"
+            );
+            assert_eq!(samples.len(), requested, "the channels differ in length");
+            unsafe { self.shared.read_channel(read, frames, channel, samples) };
+            count += 1;
+        }
+        assert_eq!(count, self.shared.channels, "too few channels were popped");
+        self.release(read, frames);
+        frames
+    }
+
+    /// Pop a single interleaved frame into `frame`, returning `false` if the buffer is empty.
+    ///
+    /// Panics if the length of `frame` is not the number of channels.
+    pub fn pop_frame(&mut self, frame: &mut [S]) -> bool {
+        assert_eq!(frame.len(), self.shared.channels, "not a single frame");
+        self.pop_interleaved(frame) == 1
+    }
+
+    /// Discard up to `frames` frames, returning the number of frames discarded.
+    ///
+    /// Discarding fewer frames than requested is not counted as an underrun.
+    pub fn skip(&mut self, frames: usize) -> usize {
+        let read = self.shared.read.load(Ordering::Relaxed);
+        let frames = frames.min(self.shared.len());
+        self.release(read, frames);
+        frames
+    }
+
+    /// Pop into the `data` of the **Args** given to a callback, returning the number of frames
+    /// popped.
+    ///
+    /// If fewer frames are in the buffer than `data` holds, the remaining frames are left
+    /// unchanged.
+    pub fn pop_data<D: RingData<S>>(&mut self, data: &mut D) -> usize {
+        data.pop_from(self)
+    }
+}
+
+/// Callback data that may be pushed to a **Producer** and popped from a **Consumer**.
+pub trait RingData<S> {
+    /// Push the frames of `self`, returning the number of frames pushed.
+    fn push_to(&self, producer: &mut Producer<S>) -> usize;
+    /// Pop frames into `self`, returning the number of frames popped.
+    fn pop_from(&mut self, consumer: &mut Consumer<S>) -> usize;
+}
+
+impl<S: Sample + Copy> RingData<S> for Interleaved<S> {
+    fn push_to(&self, producer: &mut Producer<S>) -> usize {
+        producer.push_interleaved(&*self.buffer)
+    }
+
+    fn pop_from(&mut self, consumer: &mut Consumer<S>) -> usize {
+        consumer.pop_interleaved(&mut *self.buffer)
+    }
+}
+
+impl<S: Sample + Copy> RingData<S> for NonInterleaved<S> {
+    fn push_to(&self, producer: &mut Producer<S>) -> usize {
+        producer.push_channels(self.channels())
+    }
+
+    fn pop_from(&mut self, consumer: &mut Consumer<S>) -> usize {
+        consumer.pop_channels(self.channels_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::render_callback::Data;
+    use crate::sys::{AudioBuffer, AudioBufferList};
+    use std::os::raw::c_void;
+    use std::thread;
+
+    #[test]
+    fn test_wraps_and_counts_overruns_and_underruns() {
+        let (mut producer, mut consumer) = interleaved::<i16>(2, 4);
+        assert_eq!(producer.push_interleaved(&[1, 2, 3, 4, 5, 6]), 3);
+        assert_eq!(consumer.len(), 3);
+        assert_eq!(producer.free_len(), 1);
+        let mut frame = [0; 2];
+        assert!(consumer.pop_frame(&mut frame));
+        assert_eq!(frame, [1, 2]);
+
+        // Only two of the three frames fit, and they wrap around the end of the buffer.
+        assert_eq!(producer.push_interleaved(&[7, 8, 9, 10, 11, 12]), 2);
+        assert_eq!(producer.overruns(), 1);
+        assert!(producer.is_full());
+        assert_eq!(consumer.fill_level(), 1.0);
+
+        let mut samples = [0; 10];
+        assert_eq!(consumer.pop_interleaved(&mut samples), 4);
+        assert_eq!(samples, [3, 4, 5, 6, 7, 8, 9, 10, 0, 0]);
+        assert_eq!(consumer.underruns(), 1);
+        assert!(consumer.is_empty());
+        assert!(!consumer.pop_frame(&mut frame));
+        assert_eq!(producer.underruns(), 2);
+    }
+
+    #[test]
+    fn test_converts_between_layouts() {
+        for &layout in &[Layout::Interleaved, Layout::NonInterleaved] {
+            let (mut producer, mut consumer) = split(Shared::<f32>::new(layout, 2, 3));
+            for _ in 0..4 {
+                let left = [1.0, 2.0];
+                let right = [-1.0, -2.0];
+                assert_eq!(
+                    producer.push_channels(vec![&left[..], &right[..]]),
+                    2,
+                    "{:?}",
+                    layout
+                );
+                let mut samples = [0.0; 4];
+                assert_eq!(consumer.pop_interleaved(&mut samples), 2, "{:?}", layout);
+                assert_eq!(samples, [1.0, -1.0, 2.0, -2.0], "{:?}", layout);
+
+                assert_eq!(producer.push_interleaved(&samples), 2, "{:?}", layout);
+                let (mut left, mut right) = ([0.0; 2], [0.0; 2]);
+                let channels = vec![&mut left[..], &mut right[..]];
+                assert_eq!(consumer.pop_channels(channels), 2, "{:?}", layout);
+                assert_eq!((left, right), ([1.0, 2.0], [-1.0, -2.0]), "{:?}", layout);
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_and_pop_callback_data() {
+        let mut samples = [1.0f32, 2.0, 3.0, 4.0];
+        let mut list = AudioBufferList {
+            mNumberBuffers: 1,
+            mBuffers: [AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: 16,
+                mData: samples.as_mut_ptr() as *mut c_void,
+            }],
+        };
+        let mut data = unsafe { Interleaved::<f32>::from_input_proc_args(2, &mut list) }.unwrap();
+        let (mut producer, mut consumer) = non_interleaved::<f32>(2, 8);
+        assert_eq!(producer.push_data(&data), 2);
+        data.buffer.fill(0.0);
+        assert_eq!(consumer.skip(1), 1);
+        assert_eq!(consumer.pop_data(&mut data), 1);
+        assert_eq!(samples, [3.0, 4.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_concurrent_transfer() {
+        const FRAMES: i32 = 20_000;
+        let (mut producer, mut consumer) = interleaved::<i32>(1, 64);
+        let writer = thread::spawn(move || {
+            let mut next = 0;
+            while next < FRAMES {
+                let chunk: Vec<i32> = (next..(next + 7).min(FRAMES)).collect();
+                match producer.push_interleaved(&chunk) {
+                    0 => thread::yield_now(),
+                    pushed => next += pushed as i32,
+                }
+            }
+        });
+        let mut expected = 0;
+        let mut chunk = [0i32; 5];
+        while expected < FRAMES {
+            let popped = consumer.pop_interleaved(&mut chunk);
+            if popped == 0 {
+                thread::yield_now();
+            }
+            for &sample in &chunk[..popped] {
+                assert_eq!(sample, expected);
+                expected += 1;
+            }
+        }
+        writer.join().unwrap();
+    }
+}
@@ -1,8 +1,15 @@
 use super::audio_format::{self, LinearPcmFlags};
+use std::slice;
 
 /// Dynamic representation of audio data sample format.
+///
+/// More formats may be supported in future versions, so matches on a **SampleFormat** need a
+/// wildcard arm.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SampleFormat {
+    /// 64-bit float.
+    F64,
     /// 32-bit float.
     F32,
     /// 32-bit signed integer.
@@ -13,20 +20,28 @@ pub enum SampleFormat {
     I16,
     /// 8-bit signed integer.
     I8,
+    /// 16-bit unsigned integer.
+    U16,
+    /// 8-bit unsigned integer.
+    U8,
 }
 
 impl SampleFormat {
     /// Check if the format flags are appropriate for the given format.
+    ///
+    /// The byte order given by the `IS_BIG_ENDIAN` flag is not considered here, see
+    /// `Sample::does_match_flags`.
     pub fn does_match_flags(&self, flags: audio_format::LinearPcmFlags) -> bool {
         let is_float = flags.contains(LinearPcmFlags::IS_FLOAT);
         let is_signed_integer = flags.contains(LinearPcmFlags::IS_SIGNED_INTEGER);
         let is_packed = flags.contains(LinearPcmFlags::IS_PACKED);
         match *self {
-            SampleFormat::F32 => is_float && !is_signed_integer && is_packed,
+            SampleFormat::F64 | SampleFormat::F32 => is_float && !is_signed_integer && is_packed,
             SampleFormat::I32 | SampleFormat::I16 | SampleFormat::I8 => {
                 is_signed_integer && !is_float && is_packed
             }
             SampleFormat::I24 => is_signed_integer && !is_float,
+            SampleFormat::U16 | SampleFormat::U8 => !is_signed_integer && !is_float && is_packed,
         }
     }
 
@@ -39,6 +54,7 @@ impl SampleFormat {
         let sample_format = if flags.contains(LinearPcmFlags::IS_FLOAT) {
             match (bits_per_sample, packed) {
                 (32, true) => SampleFormat::F32,
+                (64, true) => SampleFormat::F64,
                 _ => return None,
             }
         } else if flags.contains(LinearPcmFlags::IS_SIGNED_INTEGER) {
@@ -50,8 +66,12 @@ impl SampleFormat {
                 _ => return None,
             }
         } else {
-            // TODO: Check whether or not we need to consider other formats, like unsigned ints.
-            return None;
+            // Neither float nor signed, so the samples are unsigned integers.
+            match (bits_per_sample, packed) {
+                (8, true) => SampleFormat::U8,
+                (16, true) => SampleFormat::U16,
+                _ => return None,
+            }
         };
         Some(sample_format)
     }
@@ -60,22 +80,39 @@ impl SampleFormat {
     pub fn size_in_bytes(&self) -> usize {
         use std::mem::size_of;
         match *self {
+            SampleFormat::F64 => size_of::<f64>(),
             SampleFormat::F32 => size_of::<f32>(),
             SampleFormat::I32 => size_of::<i32>(),
             SampleFormat::I24 => 3 * size_of::<u8>(),
             SampleFormat::I16 => size_of::<i16>(),
             SampleFormat::I8 => size_of::<i8>(),
+            SampleFormat::U16 => size_of::<u16>(),
+            SampleFormat::U8 => size_of::<u8>(),
+        }
+    }
+
+    /// Return the size of one sample in bytes for the given format flags.
+    ///
+    /// This differs from `size_in_bytes` only for I24 samples that are not packed, which occupy
+    /// the high or low 24 bits of 4 bytes.
+    pub fn size_in_bytes_with_flags(&self, flags: audio_format::LinearPcmFlags) -> usize {
+        match *self {
+            SampleFormat::I24 if !flags.contains(LinearPcmFlags::IS_PACKED) => 4,
+            _ => self.size_in_bytes(),
         }
     }
 
     /// Return the number of valid bits for one sample.
     pub fn size_in_bits(&self) -> u32 {
         match *self {
+            SampleFormat::F64 => 64,
             SampleFormat::F32 => 32,
             SampleFormat::I32 => 32,
             SampleFormat::I24 => 24,
             SampleFormat::I16 => 16,
             SampleFormat::I8 => 8,
+            SampleFormat::U16 => 16,
+            SampleFormat::U8 => 8,
         }
     }
 }
@@ -84,6 +121,31 @@ impl SampleFormat {
 pub trait Sample {
     /// Dynamic representation of audio data sample format.
     fn sample_format() -> SampleFormat;
+
+    /// Check if the format flags describe the memory layout of this sample type.
+    ///
+    /// By default this defers to the flags check of the `SampleFormat` and requires the samples
+    /// to be in native byte order. Use [**BigEndian**](./struct.BigEndian.html) or
+    /// [**LittleEndian**](./struct.LittleEndian.html) to access samples in a specific byte order.
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        let format = Self::sample_format();
+        format.does_match_flags(flags) && is_native_endian(format, flags)
+    }
+}
+
+/// Whether the byte order described by the flags is the native byte order.
+///
+/// Single byte formats have no byte order, so they are always considered native.
+fn is_native_endian(format: SampleFormat, flags: audio_format::LinearPcmFlags) -> bool {
+    format.size_in_bytes() == 1
+        || flags.contains(LinearPcmFlags::IS_BIG_ENDIAN) == cfg!(target_endian = "big")
+}
+
+/// Return the flags with the `IS_BIG_ENDIAN` flag describing the native byte order.
+fn with_native_endian(flags: audio_format::LinearPcmFlags) -> audio_format::LinearPcmFlags {
+    let mut flags = flags;
+    flags.set(LinearPcmFlags::IS_BIG_ENDIAN, cfg!(target_endian = "big"));
+    flags
 }
 
 /// Simplified implementation of the `Sample` trait for sample types.
@@ -100,4 +162,489 @@ macro_rules! impl_sample {
     }
 }
 
-impl_sample!(f32 F32, i32 I32, i16 I16, i8 I8);
+impl_sample!(f64 F64, f32 F32, i32 I32, i16 I16, i8 I8, u16 U16, u8 U8);
+
+/// The smallest value representable by a 24-bit signed integer.
+const I24_MIN: i32 = -(1 << 23);
+/// The largest value representable by a 24-bit signed integer.
+const I24_MAX: i32 = (1 << 23) - 1;
+
+/// A packed 24-bit signed integer sample, stored as 3 native-endian bytes.
+///
+/// This is the layout used by I24 stream formats with the `IS_PACKED` flag set.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct I24([u8; 3]);
+
+impl I24 {
+    /// The smallest value an `I24` can hold.
+    pub const MIN: i32 = I24_MIN;
+    /// The largest value an `I24` can hold.
+    pub const MAX: i32 = I24_MAX;
+
+    /// Create a sample from the given value, clamped to the 24-bit range.
+    pub fn new(value: i32) -> Self {
+        let mut sample = I24::default();
+        sample.set(value);
+        sample
+    }
+
+    /// Create a sample from its native-endian byte representation.
+    pub fn from_ne_bytes(bytes: [u8; 3]) -> Self {
+        I24(bytes)
+    }
+
+    /// Return the native-endian byte representation of the sample.
+    pub fn to_ne_bytes(self) -> [u8; 3] {
+        self.0
+    }
+
+    /// Read the value of the sample.
+    pub fn get(self) -> i32 {
+        let [a, b, c] = self.0;
+        // Place the 3 bytes in the high bytes of an `i32` and shift back to sign-extend.
+        let bytes = if cfg!(target_endian = "little") {
+            [0, a, b, c]
+        } else {
+            [a, b, c, 0]
+        };
+        i32::from_ne_bytes(bytes) >> 8
+    }
+
+    /// Write the value of the sample, clamped to the 24-bit range.
+    pub fn set(&mut self, value: i32) {
+        let [a, b, c, d] = (value.clamp(I24_MIN, I24_MAX) << 8).to_ne_bytes();
+        self.0 = if cfg!(target_endian = "little") {
+            [b, c, d]
+        } else {
+            [a, b, c]
+        };
+    }
+
+    /// View a buffer of packed bytes as a slice of samples.
+    ///
+    /// Returns `None` if the length of `bytes` is not a multiple of 3.
+    pub fn slice_from_bytes(bytes: &[u8]) -> Option<&[I24]> {
+        if bytes.len() % 3 != 0 {
+            return None;
+        }
+        let len = bytes.len() / 3;
+        // `I24` is a transparent wrapper around `[u8; 3]` so it has an alignment of 1.
+        Some(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const I24, len) })
+    }
+
+    /// View a buffer of packed bytes as a mutable slice of samples.
+    ///
+    /// Returns `None` if the length of `bytes` is not a multiple of 3.
+    pub fn slice_from_bytes_mut(bytes: &mut [u8]) -> Option<&mut [I24]> {
+        if bytes.len() % 3 != 0 {
+            return None;
+        }
+        let len = bytes.len() / 3;
+        Some(unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut I24, len) })
+    }
+}
+
+impl From<I24> for i32 {
+    fn from(sample: I24) -> i32 {
+        sample.get()
+    }
+}
+
+impl Sample for I24 {
+    fn sample_format() -> SampleFormat {
+        SampleFormat::I24
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        SampleFormat::I24.does_match_flags(flags)
+            && is_native_endian(SampleFormat::I24, flags)
+            && flags.contains(LinearPcmFlags::IS_PACKED)
+    }
+}
+
+/// A 24-bit signed integer sample occupying the high 24 bits of a native-endian 32-bit word.
+///
+/// This is the layout used by I24 stream formats with `IS_ALIGNED_HIGH` set and `IS_PACKED`
+/// clear.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct I24AlignedHigh(i32);
+
+impl I24AlignedHigh {
+    /// Create a sample from the given value, clamped to the 24-bit range.
+    pub fn new(value: i32) -> Self {
+        let mut sample = I24AlignedHigh::default();
+        sample.set(value);
+        sample
+    }
+
+    /// Create a sample from the raw 32-bit word.
+    pub fn from_bits(bits: i32) -> Self {
+        I24AlignedHigh(bits)
+    }
+
+    /// Return the raw 32-bit word.
+    pub fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// Read the value of the sample.
+    pub fn get(self) -> i32 {
+        self.0 >> 8
+    }
+
+    /// Write the value of the sample, clamped to the 24-bit range.
+    pub fn set(&mut self, value: i32) {
+        self.0 = value.clamp(I24_MIN, I24_MAX) << 8;
+    }
+}
+
+impl From<I24AlignedHigh> for i32 {
+    fn from(sample: I24AlignedHigh) -> i32 {
+        sample.get()
+    }
+}
+
+impl Sample for I24AlignedHigh {
+    fn sample_format() -> SampleFormat {
+        SampleFormat::I24
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        SampleFormat::I24.does_match_flags(flags)
+            && is_native_endian(SampleFormat::I24, flags)
+            && !flags.contains(LinearPcmFlags::IS_PACKED)
+            && flags.contains(LinearPcmFlags::IS_ALIGNED_HIGH)
+    }
+}
+
+/// A 24-bit signed integer sample occupying the low 24 bits of a native-endian 32-bit word.
+///
+/// This is the layout used by I24 stream formats with both `IS_ALIGNED_HIGH` and `IS_PACKED`
+/// clear.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct I24AlignedLow(i32);
+
+impl I24AlignedLow {
+    /// Create a sample from the given value, clamped to the 24-bit range.
+    pub fn new(value: i32) -> Self {
+        let mut sample = I24AlignedLow::default();
+        sample.set(value);
+        sample
+    }
+
+    /// Create a sample from the raw 32-bit word.
+    pub fn from_bits(bits: i32) -> Self {
+        I24AlignedLow(bits)
+    }
+
+    /// Return the raw 32-bit word.
+    pub fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// Read the value of the sample.
+    ///
+    /// The high 8 bits of the word are ignored.
+    pub fn get(self) -> i32 {
+        (self.0 << 8) >> 8
+    }
+
+    /// Write the value of the sample, clamped to the 24-bit range.
+    pub fn set(&mut self, value: i32) {
+        self.0 = value.clamp(I24_MIN, I24_MAX);
+    }
+}
+
+impl From<I24AlignedLow> for i32 {
+    fn from(sample: I24AlignedLow) -> i32 {
+        sample.get()
+    }
+}
+
+impl Sample for I24AlignedLow {
+    fn sample_format() -> SampleFormat {
+        SampleFormat::I24
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        SampleFormat::I24.does_match_flags(flags)
+            && is_native_endian(SampleFormat::I24, flags)
+            && !flags.intersects(LinearPcmFlags::IS_PACKED | LinearPcmFlags::IS_ALIGNED_HIGH)
+    }
+}
+
+/// A signed 32-bit fixed-point sample with `FRAC` fractional bits.
+///
+/// This matches I32 stream formats whose flags hold `FRAC` in the `FLAGS_SAMPLE_FRACTION_MASK`
+/// bitfield (see [**LinearPcmFlags::fraction_bits**](../audio_format/struct.LinearPcmFlags.html#method.fraction_bits)).
+/// The canonical iOS audio unit format uses [**Fixed8_24**](./type.Fixed8_24.html) samples.
+///
+/// Note that `i32` also matches fixed-point streams, giving access to the raw sample words.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FixedPoint<const FRAC: u32>(i32);
+
+/// The 8.24 fixed-point format used by audio units on iOS.
+pub type Fixed8_24 = FixedPoint<24>;
+
+impl<const FRAC: u32> FixedPoint<FRAC> {
+    /// The value of `1.0` as a raw fixed-point word.
+    const SCALE: f32 = (1u64 << FRAC) as f32;
+
+    /// Create a sample from the raw fixed-point word.
+    pub fn from_bits(bits: i32) -> Self {
+        FixedPoint(bits)
+    }
+
+    /// Return the raw fixed-point word.
+    pub fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// Convert a float to the nearest fixed-point value, saturating at the limits of the format.
+    ///
+    /// `NaN` is converted to zero.
+    pub fn from_f32(value: f32) -> Self {
+        // Float to integer `as` casts saturate and map `NaN` to zero.
+        FixedPoint((value * Self::SCALE).round() as i32)
+    }
+
+    /// Convert the sample to a float.
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::SCALE
+    }
+}
+
+impl<const FRAC: u32> From<f32> for FixedPoint<FRAC> {
+    fn from(value: f32) -> Self {
+        FixedPoint::from_f32(value)
+    }
+}
+
+impl<const FRAC: u32> From<FixedPoint<FRAC>> for f32 {
+    fn from(sample: FixedPoint<FRAC>) -> f32 {
+        sample.to_f32()
+    }
+}
+
+impl<const FRAC: u32> Sample for FixedPoint<FRAC> {
+    fn sample_format() -> SampleFormat {
+        SampleFormat::I32
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        SampleFormat::I32.does_match_flags(flags)
+            && is_native_endian(SampleFormat::I32, flags)
+            && flags.fraction_bits() == FRAC
+    }
+}
+
+/// Sample types whose byte order can be reversed.
+pub trait SwapBytes: Copy {
+    /// Reverse the byte order of the sample.
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! impl_swap_bytes_int {
+    ($($T:ident),* $(,)*) => {
+        $(
+            impl SwapBytes for $T {
+                fn swap_bytes(self) -> Self {
+                    $T::swap_bytes(self)
+                }
+            }
+        )*
+    }
+}
+
+impl_swap_bytes_int!(i32, i16, i8, u16, u8);
+
+impl SwapBytes for f64 {
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl SwapBytes for f32 {
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl SwapBytes for I24 {
+    fn swap_bytes(self) -> Self {
+        let [a, b, c] = self.0;
+        I24([c, b, a])
+    }
+}
+
+impl<const FRAC: u32> SwapBytes for FixedPoint<FRAC> {
+    fn swap_bytes(self) -> Self {
+        FixedPoint(self.0.swap_bytes())
+    }
+}
+
+impl SwapBytes for I24AlignedHigh {
+    fn swap_bytes(self) -> Self {
+        I24AlignedHigh(self.0.swap_bytes())
+    }
+}
+
+impl SwapBytes for I24AlignedLow {
+    fn swap_bytes(self) -> Self {
+        I24AlignedLow(self.0.swap_bytes())
+    }
+}
+
+/// A sample of type `S` stored in big-endian byte order.
+///
+/// This matches stream formats with the `IS_BIG_ENDIAN` flag set.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BigEndian<S>(S);
+
+impl<S: SwapBytes> BigEndian<S> {
+    /// Store the given native-endian sample in big-endian byte order.
+    pub fn new(sample: S) -> Self {
+        BigEndian(to_big_endian(sample))
+    }
+
+    /// Read the sample in native byte order.
+    pub fn get(self) -> S {
+        to_big_endian(self.0)
+    }
+
+    /// Write the given native-endian sample.
+    pub fn set(&mut self, sample: S) {
+        self.0 = to_big_endian(sample);
+    }
+}
+
+impl<S: Sample> Sample for BigEndian<S> {
+    fn sample_format() -> SampleFormat {
+        S::sample_format()
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        flags.contains(LinearPcmFlags::IS_BIG_ENDIAN)
+            && S::does_match_flags(with_native_endian(flags))
+    }
+}
+
+/// A sample of type `S` stored in little-endian byte order.
+///
+/// This matches stream formats with the `IS_BIG_ENDIAN` flag clear.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LittleEndian<S>(S);
+
+impl<S: SwapBytes> LittleEndian<S> {
+    /// Store the given native-endian sample in little-endian byte order.
+    pub fn new(sample: S) -> Self {
+        LittleEndian(to_little_endian(sample))
+    }
+
+    /// Read the sample in native byte order.
+    pub fn get(self) -> S {
+        to_little_endian(self.0)
+    }
+
+    /// Write the given native-endian sample.
+    pub fn set(&mut self, sample: S) {
+        self.0 = to_little_endian(sample);
+    }
+}
+
+impl<S: Sample> Sample for LittleEndian<S> {
+    fn sample_format() -> SampleFormat {
+        S::sample_format()
+    }
+
+    fn does_match_flags(flags: audio_format::LinearPcmFlags) -> bool {
+        !flags.contains(LinearPcmFlags::IS_BIG_ENDIAN)
+            && S::does_match_flags(with_native_endian(flags))
+    }
+}
+
+/// Convert between native and big-endian byte order. The conversion is its own inverse.
+fn to_big_endian<S: SwapBytes>(sample: S) -> S {
+    if cfg!(target_endian = "big") {
+        sample
+    } else {
+        sample.swap_bytes()
+    }
+}
+
+/// Convert between native and little-endian byte order. The conversion is its own inverse.
+fn to_little_endian<S: SwapBytes>(sample: S) -> S {
+    if cfg!(target_endian = "little") {
+        sample
+    } else {
+        sample.swap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_i24_round_trip() {
+        for &value in &[0, 1, -1, 1234567, -1234567, I24::MIN, I24::MAX] {
+            assert_eq!(I24::new(value).get(), value);
+            assert_eq!(I24AlignedHigh::new(value).get(), value);
+            assert_eq!(I24AlignedLow::new(value).get(), value);
+        }
+        assert_eq!(I24::new(i32::MAX).get(), I24::MAX);
+        assert_eq!(I24::new(i32::MIN).get(), I24::MIN);
+        assert_eq!(I24::new(-2).to_ne_bytes(), [0xfe, 0xff, 0xff]);
+        assert_eq!(I24AlignedHigh::new(1).to_bits(), 0x100);
+    }
+
+    #[test]
+    fn test_i24_slice_from_bytes() {
+        let mut bytes = [0u8; 6];
+        let samples = I24::slice_from_bytes_mut(&mut bytes).unwrap();
+        samples[1].set(-5);
+        assert_eq!(I24::slice_from_bytes(&bytes).unwrap()[1].get(), -5);
+        assert!(I24::slice_from_bytes(&bytes[..5]).is_none());
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        assert_eq!(Fixed8_24::from_f32(1.0).to_bits(), 1 << 24);
+        assert_eq!(Fixed8_24::from_f32(-0.5).to_f32(), -0.5);
+        assert_eq!(Fixed8_24::from_f32(1000.0).to_bits(), i32::MAX);
+        assert_eq!(FixedPoint::<16>::from_bits(3 << 15).to_f32(), 1.5);
+
+        let flags = LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED;
+        assert!(!<Fixed8_24 as Sample>::does_match_flags(flags));
+        assert!(<Fixed8_24 as Sample>::does_match_flags(
+            flags.with_fraction_bits(24)
+        ));
+        assert!(!<FixedPoint<16> as Sample>::does_match_flags(
+            flags.with_fraction_bits(24)
+        ));
+        assert_eq!(flags.with_fraction_bits(24).fraction_bits(), 24);
+    }
+
+    #[test]
+    fn test_endianness() {
+        let big = BigEndian::new(0x0102i16);
+        assert_eq!(big.0.to_ne_bytes(), [0x01, 0x02]);
+        assert_eq!(big.get(), 0x0102);
+        assert_eq!(LittleEndian::new(1.5f64).get(), 1.5);
+
+        let flags = LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED;
+        let big_flags = flags | LinearPcmFlags::IS_BIG_ENDIAN;
+        assert!(!<i16 as Sample>::does_match_flags(big_flags));
+        assert!(<BigEndian<i16> as Sample>::does_match_flags(big_flags));
+        assert!(!<BigEndian<i16> as Sample>::does_match_flags(flags));
+        assert!(<LittleEndian<i16> as Sample>::does_match_flags(flags));
+        // Single byte samples have no byte order.
+        assert!(<i8 as Sample>::does_match_flags(big_flags));
+    }
+}
@@ -0,0 +1,1236 @@
+//! A pure-Rust host for an [AudioUnit](../struct.AudioUnit.html) that does not require any audio
+//! hardware.
+//!
+//! A simulated **AudioUnit** stores its properties in memory and, once started, calls the
+//! registered render and input callbacks from a background thread at the configured buffer size
+//! and sample rate. This allows the callback, format negotiation and lifecycle code paths used by
+//! [**set_render_callback**](../struct.AudioUnit.html#method.set_render_callback) and
+//! [**set_input_callback**](../struct.AudioUnit.html#method.set_input_callback) to be exercised
+//! in tests and on machines without audio devices.
+//!
+//! Render notifications added with
+//! [**add_render_notify**](../struct.AudioUnit.html#method.add_render_notify) are called before
+//! and after each render of the output element, and property listeners are notified whenever a
+//! property is set, the unit is started or stopped, or a render fails. Input callbacks receive
+//! silence.
+
+use crate::sys::kAudioDevicePropertyBufferFrameSize;
+use crate::sys::{
+    kAudioOutputUnitProperty_IsRunning, kAudioOutputUnitProperty_SetInputCallback,
+    kAudioUnitErr_InvalidParameter, kAudioUnitErr_InvalidProperty,
+    kAudioUnitProperty_LastRenderError, kAudioUnitProperty_SampleRate,
+    kAudioUnitProperty_SetRenderCallback, kAudioUnitProperty_StreamFormat, kAudioUnitScope_Global,
+    kAudioUnitScope_Input, kAudioUnitScope_Output, AURenderCallback, AURenderCallbackStruct,
+    AudioUnitPropertyListenerProc, AudioUnitRenderActionFlags,
+};
+use crate::sys::{
+    kAudio_ParamError, AudioBufferList, AudioStreamBasicDescription, AudioTimeStamp,
+    AudioTimeStampFlags,
+};
+
+use super::audio_buffer_list::AudioBufferListBuf;
+use super::audio_format::LinearPcmFlags;
+use super::backend::Backend;
+use super::parameter::RawParameterEvent;
+use super::{AudioUnit, Element, SampleFormat, StreamFormat, Type};
+use crate::error::Error;
+use crate::OSStatus;
+use std::any::Any;
+use std::collections::HashMap;
+use std::os::raw::{c_uint, c_void};
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+use std::{mem, slice};
+
+/// The configuration of a simulated host.
+#[derive(Copy, Clone, Debug)]
+pub struct SimulatedConfig {
+    /// The sample rate reported by the default stream formats.
+    pub sample_rate: f64,
+    /// The number of frames passed to each callback.
+    pub buffer_frames: u32,
+    /// The number of channels in the default input stream format.
+    pub input_channels: u32,
+    /// The number of channels in the default output stream format.
+    pub output_channels: u32,
+    /// If `true`, the callbacks are called at the rate real hardware would call them.
+    ///
+    /// Otherwise the next cycle starts as soon as the previous one has completed.
+    pub realtime: bool,
+    /// The **Type** reported by [**unit_type**](../struct.AudioUnit.html#method.unit_type).
+    pub unit_type: Option<Type>,
+}
+
+impl Default for SimulatedConfig {
+    fn default() -> Self {
+        SimulatedConfig {
+            sample_rate: 44_100.0,
+            buffer_frames: 512,
+            input_channels: 2,
+            output_channels: 2,
+            realtime: true,
+            unit_type: None,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// Construct an **AudioUnit** backed by a simulated host rather than Audio Toolbox.
+    ///
+    /// The input and output stream formats default to non-interleaved `f32` with the channel
+    /// counts and sample rate given in `config`, and may be changed with
+    /// [**set_stream_format**](./struct.AudioUnit#method.set_stream_format) like those of any
+    /// other unit.
+    ///
+    /// The unit has no Audio Toolbox instance, so the handle returned by `AsRef` is null.
+    pub fn new_simulated(config: SimulatedConfig) -> Result<AudioUnit, Error> {
+        let backend = Arc::new(SimulatedBackend::new(config));
+        Error::from_os_status(backend.initialize())?;
+        Ok(AudioUnit::from_backend(
+            ptr::null_mut(),
+            backend,
+            config.unit_type,
+        ))
+    }
+}
+
+type PropertyKey = (u32, c_uint, c_uint);
+type ParameterKey = (u32, c_uint, c_uint);
+
+/// The state shared between a `SimulatedBackend` and its render thread.
+struct Shared {
+    config: SimulatedConfig,
+    properties: Mutex<HashMap<PropertyKey, Vec<u8>>>,
+    parameters: Mutex<HashMap<ParameterKey, f32>>,
+    // Held for the duration of each render cycle, and while a callback is replaced, so that a
+    // callback is never freed while it is being called.
+    cycle_lock: CycleLock,
+    render_notifies: Mutex<Vec<RenderNotify>>,
+    property_listeners: Mutex<Vec<Arc<PropertyListener>>>,
+    running: AtomicBool,
+}
+
+/// A lock that the thread holding it may take again.
+///
+/// A callback called during a render cycle may render the output element of its own unit, which
+/// takes the lock again from the same thread.
+struct CycleLock {
+    // The thread holding the lock and the number of times it has taken it.
+    owner: Mutex<(Option<ThreadId>, usize)>,
+    released: Condvar,
+}
+
+struct CycleGuard<'a> {
+    lock: &'a CycleLock,
+}
+
+impl CycleLock {
+    fn new() -> Self {
+        CycleLock {
+            owner: Mutex::new((None, 0)),
+            released: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> CycleGuard<'_> {
+        let current = thread::current().id();
+        let mut owner = self.owner.lock().unwrap();
+        while owner.0.is_some_and(|thread| thread != current) {
+            owner = self.released.wait(owner).unwrap();
+        }
+        owner.0 = Some(current);
+        owner.1 += 1;
+        CycleGuard { lock: self }
+    }
+}
+
+impl Drop for CycleGuard<'_> {
+    fn drop(&mut self) {
+        let mut owner = self.lock.owner.lock().unwrap();
+        owner.1 -= 1;
+        if owner.1 == 0 {
+            owner.0 = None;
+            self.lock.released.notify_all();
+        }
+    }
+}
+
+/// A notification added with `add_render_notify`.
+#[derive(Copy, Clone)]
+struct RenderNotify(AURenderCallbackStruct);
+
+// The notification is only called from the render cycle, which it is removed under.
+unsafe impl Send for RenderNotify {}
+
+/// A listener added with `add_property_listener`.
+struct PropertyListener {
+    id: u32,
+    proc_: AudioUnitPropertyListenerProc,
+    user_data: *mut c_void,
+    // The threads currently calling the listener. Removing the listener waits for the calls made
+    // by other threads to return, so that it is never freed while it is being called.
+    callers: Mutex<Vec<ThreadId>>,
+    returned: Condvar,
+}
+
+// The listener is only called while it is registered as being called, which removal waits for.
+unsafe impl Send for PropertyListener {}
+unsafe impl Sync for PropertyListener {}
+
+impl PropertyListener {
+    /// Wait until no thread other than the current one is calling the listener.
+    fn wait_for_other_callers(&self) {
+        let current = thread::current().id();
+        let mut callers = self.callers.lock().unwrap();
+        while callers.iter().any(|&thread| thread != current) {
+            callers = self.returned.wait(callers).unwrap();
+        }
+    }
+}
+
+/// A **Backend** that keeps its properties in memory and drives callbacks from a thread.
+pub(crate) struct SimulatedBackend {
+    shared: Arc<Shared>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SimulatedBackend {
+    pub(crate) fn new(config: SimulatedConfig) -> Self {
+        let mut properties = HashMap::new();
+        let format = |channels| StreamFormat {
+            sample_rate: config.sample_rate,
+            sample_format: SampleFormat::F32,
+            flags: LinearPcmFlags::IS_FLOAT
+                | LinearPcmFlags::IS_PACKED
+                | LinearPcmFlags::IS_NON_INTERLEAVED,
+            channels,
+        };
+        let mut insert = |id, scope, elem: Element, bytes: &[u8]| {
+            properties.insert((id, scope, elem.0 as c_uint), bytes.to_vec());
+        };
+        // The format of the data given to the render callback, and of the data rendered from the
+        // input element.
+        let output_asbd = format(config.output_channels).to_asbd();
+        let input_asbd = format(config.input_channels).to_asbd();
+        insert(
+            kAudioUnitProperty_StreamFormat,
+            kAudioUnitScope_Input,
+            Element::Output,
+            as_bytes(&output_asbd),
+        );
+        insert(
+            kAudioUnitProperty_StreamFormat,
+            kAudioUnitScope_Output,
+            Element::Output,
+            as_bytes(&output_asbd),
+        );
+        insert(
+            kAudioUnitProperty_StreamFormat,
+            kAudioUnitScope_Output,
+            Element::Input,
+            as_bytes(&input_asbd),
+        );
+        insert(
+            kAudioUnitProperty_SampleRate,
+            kAudioUnitScope_Input,
+            Element::Output,
+            as_bytes(&config.sample_rate),
+        );
+        insert(
+            kAudioUnitProperty_SampleRate,
+            kAudioUnitScope_Output,
+            Element::Output,
+            as_bytes(&config.sample_rate),
+        );
+        insert(
+            kAudioUnitProperty_SampleRate,
+            kAudioUnitScope_Output,
+            Element::Input,
+            as_bytes(&config.sample_rate),
+        );
+        insert(
+            kAudioDevicePropertyBufferFrameSize,
+            kAudioUnitScope_Global,
+            Element::Output,
+            as_bytes(&config.buffer_frames),
+        );
+        insert(
+            kAudioOutputUnitProperty_IsRunning,
+            kAudioUnitScope_Global,
+            Element::Output,
+            as_bytes(&0u32),
+        );
+        insert(
+            kAudioUnitProperty_LastRenderError,
+            kAudioUnitScope_Global,
+            Element::Output,
+            as_bytes(&(0 as OSStatus)),
+        );
+
+        SimulatedBackend {
+            shared: Arc::new(Shared {
+                config,
+                properties: Mutex::new(properties),
+                parameters: Mutex::new(HashMap::new()),
+                cycle_lock: CycleLock::new(),
+                render_notifies: Mutex::new(Vec::new()),
+                property_listeners: Mutex::new(Vec::new()),
+                running: AtomicBool::new(false),
+            }),
+            thread: Mutex::new(None),
+        }
+    }
+}
+
+impl Backend for SimulatedBackend {
+    fn initialize(&self) -> OSStatus {
+        0
+    }
+
+    fn uninitialize(&self) -> OSStatus {
+        0
+    }
+
+    fn start(&self) -> OSStatus {
+        let mut thread = self.thread.lock().unwrap();
+        if thread.is_none() {
+            self.shared.running.store(true, Ordering::SeqCst);
+            let shared = self.shared.clone();
+            *thread = Some(thread::spawn(move || shared.run()));
+            self.shared
+                .set_global(kAudioOutputUnitProperty_IsRunning, &1u32);
+        }
+        0
+    }
+
+    fn stop(&self) -> OSStatus {
+        let mut thread = self.thread.lock().unwrap();
+        if let Some(handle) = thread.take() {
+            self.shared.running.store(false, Ordering::SeqCst);
+            handle.join().ok();
+            self.shared
+                .set_global(kAudioOutputUnitProperty_IsRunning, &0u32);
+        }
+        0
+    }
+
+    unsafe fn set_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: *const c_void,
+        size: u32,
+    ) -> OSStatus {
+        let is_callback = id == kAudioUnitProperty_SetRenderCallback
+            || id == kAudioOutputUnitProperty_SetInputCallback;
+        let _cycle = is_callback.then(|| self.shared.cycle_lock.lock());
+        let mut changed = vec![id];
+        {
+            let mut properties = self.shared.properties.lock().unwrap();
+            if data.is_null() {
+                properties.remove(&(id, scope, elem));
+                return 0;
+            }
+            let bytes = slice::from_raw_parts(data as *const u8, size as usize).to_vec();
+
+            // Keep the sample rate and the stream format of the same element in sync.
+            if id == kAudioUnitProperty_SampleRate && bytes.len() == mem::size_of::<f64>() {
+                let key = (kAudioUnitProperty_StreamFormat, scope, elem);
+                if let Some(format) = properties.get_mut(&key) {
+                    let mut asbd: AudioStreamBasicDescription = from_bytes(format);
+                    asbd.mSampleRate = from_bytes(&bytes);
+                    *format = as_bytes(&asbd).to_vec();
+                    changed.push(kAudioUnitProperty_StreamFormat);
+                }
+            } else if id == kAudioUnitProperty_StreamFormat
+                && bytes.len() == mem::size_of::<AudioStreamBasicDescription>()
+            {
+                let asbd: AudioStreamBasicDescription = from_bytes(&bytes);
+                let key = (kAudioUnitProperty_SampleRate, scope, elem);
+                properties.insert(key, as_bytes(&asbd.mSampleRate).to_vec());
+                changed.push(kAudioUnitProperty_SampleRate);
+            }
+
+            properties.insert((id, scope, elem), bytes);
+        }
+        // The listeners may read the properties, so they are only notified once they are
+        // unlocked.
+        for id in changed {
+            self.shared.notify_property(id, scope, elem);
+        }
+        0
+    }
+
+    unsafe fn get_property(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        data: NonNull<c_void>,
+        mut size: NonNull<u32>,
+    ) -> OSStatus {
+        let properties = self.shared.properties.lock().unwrap();
+        let bytes = match properties.get(&(id, scope, elem)) {
+            Some(bytes) => bytes,
+            None => return kAudioUnitErr_InvalidProperty,
+        };
+        if (*size.as_ref() as usize) < bytes.len() {
+            return kAudio_ParamError;
+        }
+        ptr::copy_nonoverlapping(bytes.as_ptr(), data.cast::<u8>().as_ptr(), bytes.len());
+        *size.as_mut() = bytes.len() as u32;
+        0
+    }
+
+    fn get_property_info(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        size: &mut u32,
+        writable: &mut bool,
+    ) -> OSStatus {
+        let properties = self.shared.properties.lock().unwrap();
+        match properties.get(&(id, scope, elem)) {
+            Some(bytes) => {
+                *size = bytes.len() as u32;
+                *writable = true;
+                0
+            }
+            None => kAudioUnitErr_InvalidProperty,
+        }
+    }
+
+    unsafe fn add_property_listener(
+        &self,
+        id: u32,
+        proc_: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus {
+        let listener = PropertyListener {
+            id,
+            proc_,
+            user_data,
+            callers: Mutex::new(Vec::new()),
+            returned: Condvar::new(),
+        };
+        self.shared
+            .property_listeners
+            .lock()
+            .unwrap()
+            .push(Arc::new(listener));
+        0
+    }
+
+    fn remove_property_listener(
+        &self,
+        id: u32,
+        _proc: AudioUnitPropertyListenerProc,
+        user_data: *mut c_void,
+    ) -> OSStatus {
+        let removed: Vec<_> = {
+            let mut listeners = self.shared.property_listeners.lock().unwrap();
+            let (removed, kept) = listeners
+                .drain(..)
+                .partition(|listener| listener.id == id && listener.user_data == user_data);
+            *listeners = kept;
+            removed
+        };
+        for listener in removed {
+            listener.wait_for_other_callers();
+        }
+        0
+    }
+
+    fn get_parameter(&self, id: u32, scope: c_uint, elem: c_uint, value: &mut f32) -> OSStatus {
+        let parameters = self.shared.parameters.lock().unwrap();
+        match parameters.get(&(id, scope, elem)) {
+            Some(v) => {
+                *value = *v;
+                0
+            }
+            None => kAudioUnitErr_InvalidParameter,
+        }
+    }
+
+    fn set_parameter(
+        &self,
+        id: u32,
+        scope: c_uint,
+        elem: c_uint,
+        value: f32,
+        _buffer_offset: u32,
+    ) -> OSStatus {
+        let mut parameters = self.shared.parameters.lock().unwrap();
+        parameters.insert((id, scope, elem), value);
+        0
+    }
+
+    fn schedule_parameters(&self, events: &[RawParameterEvent]) -> OSStatus {
+        // Nothing is rendered through the parameters, so every event completes immediately.
+        let mut parameters = self.shared.parameters.lock().unwrap();
+        for event in events {
+            parameters.insert(event.target(), event.final_value());
+        }
+        0
+    }
+
+    unsafe fn add_render_notify(&self, proc_: AURenderCallback, ref_con: *mut c_void) -> OSStatus {
+        let notify = RenderNotify(AURenderCallbackStruct {
+            inputProc: proc_,
+            inputProcRefCon: ref_con,
+        });
+        self.shared.render_notifies.lock().unwrap().push(notify);
+        0
+    }
+
+    fn remove_render_notify(&self, _proc: AURenderCallback, ref_con: *mut c_void) -> OSStatus {
+        let _cycle = self.shared.cycle_lock.lock();
+        let mut notifies = self.shared.render_notifies.lock().unwrap();
+        notifies.retain(|notify| notify.0.inputProcRefCon != ref_con);
+        0
+    }
+
+    fn retire(&self, value: Box<dyn Any + Send>) {
+        // `remove_property_listener` waits for the listeners running on other threads, so nothing
+        // else can still be using `value`.
+        drop(value);
+    }
+
+    unsafe fn render(
+        &self,
+        _flags: *mut AudioUnitRenderActionFlags,
+        time_stamp: NonNull<AudioTimeStamp>,
+        bus: u32,
+        frames: u32,
+        data: NonNull<AudioBufferList>,
+    ) -> OSStatus {
+        // There is no input device, so the input element renders silence.
+        if bus != Element::Output.0 {
+            write_silence(data);
+            return 0;
+        }
+
+        // Rendering the output element pulls from the render callback, as a generic output unit
+        // would.
+        let _cycle = self.shared.cycle_lock.lock();
+        let render_callback = self.shared.property::<AURenderCallbackStruct>(
+            kAudioUnitProperty_SetRenderCallback,
+            kAudioUnitScope_Input,
+            Element::Output,
+        );
+        let time_stamp = time_stamp.as_ref();
+        self.shared
+            .notify_render(time_stamp, frames, data, || match render_callback {
+                Some(callback) => call(callback, time_stamp, bus, frames, Some(data)),
+                None => {
+                    write_silence(data);
+                    0
+                }
+            })
+    }
+}
+
+impl Drop for SimulatedBackend {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Shared {
+    /// The body of the render thread.
+    fn run(&self) {
+        let mut sample_time = 0.0;
+        let mut output = OutputBuffers::new();
+        let mut deadline = Instant::now();
+        while self.running.load(Ordering::SeqCst) {
+            let frames = self.cycle(sample_time, &mut output);
+            sample_time += frames as f64;
+            if self.config.realtime {
+                let sample_rate = self
+                    .property::<f64>(
+                        kAudioUnitProperty_SampleRate,
+                        kAudioUnitScope_Input,
+                        Element::Output,
+                    )
+                    .unwrap_or(self.config.sample_rate);
+                deadline += Duration::from_secs_f64(frames as f64 / sample_rate);
+                let now = Instant::now();
+                if deadline > now {
+                    thread::sleep(deadline - now);
+                }
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+
+    /// Call the input callback followed by the render callback, returning the number of frames
+    /// processed.
+    fn cycle(&self, sample_time: f64, output: &mut OutputBuffers) -> u32 {
+        let _cycle = self.cycle_lock.lock();
+        let frames = self
+            .property::<u32>(
+                kAudioDevicePropertyBufferFrameSize,
+                kAudioUnitScope_Global,
+                Element::Output,
+            )
+            .unwrap_or(self.config.buffer_frames);
+
+        let mut time_stamp: AudioTimeStamp = unsafe { mem::zeroed() };
+        time_stamp.mSampleTime = sample_time;
+        time_stamp.mRateScalar = 1.0;
+        time_stamp.mFlags =
+            AudioTimeStampFlags::SampleTimeValid | AudioTimeStampFlags::RateScalarValid;
+
+        let input_callback = self.property::<AURenderCallbackStruct>(
+            kAudioOutputUnitProperty_SetInputCallback,
+            kAudioUnitScope_Global,
+            Element::Output,
+        );
+        if let Some(callback) = input_callback {
+            call(callback, &time_stamp, Element::Input.0, frames, None);
+        }
+
+        let render_callback = self.property::<AURenderCallbackStruct>(
+            kAudioUnitProperty_SetRenderCallback,
+            kAudioUnitScope_Input,
+            Element::Output,
+        );
+        let output_format = self.property::<AudioStreamBasicDescription>(
+            kAudioUnitProperty_StreamFormat,
+            kAudioUnitScope_Input,
+            Element::Output,
+        );
+        if let (Some(callback), Some(asbd)) = (render_callback, output_format) {
+            let list = output.prepare(&asbd, frames);
+            self.notify_render(&time_stamp, frames, list, || {
+                call(callback, &time_stamp, Element::Output.0, frames, Some(list))
+            });
+        }
+        frames
+    }
+
+    /// Render the output element with `render`, calling the render notifications before and
+    /// after it as Audio Toolbox does.
+    fn notify_render(
+        &self,
+        time_stamp: &AudioTimeStamp,
+        frames: u32,
+        data: NonNull<AudioBufferList>,
+        render: impl FnOnce() -> OSStatus,
+    ) -> OSStatus {
+        let notifies = self.render_notifies.lock().unwrap().clone();
+        let notify_all = |flags| {
+            for notify in &notifies {
+                let flags = AudioUnitRenderActionFlags(flags);
+                call_with_flags(
+                    notify.0,
+                    flags,
+                    time_stamp,
+                    Element::Output.0,
+                    frames,
+                    Some(data),
+                );
+            }
+        };
+        notify_all(AudioUnitRenderActionFlags::UnitRenderAction_PreRender.0);
+        let status = render();
+        let mut flags = AudioUnitRenderActionFlags::UnitRenderAction_PostRender.0;
+        if status != 0 {
+            flags |= AudioUnitRenderActionFlags::UnitRenderAction_PostRenderError.0;
+        }
+        notify_all(flags);
+        if status != 0 {
+            self.set_global(kAudioUnitProperty_LastRenderError, &status);
+        }
+        status
+    }
+
+    /// Set a property of the global scope, notifying its listeners.
+    fn set_global<T>(&self, id: u32, value: &T) {
+        let key = (id, kAudioUnitScope_Global, Element::Output.0 as c_uint);
+        let bytes = as_bytes(value).to_vec();
+        self.properties.lock().unwrap().insert(key, bytes);
+        self.notify_property(id, kAudioUnitScope_Global, Element::Output.0 as c_uint);
+    }
+
+    /// Call the listeners of the given property, as Audio Toolbox does when it changes.
+    ///
+    /// The listeners are called without the list of listeners locked, so that they may set
+    /// properties and add or remove listeners themselves.
+    fn notify_property(&self, id: u32, scope: c_uint, elem: c_uint) {
+        let current = thread::current().id();
+        let listeners: Vec<_> = {
+            let listeners = self.property_listeners.lock().unwrap();
+            listeners
+                .iter()
+                .filter(|listener| listener.id == id)
+                .inspect(|listener| listener.callers.lock().unwrap().push(current))
+                .cloned()
+                .collect()
+        };
+        for listener in listeners {
+            if let (Some(proc_), Some(user_data)) =
+                (listener.proc_, NonNull::new(listener.user_data))
+            {
+                unsafe { proc_(user_data, ptr::null_mut(), id, scope, elem) };
+            }
+            let mut callers = listener.callers.lock().unwrap();
+            if let Some(i) = callers.iter().position(|&thread| thread == current) {
+                callers.swap_remove(i);
+            }
+            listener.returned.notify_all();
+        }
+    }
+
+    fn property<T: Copy>(&self, id: u32, scope: c_uint, elem: Element) -> Option<T> {
+        let properties = self.properties.lock().unwrap();
+        properties
+            .get(&(id, scope, elem.0 as c_uint))
+            .filter(|bytes| bytes.len() == mem::size_of::<T>())
+            .map(|bytes| unsafe { from_bytes(bytes) })
+    }
+}
+
+/// Invoke a registered callback the way Audio Toolbox would, returning its status.
+fn call(
+    callback: AURenderCallbackStruct,
+    time_stamp: &AudioTimeStamp,
+    bus: u32,
+    frames: u32,
+    data: Option<NonNull<AudioBufferList>>,
+) -> OSStatus {
+    let flags = AudioUnitRenderActionFlags(0);
+    call_with_flags(callback, flags, time_stamp, bus, frames, data)
+}
+
+/// As `call`, with the given action flags.
+fn call_with_flags(
+    callback: AURenderCallbackStruct,
+    mut flags: AudioUnitRenderActionFlags,
+    time_stamp: &AudioTimeStamp,
+    bus: u32,
+    frames: u32,
+    data: Option<NonNull<AudioBufferList>>,
+) -> OSStatus {
+    let (Some(proc_), Some(ref_con)) = (callback.inputProc, NonNull::new(callback.inputProcRefCon))
+    else {
+        return 0;
+    };
+    let data = data.map_or(ptr::null_mut(), NonNull::as_ptr);
+    unsafe {
+        proc_(
+            ref_con,
+            NonNull::from(&mut flags),
+            NonNull::from(time_stamp),
+            bus,
+            frames,
+            data,
+        )
+    }
+}
+
+/// Zero every buffer in `data`.
+///
+/// # Safety
+///
+/// `data` must describe valid buffers.
+unsafe fn write_silence(data: NonNull<AudioBufferList>) {
+    let list = data.as_ptr();
+    let ptr = (*list).mBuffers.as_mut_ptr();
+    let len = (*list).mNumberBuffers as usize;
+    for buffer in slice::from_raw_parts_mut(ptr, len) {
+        if !buffer.mData.is_null() {
+            ptr::write_bytes(buffer.mData as *mut u8, 0, buffer.mDataByteSize as usize);
+        }
+    }
+}
+
+/// The buffers handed to the render callback.
+///
+/// The list is only reallocated when the number of buffers or channels changes. Changes to the
+/// number of frames resize the existing buffers.
+struct OutputBuffers {
+    list: Option<AudioBufferListBuf>,
+}
+
+impl OutputBuffers {
+    fn new() -> Self {
+        OutputBuffers { list: None }
+    }
+
+    fn prepare(
+        &mut self,
+        asbd: &AudioStreamBasicDescription,
+        frames: u32,
+    ) -> NonNull<AudioBufferList> {
+        let flags = LinearPcmFlags::from_bits_truncate(asbd.mFormatFlags);
+        let (n_buffers, channels_per_buffer) = if flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED)
+        {
+            (asbd.mChannelsPerFrame, 1)
+        } else {
+            (1, asbd.mChannelsPerFrame)
+        };
+        let byte_size = asbd.mBytesPerFrame * frames;
+        let reusable = self.list.as_ref().is_some_and(|list| {
+            list.len() == n_buffers as usize
+                && list
+                    .audio_buffers()
+                    .iter()
+                    .all(|buffer| buffer.mNumberChannels == channels_per_buffer)
+        });
+        let list = match self.list {
+            Some(ref mut list) if reusable => {
+                list.resize(byte_size);
+                list
+            }
+            _ => self.list.insert(AudioBufferListBuf::new(
+                n_buffers,
+                channels_per_buffer,
+                byte_size,
+            )),
+        };
+        list.as_non_null()
+    }
+}
+
+fn as_bytes<T>(value: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+/// # Safety
+///
+/// `bytes` must hold a valid `T` and be at least `size_of::<T>()` long.
+unsafe fn from_bytes<T>(bytes: &[u8]) -> T {
+    ptr::read_unaligned(bytes.as_ptr() as *const T)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::render_callback::{self, data};
+    use std::sync::mpsc;
+
+    fn config() -> SimulatedConfig {
+        SimulatedConfig {
+            buffer_frames: 64,
+            realtime: false,
+            ..SimulatedConfig::default()
+        }
+    }
+
+    /// Receive from `rx` until a message differs from `previous`.
+    ///
+    /// The render thread does not wait for the receiver, so any number of messages sent before a
+    /// change took effect may be queued ahead of it.
+    fn recv_change<T: PartialEq>(rx: &mpsc::Receiver<T>, previous: &T) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let received = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            if received != *previous || Instant::now() > deadline {
+                return received;
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_callback_is_driven() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (tx, rx) = mpsc::channel();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_render_callback(move |mut args: Args| {
+                for channel in args.data.channels_mut() {
+                    channel.fill(0.5);
+                }
+                tx.send((args.num_frames, args.data.channels().count()))
+                    .ok();
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        for _ in 0..4 {
+            let received = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            assert_eq!(received, (64, 2));
+        }
+        audio_unit.stop().unwrap();
+    }
+
+    #[test]
+    fn test_render_notify_sees_rendered_output() {
+        use crate::audio_unit::render_callback::{ActionFlags, CallbackOutcome};
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_render_callback(|mut args: Args| {
+                for channel in args.data.channels_mut() {
+                    channel.fill(0.5);
+                }
+                Ok(())
+            })
+            .unwrap();
+        // A notification observes the render, so asking for silence leaves the output intact.
+        let (cycle_tx, cycle_rx) = mpsc::channel();
+        audio_unit
+            .add_render_notify(move |_: Args| {
+                cycle_tx.send(()).ok();
+                CallbackOutcome::Silence
+            })
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        let id = audio_unit
+            .add_render_notify(move |args: Args| {
+                let flags = args.flags.get();
+                if flags.contains(ActionFlags::POST_RENDER) {
+                    let peak = args
+                        .data
+                        .channels()
+                        .flatten()
+                        .fold(0.0f32, |a, &s| a.max(s));
+                    tx.send((flags, peak)).ok();
+                }
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        let (flags, peak) = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(flags, ActionFlags::POST_RENDER);
+        assert_eq!(peak, 0.5);
+        audio_unit.stop().unwrap();
+
+        audio_unit.remove_render_notify(id).unwrap();
+        assert!(matches!(
+            audio_unit.remove_render_notify(id),
+            Err(Error::Unspecified)
+        ));
+
+        // A removed notification is no longer called, but is only dropped along with the unit.
+        while rx.try_recv().is_ok() {}
+        audio_unit.start().unwrap();
+        while cycle_rx.try_recv().is_ok() {}
+        for _ in 0..4 {
+            cycle_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        }
+        assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Empty));
+        drop(audio_unit);
+        assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_render_callback_format_mismatch() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        type Args = render_callback::Args<data::Interleaved<f32>>;
+        let result = audio_unit.set_render_callback(|_: Args| Ok(()));
+        assert!(matches!(
+            result,
+            Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat)
+        ));
+    }
+
+    #[test]
+    fn test_variable_length_property() {
+        use crate::audio_unit::Scope;
+        use crate::sys::kAudioUnitProperty_ParameterList;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let id = kAudioUnitProperty_ParameterList;
+        let ids: [u32; 3] = [1, 2, 3];
+        audio_unit
+            .set_property(id, Scope::Global, Element::Output, Some(&ids))
+            .unwrap();
+
+        let info = audio_unit.property_info(id, Scope::Global, Element::Output);
+        assert_eq!(info.unwrap(), (12, true));
+        let values: Vec<u32> = audio_unit
+            .get_property_vec(id, Scope::Global, Element::Output)
+            .unwrap();
+        assert_eq!(values, ids);
+        let bytes = audio_unit.get_property_bytes(id, Scope::Global, Element::Output);
+        assert_eq!(bytes.unwrap().len(), 12);
+        let result = audio_unit.get_property_vec::<u64>(id, Scope::Global, Element::Output);
+        assert!(matches!(
+            result,
+            Err(Error::PropertySizeMismatch {
+                size: 12,
+                element_size: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_stream_format_on_any_element() {
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let format = StreamFormat {
+            channels: 1,
+            ..audio_unit.output_stream_format().unwrap()
+        };
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element(3))
+            .unwrap();
+        let read = audio_unit
+            .stream_format(Scope::Input, Element::from(3))
+            .unwrap();
+        assert_eq!(read.channels, 1);
+        assert_eq!(audio_unit.output_stream_format().unwrap().channels, 2);
+        assert!(audio_unit.stream_format(Scope::Input, Element(4)).is_err());
+    }
+
+    #[test]
+    fn test_render_callback_panic_is_caught() {
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (tx, rx) = mpsc::channel();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_render_callback(move |_: Args| -> Result<(), ()> {
+                tx.send(()).ok();
+                panic!("render callback panicked");
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        audio_unit.stop().unwrap();
+        let payload = audio_unit.take_callback_panic().unwrap();
+        assert_eq!(
+            payload.downcast_ref::<&str>(),
+            Some(&"render callback panicked")
+        );
+        assert!(audio_unit.take_callback_panic().is_none());
+    }
+
+    #[cfg(not(target_os = "ios"))]
+    #[test]
+    fn test_input_callback_follows_buffer_size() {
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let format = StreamFormat {
+            channels: 1,
+            ..audio_unit.input_stream_format().unwrap()
+        };
+        audio_unit
+            .set_stream_format(format, Scope::Output, Element::Input)
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_input_callback(move |args: Args| {
+                tx.send(args.num_frames).ok();
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 64);
+
+        let frames = 128u32;
+        audio_unit
+            .set_property(
+                kAudioDevicePropertyBufferFrameSize,
+                Scope::Global,
+                Element::Output,
+                Some(&frames),
+            )
+            .unwrap();
+        assert_eq!(recv_change(&rx, &64), 128);
+        audio_unit.stop().unwrap();
+    }
+
+    #[cfg(not(target_os = "ios"))]
+    #[test]
+    fn test_non_interleaved_multichannel_input() {
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let format = StreamFormat {
+            channels: 8,
+            ..audio_unit.input_stream_format().unwrap()
+        };
+        audio_unit
+            .set_stream_format(format, Scope::Output, Element::Input)
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit
+            .set_input_callback(move |args: Args| {
+                let lens: Vec<usize> = args.data.channels().map(|c| c.len()).collect();
+                tx.send((args.num_frames, lens)).ok();
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            (64, vec![64; 8])
+        );
+
+        let frames = 32u32;
+        audio_unit
+            .set_property(
+                kAudioDevicePropertyBufferFrameSize,
+                Scope::Global,
+                Element::Output,
+                Some(&frames),
+            )
+            .unwrap();
+        assert_eq!(recv_change(&rx, &(64, vec![64; 8])), (32, vec![32; 8]));
+        audio_unit.stop().unwrap();
+    }
+
+    /// A stream format of interleaved `i16` samples, as a host may switch to while running.
+    fn i16_format(format: StreamFormat, channels: u32) -> StreamFormat {
+        StreamFormat {
+            sample_format: SampleFormat::I16,
+            flags: LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED,
+            channels,
+            ..format
+        }
+    }
+
+    #[test]
+    fn test_render_follows_format_changes() {
+        use crate::audio_unit::render_callback::ActionFlags;
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        type Args = render_callback::Args<data::Dynamic>;
+        let (tx, rx) = mpsc::channel();
+        audio_unit
+            .set_render_callback(move |mut args: Args| {
+                for channel in args.data.channels_mut() {
+                    channel.fill(0.5);
+                }
+                let format = args.data.stream_format().map(|f| f.sample_format);
+                tx.send((args.data.num_channels(), format)).ok();
+                Ok(())
+            })
+            .unwrap();
+        let (notify_tx, notify_rx) = mpsc::channel();
+        audio_unit
+            .add_render_notify(move |args: Args| {
+                if args.flags.get().contains(ActionFlags::POST_RENDER) {
+                    let peak = args
+                        .data
+                        .channels()
+                        .flatten()
+                        .fold(0.0f32, |a, &s| a.max(s));
+                    notify_tx.send((args.data.num_channels(), peak)).ok();
+                }
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), (2, None));
+        let received = notify_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(received, (2, 0.5));
+
+        let format = i16_format(audio_unit.output_stream_format().unwrap(), 1);
+        for scope in [Scope::Input, Scope::Output] {
+            audio_unit
+                .set_stream_format(format, scope, Element::Output)
+                .unwrap();
+        }
+        let received = recv_change(&rx, &(2, None));
+        assert_eq!(received, (1, Some(SampleFormat::I16)));
+        assert_eq!(recv_change(&notify_rx, &(2, 0.5)), (1, 0.5));
+        audio_unit.stop().unwrap();
+    }
+
+    #[cfg(not(target_os = "ios"))]
+    #[test]
+    fn test_input_follows_format_changes() {
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (tx, rx) = mpsc::channel();
+        type Args = render_callback::Args<data::Dynamic>;
+        audio_unit
+            .set_input_callback(move |args: Args| {
+                let format = args.data.stream_format().map(|f| f.sample_format);
+                tx.send((args.data.num_channels(), format)).ok();
+                Ok(())
+            })
+            .unwrap();
+        audio_unit.start().unwrap();
+        let channels = audio_unit.input_stream_format().unwrap().channels as usize;
+        let previous = (channels, None);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), previous);
+
+        let format = i16_format(audio_unit.input_stream_format().unwrap(), 1);
+        audio_unit
+            .set_stream_format(format, Scope::Output, Element::Input)
+            .unwrap();
+        assert_eq!(recv_change(&rx, &previous), (1, Some(SampleFormat::I16)));
+        audio_unit.stop().unwrap();
+    }
+
+    #[test]
+    fn test_listener_may_drop_listeners() {
+        use crate::audio_unit::property::{SampleRate, StreamFormat as StreamFormatProperty};
+        use crate::audio_unit::Scope;
+
+        let mut audio_unit = AudioUnit::new_simulated(config()).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let mut sample_rate_listener =
+            Some(audio_unit.add_property_sender(SampleRate, tx).unwrap());
+        let _listener = audio_unit
+            .add_property_listener(StreamFormatProperty, move |_| {
+                sample_rate_listener.take();
+            })
+            .unwrap();
+
+        // Setting the stream format notifies its listeners before those of the sample rate.
+        let format = audio_unit.output_stream_format().unwrap();
+        audio_unit
+            .set_stream_format(format, Scope::Input, Element::Output)
+            .unwrap();
+        assert_eq!(rx.try_recv().unwrap_err(), mpsc::TryRecvError::Disconnected);
+    }
+
+    #[cfg(not(target_os = "ios"))]
+    #[test]
+    fn test_callback_may_render_its_own_unit() {
+        let audio_unit = Arc::new(Mutex::new(AudioUnit::new_simulated(config()).unwrap()));
+        let mut unit = audio_unit.lock().unwrap();
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        unit.set_render_callback(|mut args: Args| {
+            for channel in args.data.channels_mut() {
+                channel.fill(0.5);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let this = audio_unit.clone();
+        let format = unit.output_stream_format().unwrap();
+        let mut buffer = AudioBufferListBuf::for_format(&format, 64);
+        unit.set_input_callback(move |_: Args| {
+            // The test thread holds the unit while it is being started and stopped.
+            if let Ok(mut unit) = this.try_lock() {
+                let time_stamp: AudioTimeStamp = unsafe { mem::zeroed() };
+                unit.render(64, &mut buffer, &time_stamp).unwrap();
+                let bytes = buffer.buffer(0).unwrap();
+                tx.send(f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .ok();
+            }
+            Ok(())
+        })
+        .unwrap();
+        unit.start().unwrap();
+        drop(unit);
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 0.5);
+        let mut unit = audio_unit.lock().unwrap();
+        unit.stop().unwrap();
+        // The callback holds a reference to the unit.
+        unit.free_input_callback();
+    }
+}
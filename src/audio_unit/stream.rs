@@ -0,0 +1,493 @@
+//! Opening a stream to or from an audio device without configuring an **AudioUnit** by hand.
+//!
+//! An **OutputStreamBuilder** or **InputStreamBuilder** collects the device, sample rate, channel
+//! count, sample type and buffer size wanted for a stream. Building it creates a **HalOutput**
+//! unit for the device, negotiates the closest configuration the device supports and sets a
+//! stream format whose layout matches the callback's **Data** type, so that the callback is
+//! always accepted. The resulting **Stream** reports the negotiated **StreamConfig**.
+//!
+//! Anything left unset follows the device: its default input or output, its current sample rate
+//! and buffer size and all of its channels.
+//!
+//! ```no_run
+//! use coreaudio::audio_unit::render_callback::{self, data};
+//! use coreaudio::audio_unit::stream::OutputStreamBuilder;
+//!
+//! # fn main() -> Result<(), coreaudio::Error> {
+//! type Args = render_callback::Args<data::Interleaved<i16>>;
+//! let mut stream = OutputStreamBuilder::new()
+//!     .sample_type::<i16>()
+//!     .sample_rate(48_000.0)
+//!     .channels(2)
+//!     .buffer_frames(256)
+//!     .build(|args: Args| {
+//!         args.data.buffer.fill(0);
+//!         Ok(())
+//!     })?;
+//! println!("{:?}", stream.config());
+//! stream.play()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use objc2_core_audio::{kAudioDevicePropertyBufferFrameSize, AudioDeviceID};
+use objc2_core_audio_types::AudioValueRange;
+
+use super::audio_format::LinearPcmFlags;
+use super::macos_helpers::{
+    audio_unit_from_device_id, get_available_sample_rates, get_buffer_frame_size_range,
+    get_default_device_id, get_device_channel_count, get_device_sample_rate,
+    set_device_sample_rate,
+};
+use super::render_callback::{Args, Data, IntoCallbackOutcome};
+use super::{AudioUnit, Element, Sample, SampleFormat, Scope, StreamFormat};
+use crate::error::Error;
+use std::marker::PhantomData;
+
+/// The configuration a **Stream** was opened with, as negotiated with its device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StreamConfig {
+    /// The device the stream plays to or records from.
+    pub device_id: AudioDeviceID,
+    /// The format of the buffers given to the callback.
+    pub stream_format: StreamFormat,
+    /// The number of frames the device processes per cycle.
+    pub buffer_frames: u32,
+}
+
+/// The parts of a stream's configuration that may be requested.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Request {
+    device_id: Option<AudioDeviceID>,
+    sample_rate: Option<f64>,
+    channels: Option<u32>,
+    buffer_frames: Option<u32>,
+}
+
+/// What a device supports, and its current settings.
+#[derive(Clone, Debug)]
+struct Capabilities {
+    sample_rates: Vec<AudioValueRange>,
+    sample_rate: f64,
+    channels: u32,
+    buffer_frame_range: AudioValueRange,
+    buffer_frames: u32,
+}
+
+/// The settings negotiated for a device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Negotiated {
+    sample_rate: f64,
+    channels: u32,
+    buffer_frames: u32,
+}
+
+/// The supported sample rate closest to `requested`, preferring the higher of two equally close
+/// rates.
+fn closest_sample_rate(ranges: &[AudioValueRange], requested: f64) -> Option<f64> {
+    ranges
+        .iter()
+        .map(|range| requested.max(range.mMinimum).min(range.mMaximum))
+        .fold(None, |closest: Option<f64>, rate| match closest {
+            Some(closest) => {
+                let (distance, closest_distance) =
+                    ((rate - requested).abs(), (closest - requested).abs());
+                if distance < closest_distance || (distance == closest_distance && rate > closest) {
+                    Some(rate)
+                } else {
+                    Some(closest)
+                }
+            }
+            None => Some(rate),
+        })
+}
+
+/// The closest settings to `request` that a device with the given capabilities supports.
+///
+/// The unit converts the sample rate of an output stream, so any requested rate is kept. Input
+/// must run at the rate of the device, which `set_device_sample_rate` can only switch to the
+/// rates of single-rate ranges, so the closest of those is chosen.
+fn negotiate(request: &Request, capabilities: &Capabilities, input: bool) -> Negotiated {
+    let sample_rate = match request.sample_rate {
+        Some(rate) if input => {
+            let ranges: Vec<AudioValueRange> = capabilities
+                .sample_rates
+                .iter()
+                .filter(|range| range.mMinimum == range.mMaximum)
+                .copied()
+                .collect();
+            closest_sample_rate(&ranges, rate).unwrap_or(capabilities.sample_rate)
+        }
+        Some(rate) => rate,
+        None => capabilities.sample_rate,
+    };
+    let channels = match request.channels {
+        Some(channels) if capabilities.channels > 0 => channels.clamp(1, capabilities.channels),
+        Some(channels) => channels.max(1),
+        None => capabilities.channels.max(1),
+    };
+    let range = &capabilities.buffer_frame_range;
+    let buffer_frames = match request.buffer_frames {
+        Some(frames) if range.mMaximum >= range.mMinimum && range.mMaximum > 0.0 => {
+            (frames as f64).max(range.mMinimum).min(range.mMaximum) as u32
+        }
+        Some(frames) => frames,
+        None => capabilities.buffer_frames,
+    };
+    Negotiated {
+        sample_rate,
+        channels,
+        buffer_frames,
+    }
+}
+
+/// The flags describing packed samples of the given format in native byte order.
+fn sample_flags(sample_format: SampleFormat) -> LinearPcmFlags {
+    let mut flags = match sample_format {
+        SampleFormat::F64 | SampleFormat::F32 => LinearPcmFlags::IS_FLOAT,
+        SampleFormat::I32 | SampleFormat::I24 | SampleFormat::I16 | SampleFormat::I8 => {
+            LinearPcmFlags::IS_SIGNED_INTEGER
+        }
+        SampleFormat::U16 | SampleFormat::U8 => LinearPcmFlags::empty(),
+    };
+    flags.insert(LinearPcmFlags::IS_PACKED);
+    flags.set(LinearPcmFlags::IS_BIG_ENDIAN, cfg!(target_endian = "big"));
+    flags
+}
+
+/// A stream format of samples of type `S` in a layout, and byte order, accepted by `D`.
+///
+/// Non-interleaved layouts are tried first, as they are the canonical layout of audio units.
+fn stream_format_for<S: Sample, D: Data>(sample_rate: f64, channels: u32) -> Option<StreamFormat> {
+    let sample_format = S::sample_format();
+    let flags = sample_flags(sample_format);
+    let layouts = [LinearPcmFlags::IS_NON_INTERLEAVED, LinearPcmFlags::empty()];
+    let byte_orders = [LinearPcmFlags::empty(), LinearPcmFlags::IS_BIG_ENDIAN];
+    layouts
+        .iter()
+        .flat_map(|&layout| {
+            byte_orders
+                .iter()
+                .map(move |&order| (flags | layout) ^ order)
+        })
+        .map(|flags| StreamFormat {
+            sample_rate,
+            sample_format,
+            flags,
+            channels,
+        })
+        .find(|format| D::does_stream_format_match(format))
+}
+
+/// Query what `device_id` supports in the given scope.
+fn capabilities(
+    audio_unit: &AudioUnit,
+    device_id: AudioDeviceID,
+    scope: Scope,
+) -> Result<Capabilities, Error> {
+    let buffer_frames: u32 = audio_unit.get_property(
+        kAudioDevicePropertyBufferFrameSize,
+        Scope::Global,
+        Element::Output,
+    )?;
+    Ok(Capabilities {
+        sample_rates: get_available_sample_rates(device_id)?,
+        sample_rate: get_device_sample_rate(device_id)?,
+        channels: get_device_channel_count(device_id, scope)?,
+        buffer_frame_range: get_buffer_frame_size_range(device_id)?,
+        buffer_frames,
+    })
+}
+
+/// Create a unit for the requested device, apply the closest settings it supports and set a
+/// stream format accepted by `D` on the side facing the callback.
+fn open<S: Sample, D: Data>(
+    request: &Request,
+    input: bool,
+) -> Result<(AudioUnit, StreamConfig), Error> {
+    let device_id = match request.device_id {
+        Some(device_id) => device_id,
+        None => get_default_device_id(input).ok_or(Error::NoMatchingDefaultAudioUnitFound)?,
+    };
+    let mut audio_unit = audio_unit_from_device_id(device_id, input)?;
+    let device_scope = if input { Scope::Input } else { Scope::Output };
+    let capabilities = capabilities(&audio_unit, device_id, device_scope)?;
+    let negotiated = negotiate(request, &capabilities, input);
+
+    // Only input changes the rate of the device, as the device may be shared by other streams.
+    if input && negotiated.sample_rate != capabilities.sample_rate {
+        set_device_sample_rate(device_id, negotiated.sample_rate)?;
+    }
+    if negotiated.buffer_frames != capabilities.buffer_frames {
+        audio_unit.set_property(
+            kAudioDevicePropertyBufferFrameSize,
+            Scope::Global,
+            Element::Output,
+            Some(&negotiated.buffer_frames),
+        )?;
+    }
+
+    let stream_format = stream_format_for::<S, D>(negotiated.sample_rate, negotiated.channels)
+        .ok_or(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat)?;
+    let (scope, element) = if input {
+        (Scope::Output, Element::Input)
+    } else {
+        (Scope::Input, Element::Output)
+    };
+    audio_unit.set_stream_format(stream_format, scope, element)?;
+
+    let config = StreamConfig {
+        device_id,
+        stream_format: audio_unit.stream_format(scope, element)?,
+        buffer_frames: audio_unit.get_property(
+            kAudioDevicePropertyBufferFrameSize,
+            Scope::Global,
+            Element::Output,
+        )?,
+    };
+    Ok((audio_unit, config))
+}
+
+/// Implement the setters shared by the stream builders.
+macro_rules! impl_builder {
+    ($($Builder:ident),*) => {$(
+        impl $Builder<f32> {
+            /// A builder for a stream of `f32` samples on the default device, with every other
+            /// setting following the device.
+            pub fn new() -> Self {
+                $Builder {
+                    request: Request::default(),
+                    sample_type: PhantomData,
+                }
+            }
+        }
+
+        impl Default for $Builder<f32> {
+            fn default() -> Self {
+                $Builder::new()
+            }
+        }
+
+        impl<S: Sample> $Builder<S> {
+            /// Use the device with the given ID, e.g. from `macos_helpers::get_device_id_from_name`.
+            pub fn device(mut self, device_id: AudioDeviceID) -> Self {
+                self.request.device_id = Some(device_id);
+                self
+            }
+
+            /// Request the given sample rate.
+            ///
+            /// An output stream converts to the rate of the device. An input stream switches the
+            /// device to the closest rate it supports.
+            pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+                self.request.sample_rate = Some(sample_rate);
+                self
+            }
+
+            /// Request the given number of channels, limited to the channels of the device.
+            pub fn channels(mut self, channels: u32) -> Self {
+                self.request.channels = Some(channels);
+                self
+            }
+
+            /// Request the given number of frames per cycle, limited to the range the device
+            /// supports.
+            pub fn buffer_frames(mut self, buffer_frames: u32) -> Self {
+                self.request.buffer_frames = Some(buffer_frames);
+                self
+            }
+
+            /// Use samples of type `T`, which must match the sample type of the callback's
+            /// **Data**.
+            pub fn sample_type<T: Sample>(self) -> $Builder<T> {
+                $Builder {
+                    request: self.request,
+                    sample_type: PhantomData,
+                }
+            }
+        }
+    )*};
+}
+
+/// Configures a **Stream** playing to an output device.
+#[derive(Clone, Debug)]
+pub struct OutputStreamBuilder<S> {
+    request: Request,
+    sample_type: PhantomData<S>,
+}
+
+/// Configures a **Stream** recording from an input device.
+#[derive(Clone, Debug)]
+pub struct InputStreamBuilder<S> {
+    request: Request,
+    sample_type: PhantomData<S>,
+}
+
+impl_builder!(OutputStreamBuilder, InputStreamBuilder);
+
+impl<S: Sample> OutputStreamBuilder<S> {
+    /// Open the stream, calling `f` to fill each buffer of output once the stream is played.
+    ///
+    /// The layout of the stream is chosen to match `D`. Returns
+    /// `Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat` if `D` does not hold
+    /// samples of type `S`.
+    pub fn build<D, F, R>(self, f: F) -> Result<Stream, Error>
+    where
+        F: FnMut(Args<D>) -> R + 'static,
+        R: IntoCallbackOutcome,
+        D: Data,
+    {
+        let (mut audio_unit, config) = open::<S, D>(&self.request, false)?;
+        audio_unit.set_render_callback(f)?;
+        Ok(Stream::new(audio_unit, config))
+    }
+}
+
+impl<S: Sample> InputStreamBuilder<S> {
+    /// Open the stream, calling `f` with each buffer of input once the stream is played.
+    ///
+    /// The layout of the stream is chosen to match `D`. Returns
+    /// `Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat` if `D` does not hold
+    /// samples of type `S`.
+    pub fn build<D, F, R>(self, f: F) -> Result<Stream, Error>
+    where
+        F: FnMut(Args<D>) -> R + 'static,
+        R: IntoCallbackOutcome,
+        D: Data,
+    {
+        let (mut audio_unit, config) = open::<S, D>(&self.request, true)?;
+        audio_unit.set_input_callback(f)?;
+        Ok(Stream::new(audio_unit, config))
+    }
+}
+
+/// A stream opened by an **OutputStreamBuilder** or **InputStreamBuilder**.
+///
+/// The stream is created paused, and stops when dropped.
+pub struct Stream {
+    audio_unit: AudioUnit,
+    config: StreamConfig,
+    playing: bool,
+}
+
+impl Stream {
+    fn new(audio_unit: AudioUnit, config: StreamConfig) -> Self {
+        Stream {
+            audio_unit,
+            config,
+            playing: false,
+        }
+    }
+
+    /// Start calling the callback.
+    pub fn play(&mut self) -> Result<(), Error> {
+        self.audio_unit.start()?;
+        self.playing = true;
+        Ok(())
+    }
+
+    /// Stop calling the callback.
+    pub fn pause(&mut self) -> Result<(), Error> {
+        self.audio_unit.stop()?;
+        self.playing = false;
+        Ok(())
+    }
+
+    /// Returns `true` if the stream has been played and not paused since.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The configuration negotiated with the device.
+    pub fn config(&self) -> &StreamConfig {
+        &self.config
+    }
+
+    /// The unit driving the stream.
+    pub fn audio_unit(&self) -> &AudioUnit {
+        &self.audio_unit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::render_callback::data;
+
+    fn range(minimum: f64, maximum: f64) -> AudioValueRange {
+        AudioValueRange {
+            mMinimum: minimum,
+            mMaximum: maximum,
+        }
+    }
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            sample_rates: vec![range(44_100.0, 44_100.0), range(48_000.0, 48_000.0)],
+            sample_rate: 44_100.0,
+            channels: 2,
+            buffer_frame_range: range(32.0, 4096.0),
+            buffer_frames: 512,
+        }
+    }
+
+    #[test]
+    fn test_negotiates_closest_supported_settings() {
+        let capabilities = capabilities();
+        let request = Request {
+            sample_rate: Some(96_000.0),
+            channels: Some(8),
+            buffer_frames: Some(16),
+            ..Request::default()
+        };
+        let expected = Negotiated {
+            sample_rate: 48_000.0,
+            channels: 2,
+            buffer_frames: 32,
+        };
+        assert_eq!(negotiate(&request, &capabilities, true), expected);
+
+        let expected = Negotiated {
+            sample_rate: 96_000.0,
+            ..expected
+        };
+        assert_eq!(negotiate(&request, &capabilities, false), expected);
+
+        let expected = Negotiated {
+            sample_rate: 44_100.0,
+            channels: 2,
+            buffer_frames: 512,
+        };
+        assert_eq!(
+            negotiate(&Request::default(), &capabilities, true),
+            expected
+        );
+        assert_eq!(
+            negotiate(&Request::default(), &capabilities, false),
+            expected
+        );
+
+        // A rate inside a continuous range cannot be applied to the device.
+        let mut continuous = capabilities.clone();
+        continuous.sample_rates.push(range(88_200.0, 192_000.0));
+        assert_eq!(negotiate(&request, &continuous, true).sample_rate, 48_000.0);
+
+        let ranges = [range(8_000.0, 22_050.0), range(88_200.0, 96_000.0)];
+        assert_eq!(closest_sample_rate(&ranges, 16_000.0), Some(16_000.0));
+        assert_eq!(closest_sample_rate(&ranges, 55_125.0), Some(88_200.0));
+        assert_eq!(closest_sample_rate(&[], 44_100.0), None);
+    }
+
+    #[test]
+    fn test_stream_format_matches_data_layout() {
+        let format = stream_format_for::<f32, data::NonInterleaved<f32>>(48_000.0, 2).unwrap();
+        assert!(format.flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED));
+        assert!(format.flags.contains(LinearPcmFlags::IS_FLOAT));
+
+        let format = stream_format_for::<i16, data::Interleaved<i16>>(48_000.0, 2).unwrap();
+        assert!(!format.flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED));
+        assert_eq!(format.sample_format, SampleFormat::I16);
+
+        assert!(stream_format_for::<f32, data::Interleaved<i16>>(48_000.0, 2).is_none());
+    }
+}
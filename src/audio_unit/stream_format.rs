@@ -2,7 +2,7 @@
 //!
 //! Find the original `AudioStreamBasicDescription` reference [here](https://developer.apple.com/library/mac/documentation/MusicAudio/Reference/CoreAudioDataTypesRef/#//apple_ref/c/tdef/AudioStreamBasicDescription).
 
-use objc2_core_audio_types::AudioStreamBasicDescription;
+use crate::sys::AudioStreamBasicDescription;
 
 use super::audio_format::AudioFormat;
 use super::audio_format::LinearPcmFlags;
@@ -35,7 +35,7 @@ use crate::error::{self, Error};
 /// > - Mac input and output: Linear PCM with 32-bit floating point samples.
 /// > - Mac audio units and other audio processing: Noninterleaved linear PCM with 32-bit floating
 /// >   point samples.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct StreamFormat {
     /// The number of frames of audio data per second used to represent a signal.
     pub sample_rate: f64,
@@ -49,6 +49,9 @@ pub struct StreamFormat {
     /// interfaces from Audio Converter Services (TODO: look into exposing this).
     pub sample_format: SampleFormat,
     /// The format flags for the given StreamFormat.
+    ///
+    /// For fixed-point formats such as the 8.24 format used on iOS, the flags also hold the number
+    /// of fractional bits (see `LinearPcmFlags::fraction_bits`).
     pub flags: super::audio_format::LinearPcmFlags,
     /// The number of channels.
     pub channels: u32,
@@ -104,9 +107,15 @@ impl StreamFormat {
     }
 
     /// Convert a StreamFormat into an AudioStreamBasicDescription.
-    /// Note that this function assumes that only packed formats are used.
-    /// This only affects I24, since all other formats supported by `StreamFormat`
-    /// are always packed.
+    ///
+    /// Note that this function assumes that packed formats are used, with the exception of I24.
+    /// I24 samples are packed into 3 bytes when the `IS_PACKED` flag is set, and otherwise occupy
+    /// 4 bytes aligned according to the `IS_ALIGNED_HIGH` flag. All other formats supported by
+    /// `StreamFormat` are always packed.
+    ///
+    /// Earlier versions added `IS_PACKED` to I24 formats as well. An I24 `StreamFormat` without
+    /// `IS_PACKED` now describes 4-byte samples aligned low, so set the flag explicitly to keep
+    /// the 3-byte layout.
     pub fn to_asbd(self) -> AudioStreamBasicDescription {
         let StreamFormat {
             sample_rate,
@@ -115,16 +124,20 @@ impl StreamFormat {
             channels,
         } = self;
 
-        let (format, maybe_flag) =
-            AudioFormat::LinearPCM(flags | LinearPcmFlags::IS_PACKED).as_format_and_flag();
+        let flags = match sample_format {
+            SampleFormat::I24 => flags,
+            _ => flags | LinearPcmFlags::IS_PACKED,
+        };
+        let (format, maybe_flag) = AudioFormat::LinearPCM(flags).as_format_and_flag();
 
         let flag = maybe_flag.unwrap_or(u32::MAX - 2147483647);
 
         let non_interleaved = flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED);
+        let sample_bytes = sample_format.size_in_bytes_with_flags(flags) as u32;
         let bytes_per_frame = if non_interleaved {
-            sample_format.size_in_bytes() as u32
+            sample_bytes
         } else {
-            sample_format.size_in_bytes() as u32 * channels
+            sample_bytes * channels
         };
         const FRAMES_PER_PACKET: u32 = 1;
         let bytes_per_packet = bytes_per_frame * FRAMES_PER_PACKET;
@@ -143,3 +156,72 @@ impl StreamFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::{
+        kAudioFormatFlagIsBigEndian, kAudioFormatFlagIsFloat, kAudioFormatFlagIsNonInterleaved,
+        kAudioFormatFlagIsPacked, kAudioFormatFlagIsSignedInteger, kAudioFormatLinearPCM,
+    };
+
+    fn asbd(
+        flags: u32,
+        bytes_per_frame: u32,
+        channels: u32,
+        bits: u32,
+    ) -> AudioStreamBasicDescription {
+        AudioStreamBasicDescription {
+            mSampleRate: 48_000.0,
+            mFormatID: kAudioFormatLinearPCM,
+            mFormatFlags: flags,
+            mBytesPerPacket: bytes_per_frame,
+            mFramesPerPacket: 1,
+            mBytesPerFrame: bytes_per_frame,
+            mChannelsPerFrame: channels,
+            mBitsPerChannel: bits,
+            mReserved: 0,
+        }
+    }
+
+    fn assert_round_trip(asbd: AudioStreamBasicDescription, sample_format: SampleFormat) {
+        let stream_format = StreamFormat::from_asbd(asbd).unwrap();
+        assert_eq!(stream_format.sample_format, sample_format);
+        assert_eq!(stream_format.channels, asbd.mChannelsPerFrame);
+        assert_eq!(stream_format.to_asbd(), asbd);
+    }
+
+    #[test]
+    fn test_unsigned_round_trip() {
+        let flags = kAudioFormatFlagIsPacked;
+        assert_round_trip(asbd(flags, 2, 2, 8), SampleFormat::U8);
+        assert_round_trip(asbd(flags, 4, 2, 16), SampleFormat::U16);
+    }
+
+    #[test]
+    fn test_f64_round_trip() {
+        let flags = kAudioFormatFlagIsFloat | kAudioFormatFlagIsPacked;
+        assert_round_trip(asbd(flags, 16, 2, 64), SampleFormat::F64);
+        let flags = flags | kAudioFormatFlagIsNonInterleaved;
+        assert_round_trip(asbd(flags, 8, 2, 64), SampleFormat::F64);
+    }
+
+    #[test]
+    fn test_big_endian_round_trip() {
+        let flags = kAudioFormatFlagIsSignedInteger
+            | kAudioFormatFlagIsPacked
+            | kAudioFormatFlagIsBigEndian;
+        assert_round_trip(asbd(flags, 4, 2, 16), SampleFormat::I16);
+        let stream_format = StreamFormat::from_asbd(asbd(flags, 4, 2, 16)).unwrap();
+        assert!(stream_format.flags.contains(LinearPcmFlags::IS_BIG_ENDIAN));
+        let flags =
+            kAudioFormatFlagIsFloat | kAudioFormatFlagIsPacked | kAudioFormatFlagIsBigEndian;
+        assert_round_trip(asbd(flags, 8, 1, 64), SampleFormat::F64);
+    }
+
+    #[test]
+    fn test_unsupported_bits() {
+        let flags = kAudioFormatFlagIsPacked;
+        assert!(StreamFormat::from_asbd(asbd(flags, 8, 2, 32)).is_err());
+    }
+}
@@ -5,8 +5,8 @@
 #![allow(deprecated)]
 
 //#[cfg(target_os = "ios")]
-//use objc2_audio_toolbox::kAudioUnitSubType_RemoteIO;
-use objc2_audio_toolbox::{
+//use crate::sys::kAudioUnitSubType_RemoteIO;
+use crate::sys::{
     kAudioUnitSubType_3DMixer, kAudioUnitSubType_AUConverter, kAudioUnitSubType_AUFilter,
     kAudioUnitSubType_AUiPodTimeOther, kAudioUnitSubType_AudioFilePlayer,
     kAudioUnitSubType_BandPassFilter, kAudioUnitSubType_DLSSynth, kAudioUnitSubType_DefaultOutput,
@@ -31,7 +31,7 @@ use objc2_audio_toolbox::{
 /// Represents the different kinds of Audio Units that are available.
 ///
 /// Original documentation [here](https://developer.apple.com/library/prerelease/mac/documentation/AudioUnit/Reference/AUComponentServicesReference/index.html#//apple_ref/doc/constant_group/Audio_Unit_Types).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Type {
     /// Provides input, output, or both input and output simultaneously.
     ///
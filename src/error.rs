@@ -4,19 +4,21 @@ pub use self::audio::Error as AudioError;
 pub use self::audio_codec::Error as AudioCodecError;
 pub use self::audio_format::Error as AudioFormatError;
 pub use self::audio_unit::Error as AudioUnitError;
+use crate::audio_unit::Type;
 use crate::OSStatus;
 
-use objc2_audio_toolbox::{
+use crate::sys::kAudio_ParamError;
+use crate::sys::{
     kAudioServicesSystemSoundClientTimedOutError, kAudioServicesSystemSoundUnspecifiedError,
 };
 
 pub mod audio {
-    use crate::OSStatus;
-    use objc2_core_audio_types::{
+    use crate::sys::{
         kAudio_BadFilePathError, kAudio_FileNotFoundError, kAudio_FilePermissionError,
         kAudio_MemFullError, kAudio_ParamError, kAudio_TooManyFilesOpenError,
         kAudio_UnimplementedError,
     };
+    use crate::OSStatus;
 
     #[derive(Copy, Clone, Debug)]
     pub enum Error {
@@ -70,13 +72,13 @@ pub mod audio {
 }
 
 pub mod audio_codec {
-    use crate::OSStatus;
-    use objc2_audio_toolbox::{
+    use crate::sys::{
         kAudioCodecBadDataError, kAudioCodecBadPropertySizeError, kAudioCodecIllegalOperationError,
         kAudioCodecNotEnoughBufferSpaceError, kAudioCodecStateError,
         kAudioCodecUnknownPropertyError, kAudioCodecUnspecifiedError,
         kAudioCodecUnsupportedFormatError,
     };
+    use crate::OSStatus;
 
     #[derive(Copy, Clone, Debug)]
     pub enum Error {
@@ -137,12 +139,12 @@ pub mod audio_codec {
 }
 
 pub mod audio_format {
-    use crate::OSStatus;
-    use objc2_audio_toolbox::{
+    use crate::sys::{
         kAudioFormatBadPropertySizeError, kAudioFormatBadSpecifierSizeError,
         kAudioFormatUnknownFormatError, kAudioFormatUnspecifiedError,
         kAudioFormatUnsupportedDataFormatError, kAudioFormatUnsupportedPropertyError,
     };
+    use crate::OSStatus;
 
     // TODO: Finish implementing these values.
     #[derive(Copy, Clone, Debug)]
@@ -198,8 +200,7 @@ pub mod audio_format {
 }
 
 pub mod audio_unit {
-    use crate::OSStatus;
-    use objc2_audio_toolbox::{
+    use crate::sys::{
         kAudioUnitErr_CannotDoInCurrentContext, kAudioUnitErr_FailedInitialization,
         kAudioUnitErr_FormatNotSupported, kAudioUnitErr_Initialized, kAudioUnitErr_InvalidElement,
         kAudioUnitErr_InvalidFile, kAudioUnitErr_InvalidOfflineRender,
@@ -209,6 +210,7 @@ pub mod audio_unit {
         kAudioUnitErr_TooManyFramesToProcess, kAudioUnitErr_Unauthorized,
         kAudioUnitErr_Uninitialized,
     };
+    use crate::OSStatus;
 
     #[derive(Copy, Clone, Debug)]
     pub enum Error {
@@ -305,16 +307,54 @@ pub mod audio_unit {
 }
 
 /// A wrapper around all possible Core Audio errors.
+///
+/// New errors may be added in future versions, so matches on an **Error** need a wildcard arm.
 #[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
 pub enum Error {
     Unspecified,
     SystemSoundClientMessageTimedOut,
     NoMatchingDefaultAudioUnitFound,
     RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat,
     NoKnownSubtype,
+    /// No longer returned: non-interleaved input is captured with one buffer per channel.
+    #[deprecated(note = "non-interleaved input now supports any number of channels")]
     NonInterleavedInputOnlySupportsMono,
     UnsupportedSampleRate,
     UnsupportedStreamFormat,
+    /// The size of a buffer handed to a callback does not match the number of frames and the
+    /// sample format.
+    BufferSizeMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// The buffer list handed to a callback is missing, or holds fewer buffers than the stream
+    /// format requires.
+    BufferCountMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// The size of a variable-length property is not a multiple of the size of its elements.
+    PropertySizeMismatch {
+        size: u32,
+        element_size: u32,
+    },
+    /// A typed parameter was used with an **AudioUnit** of a different **Type**.
+    ParameterUnitTypeMismatch {
+        expected: Type,
+        actual: Option<Type>,
+    },
+    /// A graph connection would make a unit depend on its own output.
+    GraphCycle,
+    /// A graph connection refers to a node that is not part of the graph.
+    GraphInvalidNode,
+    /// A graph connection feeds an input that is already connected.
+    GraphInputAlreadyConnected,
+    /// The destination of a graph connection does not accept the format of its source. `status`
+    /// is the `OSStatus` returned when setting the format.
+    GraphFormatMismatch {
+        status: OSStatus,
+    },
     Audio(AudioError),
     AudioCodec(AudioCodecError),
     AudioFormat(AudioFormatError),
@@ -366,9 +406,16 @@ impl Error {
                 kAudioServicesSystemSoundUnspecifiedError
             }
             Error::SystemSoundClientMessageTimedOut => kAudioServicesSystemSoundClientTimedOutError,
+            Error::BufferSizeMismatch { .. } => kAudio_ParamError,
+            Error::BufferCountMismatch { .. } => kAudio_ParamError,
+            Error::PropertySizeMismatch { .. } => kAudio_ParamError,
+            Error::ParameterUnitTypeMismatch { .. } => kAudio_ParamError,
+            Error::GraphFormatMismatch { status } => status,
             Error::Audio(err) => err as OSStatus,
             Error::AudioCodec(err) => err as OSStatus,
+            Error::AudioFormat(err) => err as OSStatus,
             Error::AudioUnit(err) => err as OSStatus,
+            Error::Unknown(os_status) => os_status,
             _ => kAudioServicesSystemSoundUnspecifiedError,
         }
     }
@@ -385,9 +432,23 @@ impl ::std::fmt::Display for Error {
                 write!(f, "The given render callback buffer format does not match the `AudioUnit` `StreamFormat`"),
             Error::SystemSoundClientMessageTimedOut => write!(f, "The system sound client message timed out"),
             Error::NoKnownSubtype => write!(f, "The type has no known subtypes"),
+            #[allow(deprecated)]
             Error::NonInterleavedInputOnlySupportsMono => write!(f, "In non-interleaved mode input only supports one channel"),
             Error::UnsupportedSampleRate => write!(f, "The requested sample rate is not available"),
             Error::UnsupportedStreamFormat => write!(f, "The requested stream format is not available"),
+            Error::BufferSizeMismatch { expected, actual } =>
+                write!(f, "The callback buffer holds {actual} bytes but {expected} were expected"),
+            Error::BufferCountMismatch { expected, actual } =>
+                write!(f, "The callback buffer list holds {actual} buffers but {expected} were expected"),
+            Error::PropertySizeMismatch { size, element_size } =>
+                write!(f, "The property holds {size} bytes, which is not a multiple of the element size {element_size}"),
+            Error::ParameterUnitTypeMismatch { expected, actual } =>
+                write!(f, "The parameter belongs to a {expected:?} unit but the unit is {actual:?}"),
+            Error::GraphCycle => write!(f, "The connection would create a cycle in the graph"),
+            Error::GraphInvalidNode => write!(f, "The node is not part of the graph"),
+            Error::GraphInputAlreadyConnected => write!(f, "The input is already connected"),
+            Error::GraphFormatMismatch { status } =>
+                write!(f, "The destination does not accept the stream format of the source, OSStatus: {status}"),
             Error::Audio(ref err) => write!(f, "{err}"),
             Error::AudioCodec(ref err) => write!(f, "{err}"),
             Error::AudioFormat(ref err) => write!(f, "{err}"),
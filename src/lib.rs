@@ -17,6 +17,7 @@ pub use error::Error;
 #[cfg(feature = "audio_toolbox")]
 pub mod audio_unit;
 pub mod error;
+pub mod sys;
 
 // MacTypes.h
 pub type OSStatus = i32;
@@ -0,0 +1,143 @@
+//! The Core Audio types and constants this crate is written against.
+//!
+//! On Apple platforms these are re-exported from the objc2 framework bindings. Elsewhere the crate
+//! provides its own `#[repr(C)]` definitions with the same layouts and values, so that everything
+//! short of talking to a real device (the **Backend** trait, the simulated host and the format
+//! conversions) builds and runs on any platform.
+
+#[cfg(not(target_vendor = "apple"))]
+mod portable;
+
+#[cfg(not(target_vendor = "apple"))]
+pub use self::portable::*;
+
+#[cfg(target_vendor = "apple")]
+#[allow(deprecated)]
+pub use objc2_audio_toolbox::{
+    k3DMixerParam_Azimuth, k3DMixerParam_BusEnable, k3DMixerParam_Distance,
+    k3DMixerParam_DryWetReverbBlend, k3DMixerParam_Elevation, k3DMixerParam_Gain,
+    k3DMixerParam_GlobalReverbGainInDecibels, k3DMixerParam_MaxGainInDecibels,
+    k3DMixerParam_MinGainInDecibels, k3DMixerParam_ObstructionAttenuationInDecibels,
+    k3DMixerParam_OcclusionAttenuationInDecibels, k3DMixerParam_PlaybackRate,
+    k3DMixerParam_PostAveragePower, k3DMixerParam_PostPeakHoldLevel, k3DMixerParam_PreAveragePower,
+    k3DMixerParam_PrePeakHoldLevel, kAULowShelfParam_CutoffFrequency, kAULowShelfParam_Gain,
+    kAUNBandEQParam_Bandwidth, kAUNBandEQParam_BypassBand, kAUNBandEQParam_FilterType,
+    kAUNBandEQParam_Frequency, kAUNBandEQParam_Gain, kAUNBandEQParam_GlobalGain,
+    kAudioCodecBadDataError, kAudioCodecBadPropertySizeError, kAudioCodecIllegalOperationError,
+    kAudioCodecNotEnoughBufferSpaceError, kAudioCodecStateError, kAudioCodecUnknownPropertyError,
+    kAudioCodecUnspecifiedError, kAudioCodecUnsupportedFormatError,
+    kAudioFormatBadPropertySizeError, kAudioFormatBadSpecifierSizeError,
+    kAudioFormatUnknownFormatError, kAudioFormatUnspecifiedError,
+    kAudioFormatUnsupportedDataFormatError, kAudioFormatUnsupportedPropertyError,
+    kAudioOutputUnitProperty_IsRunning, kAudioOutputUnitProperty_SetInputCallback,
+    kAudioServicesSystemSoundClientTimedOutError, kAudioServicesSystemSoundUnspecifiedError,
+    kAudioSessionProperty_CurrentHardwareIOBufferDuration,
+    kAudioSessionProperty_CurrentHardwareSampleRate, kAudioUnitErr_CannotDoInCurrentContext,
+    kAudioUnitErr_FailedInitialization, kAudioUnitErr_FormatNotSupported,
+    kAudioUnitErr_Initialized, kAudioUnitErr_InvalidElement, kAudioUnitErr_InvalidFile,
+    kAudioUnitErr_InvalidOfflineRender, kAudioUnitErr_InvalidParameter,
+    kAudioUnitErr_InvalidProperty, kAudioUnitErr_InvalidPropertyValue, kAudioUnitErr_InvalidScope,
+    kAudioUnitErr_NoConnection, kAudioUnitErr_PropertyNotInUse, kAudioUnitErr_PropertyNotWritable,
+    kAudioUnitErr_TooManyFramesToProcess, kAudioUnitErr_Unauthorized, kAudioUnitErr_Uninitialized,
+    kAudioUnitManufacturer_Apple, kAudioUnitProperty_BypassEffect, kAudioUnitProperty_CPULoad,
+    kAudioUnitProperty_ElementCount, kAudioUnitProperty_LastRenderError,
+    kAudioUnitProperty_Latency, kAudioUnitProperty_MakeConnection,
+    kAudioUnitProperty_MaximumFramesPerSlice, kAudioUnitProperty_ParameterInfo,
+    kAudioUnitProperty_ParameterList, kAudioUnitProperty_SampleRate,
+    kAudioUnitProperty_SetRenderCallback, kAudioUnitProperty_ShouldAllocateBuffer,
+    kAudioUnitProperty_StreamFormat, kAudioUnitProperty_TailTime, kAudioUnitScope_Global,
+    kAudioUnitScope_Group, kAudioUnitScope_Input, kAudioUnitScope_Layer, kAudioUnitScope_LayerItem,
+    kAudioUnitScope_Note, kAudioUnitScope_Output, kAudioUnitScope_Part, kAudioUnitSubType_3DMixer,
+    kAudioUnitSubType_AUConverter, kAudioUnitSubType_AUFilter, kAudioUnitSubType_AUiPodTimeOther,
+    kAudioUnitSubType_AudioFilePlayer, kAudioUnitSubType_BandPassFilter,
+    kAudioUnitSubType_DLSSynth, kAudioUnitSubType_DefaultOutput,
+    kAudioUnitSubType_DeferredRenderer, kAudioUnitSubType_Delay, kAudioUnitSubType_Distortion,
+    kAudioUnitSubType_DynamicsProcessor, kAudioUnitSubType_GenericOutput,
+    kAudioUnitSubType_GraphicEQ, kAudioUnitSubType_HALOutput, kAudioUnitSubType_HighPassFilter,
+    kAudioUnitSubType_HighShelfFilter, kAudioUnitSubType_LowPassFilter,
+    kAudioUnitSubType_LowShelfFilter, kAudioUnitSubType_MatrixMixer,
+    kAudioUnitSubType_MatrixReverb, kAudioUnitSubType_Merger,
+    kAudioUnitSubType_MultiBandCompressor, kAudioUnitSubType_MultiChannelMixer,
+    kAudioUnitSubType_NBandEQ, kAudioUnitSubType_NetSend, kAudioUnitSubType_NewTimePitch,
+    kAudioUnitSubType_ParametricEQ, kAudioUnitSubType_PeakLimiter, kAudioUnitSubType_Pitch,
+    kAudioUnitSubType_RogerBeep, kAudioUnitSubType_SampleDelay, kAudioUnitSubType_Sampler,
+    kAudioUnitSubType_ScheduledSoundPlayer, kAudioUnitSubType_Splitter,
+    kAudioUnitSubType_StereoMixer, kAudioUnitSubType_SystemOutput, kAudioUnitSubType_TimePitch,
+    kAudioUnitSubType_Varispeed, kAudioUnitSubType_VoiceProcessingIO, kAudioUnitType_Effect,
+    kAudioUnitType_FormatConverter, kAudioUnitType_Generator, kAudioUnitType_MIDIProcessor,
+    kAudioUnitType_Mixer, kAudioUnitType_MusicDevice, kAudioUnitType_MusicEffect,
+    kAudioUnitType_OfflineEffect, kAudioUnitType_Output, kAudioUnitType_Panner,
+    kBandpassParam_Bandwidth, kBandpassParam_CenterFrequency, kDelayParam_DelayTime,
+    kDelayParam_Feedback, kDelayParam_LopassCutoff, kDelayParam_WetDryMix,
+    kDistortionParam_CubicTerm, kDistortionParam_Decay, kDistortionParam_Decimation,
+    kDistortionParam_DecimationMix, kDistortionParam_Delay, kDistortionParam_DelayMix,
+    kDistortionParam_FinalMix, kDistortionParam_LinearTerm, kDistortionParam_PolynomialMix,
+    kDistortionParam_RingModBalance, kDistortionParam_RingModFreq1, kDistortionParam_RingModFreq2,
+    kDistortionParam_RingModMix, kDistortionParam_Rounding, kDistortionParam_SoftClipGain,
+    kDistortionParam_SquaredTerm, kDynamicsProcessorParam_AttackTime,
+    kDynamicsProcessorParam_CompressionAmount, kDynamicsProcessorParam_ExpansionRatio,
+    kDynamicsProcessorParam_ExpansionThreshold, kDynamicsProcessorParam_HeadRoom,
+    kDynamicsProcessorParam_InputAmplitude, kDynamicsProcessorParam_OutputAmplitude,
+    kDynamicsProcessorParam_OverallGain, kDynamicsProcessorParam_ReleaseTime,
+    kDynamicsProcessorParam_Threshold, kGraphicEQParam_NumberOfBands,
+    kHighShelfParam_CutOffFrequency, kHighShelfParam_Gain, kHipassParam_CutoffFrequency,
+    kHipassParam_Resonance, kLimiterParam_AttackTime, kLimiterParam_DecayTime,
+    kLimiterParam_PreGain, kLowPassParam_CutoffFrequency, kLowPassParam_Resonance,
+    kMatrixMixerParam_Enable, kMatrixMixerParam_PostAveragePower,
+    kMatrixMixerParam_PostPeakHoldLevel, kMatrixMixerParam_PreAveragePower,
+    kMatrixMixerParam_PrePeakHoldLevel, kMatrixMixerParam_Volume, kMultiChannelMixerParam_Enable,
+    kMultiChannelMixerParam_Pan, kMultiChannelMixerParam_PostAveragePower,
+    kMultiChannelMixerParam_PostPeakHoldLevel, kMultiChannelMixerParam_PreAveragePower,
+    kMultiChannelMixerParam_PrePeakHoldLevel, kMultiChannelMixerParam_Volume,
+    kMultibandCompressorParam_AttackTime, kMultibandCompressorParam_Crossover1,
+    kMultibandCompressorParam_Crossover2, kMultibandCompressorParam_Crossover3,
+    kMultibandCompressorParam_EQ1, kMultibandCompressorParam_EQ2, kMultibandCompressorParam_EQ3,
+    kMultibandCompressorParam_EQ4, kMultibandCompressorParam_Headroom1,
+    kMultibandCompressorParam_Headroom2, kMultibandCompressorParam_Headroom3,
+    kMultibandCompressorParam_Headroom4, kMultibandCompressorParam_Postgain,
+    kMultibandCompressorParam_Pregain, kMultibandCompressorParam_ReleaseTime,
+    kMultibandCompressorParam_Threshold1, kMultibandCompressorParam_Threshold2,
+    kMultibandCompressorParam_Threshold3, kMultibandCompressorParam_Threshold4,
+    kParametricEQParam_CenterFreq, kParametricEQParam_Gain, kParametricEQParam_Q,
+    kReverbParam_DryWetMix, kReverbParam_FilterBandwidth, kReverbParam_FilterEnable,
+    kReverbParam_FilterFrequency, kReverbParam_FilterGain, kReverbParam_FilterType,
+    kReverbParam_LargeBrightness, kReverbParam_LargeDelay, kReverbParam_LargeDelayRange,
+    kReverbParam_LargeDensity, kReverbParam_LargeSize, kReverbParam_ModulationDepth,
+    kReverbParam_ModulationRate, kReverbParam_PreDelay, kReverbParam_SmallBrightness,
+    kReverbParam_SmallDelayRange, kReverbParam_SmallDensity, kReverbParam_SmallLargeMix,
+    kReverbParam_SmallSize, kSampleDelayParam_DelayFrames, kTimePitchParam_EffectBlend,
+    kTimePitchParam_Pitch, kTimePitchParam_Rate, AURenderCallback, AURenderCallbackStruct,
+    AudioComponentInstance, AudioUnit, AudioUnitConnection, AudioUnitParameterInfo,
+    AudioUnitParameterOptions, AudioUnitParameterUnit, AudioUnitPropertyListenerProc,
+    AudioUnitRenderActionFlags, OpaqueAudioComponentInstance,
+};
+
+#[cfg(target_vendor = "apple")]
+pub use objc2_core_audio::kAudioDevicePropertyBufferFrameSize;
+
+#[cfg(target_vendor = "apple")]
+pub use objc2_core_audio_types::{
+    kAppleLosslessFormatFlag_16BitSourceData, kAppleLosslessFormatFlag_20BitSourceData,
+    kAppleLosslessFormatFlag_24BitSourceData, kAppleLosslessFormatFlag_32BitSourceData,
+    kAudioFormat60958AC3, kAudioFormatAC3, kAudioFormatAES3, kAudioFormatALaw, kAudioFormatAMR,
+    kAudioFormatAMR_WB, kAudioFormatAppleIMA4, kAudioFormatAppleLossless, kAudioFormatAudible,
+    kAudioFormatDVIIntelIMA, kAudioFormatFlagIsAlignedHigh, kAudioFormatFlagIsBigEndian,
+    kAudioFormatFlagIsFloat, kAudioFormatFlagIsNonInterleaved, kAudioFormatFlagIsNonMixable,
+    kAudioFormatFlagIsPacked, kAudioFormatFlagIsSignedInteger, kAudioFormatLinearPCM,
+    kAudioFormatMACE3, kAudioFormatMACE6, kAudioFormatMIDIStream, kAudioFormatMPEG4AAC,
+    kAudioFormatMPEG4AAC_ELD, kAudioFormatMPEG4AAC_ELD_SBR, kAudioFormatMPEG4AAC_ELD_V2,
+    kAudioFormatMPEG4AAC_HE, kAudioFormatMPEG4AAC_HE_V2, kAudioFormatMPEG4AAC_LD,
+    kAudioFormatMPEG4AAC_Spatial, kAudioFormatMPEG4CELP, kAudioFormatMPEG4HVXC,
+    kAudioFormatMPEG4TwinVQ, kAudioFormatMPEGLayer1, kAudioFormatMPEGLayer2,
+    kAudioFormatMPEGLayer3, kAudioFormatMicrosoftGSM, kAudioFormatParameterValueStream,
+    kAudioFormatQDesign, kAudioFormatQDesign2, kAudioFormatQUALCOMM, kAudioFormatTimeCode,
+    kAudioFormatULaw, kAudioFormatiLBC, kAudio_BadFilePathError, kAudio_FileNotFoundError,
+    kAudio_FilePermissionError, kAudio_MemFullError, kAudio_ParamError,
+    kAudio_TooManyFilesOpenError, kAudio_UnimplementedError,
+    kLinearPCMFormatFlagsSampleFractionMask, kLinearPCMFormatFlagsSampleFractionShift, AudioBuffer,
+    AudioBufferList, AudioStreamBasicDescription, AudioTimeStamp, AudioTimeStampFlags,
+    MPEG4ObjectID, SMPTETime, SMPTETimeFlags, SMPTETimeType,
+};
+
+#[cfg(target_vendor = "apple")]
+pub use objc2_core_foundation::CFString;
@@ -0,0 +1,633 @@
+//! Declarations of the Core Audio types and constants used by this crate, for targets without
+//! the Apple frameworks.
+//!
+//! The layouts and values mirror those of the Audio Toolbox, Core Audio and Core Audio Types
+//! headers, as bound by the objc2 crates on Apple platforms.
+
+#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
+
+use crate::OSStatus;
+use std::ops::{BitOr, BitOrAssign};
+use std::os::raw::{c_char, c_long, c_void};
+use std::ptr::NonNull;
+
+/// Implement the bitwise operators used to combine the flags of a flags newtype.
+macro_rules! impl_flags {
+    ($name:ident) => {
+        impl BitOr for $name {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+    };
+}
+
+/// The opaque instance behind an `AudioComponentInstance`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct OpaqueAudioComponentInstance {
+    _private: [u8; 0],
+}
+
+pub type AudioComponentInstance = *mut OpaqueAudioComponentInstance;
+
+pub type AudioUnit = AudioComponentInstance;
+
+/// An opaque Core Foundation string. None exist outside Apple platforms, so pointers to this
+/// type are always null.
+#[repr(C)]
+#[derive(Debug)]
+pub struct CFString {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioBuffer {
+    pub mNumberChannels: u32,
+    pub mDataByteSize: u32,
+    pub mData: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioBufferList {
+    pub mNumberBuffers: u32,
+    pub mBuffers: [AudioBuffer; 1],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioStreamBasicDescription {
+    pub mSampleRate: f64,
+    pub mFormatID: u32,
+    pub mFormatFlags: u32,
+    pub mBytesPerPacket: u32,
+    pub mFramesPerPacket: u32,
+    pub mBytesPerFrame: u32,
+    pub mChannelsPerFrame: u32,
+    pub mBitsPerChannel: u32,
+    pub mReserved: u32,
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SMPTETimeType(pub u32);
+
+impl SMPTETimeType {
+    pub const Type24: Self = Self(0);
+    pub const Type25: Self = Self(1);
+    pub const Type30Drop: Self = Self(2);
+    pub const Type30: Self = Self(3);
+    pub const Type2997: Self = Self(4);
+    pub const Type2997Drop: Self = Self(5);
+    pub const Type60: Self = Self(6);
+    pub const Type5994: Self = Self(7);
+    pub const Type60Drop: Self = Self(8);
+    pub const Type5994Drop: Self = Self(9);
+    pub const Type50: Self = Self(10);
+    pub const Type2398: Self = Self(11);
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SMPTETimeFlags(pub u32);
+
+impl SMPTETimeFlags {
+    pub const Unknown: Self = Self(0);
+    pub const Valid: Self = Self(1 << 0);
+    pub const Running: Self = Self(1 << 1);
+}
+
+impl_flags!(SMPTETimeFlags);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SMPTETime {
+    pub mSubframes: i16,
+    pub mSubframeDivisor: i16,
+    pub mCounter: u32,
+    pub mType: SMPTETimeType,
+    pub mFlags: SMPTETimeFlags,
+    pub mHours: i16,
+    pub mMinutes: i16,
+    pub mSeconds: i16,
+    pub mFrames: i16,
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AudioTimeStampFlags(pub u32);
+
+impl AudioTimeStampFlags {
+    pub const NothingValid: Self = Self(0);
+    pub const SampleTimeValid: Self = Self(1 << 0);
+    pub const HostTimeValid: Self = Self(1 << 1);
+    pub const RateScalarValid: Self = Self(1 << 2);
+    pub const WordClockTimeValid: Self = Self(1 << 3);
+    pub const SMPTETimeValid: Self = Self(1 << 4);
+    pub const SampleHostTimeValid: Self = Self(Self::SampleTimeValid.0 | Self::HostTimeValid.0);
+}
+
+impl_flags!(AudioTimeStampFlags);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioTimeStamp {
+    pub mSampleTime: f64,
+    pub mHostTime: u64,
+    pub mRateScalar: f64,
+    pub mWordClockTime: u64,
+    pub mSMPTETime: SMPTETime,
+    pub mFlags: AudioTimeStampFlags,
+    pub mReserved: u32,
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MPEG4ObjectID(pub c_long);
+
+impl MPEG4ObjectID {
+    pub const AAC_Main: Self = Self(1);
+    pub const AAC_LC: Self = Self(2);
+    pub const AAC_SSR: Self = Self(3);
+    pub const AAC_LTP: Self = Self(4);
+    pub const AAC_SBR: Self = Self(5);
+    pub const AAC_Scalable: Self = Self(6);
+    pub const TwinVQ: Self = Self(7);
+    pub const CELP: Self = Self(8);
+    pub const HVXC: Self = Self(9);
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AudioUnitRenderActionFlags(pub u32);
+
+impl AudioUnitRenderActionFlags {
+    pub const UnitRenderAction_PreRender: Self = Self(1 << 2);
+    pub const UnitRenderAction_PostRender: Self = Self(1 << 3);
+    pub const UnitRenderAction_OutputIsSilence: Self = Self(1 << 4);
+    pub const OfflineUnitRenderAction_Preflight: Self = Self(1 << 5);
+    pub const OfflineUnitRenderAction_Render: Self = Self(1 << 6);
+    pub const OfflineUnitRenderAction_Complete: Self = Self(1 << 7);
+    pub const UnitRenderAction_PostRenderError: Self = Self(1 << 8);
+    pub const UnitRenderAction_DoNotCheckRenderArgs: Self = Self(1 << 9);
+}
+
+impl_flags!(AudioUnitRenderActionFlags);
+
+pub type AURenderCallback = Option<
+    unsafe extern "C-unwind" fn(
+        NonNull<c_void>,
+        NonNull<AudioUnitRenderActionFlags>,
+        NonNull<AudioTimeStamp>,
+        u32,
+        u32,
+        *mut AudioBufferList,
+    ) -> OSStatus,
+>;
+
+pub type AudioUnitPropertyListenerProc =
+    Option<unsafe extern "C-unwind" fn(NonNull<c_void>, AudioUnit, u32, u32, u32)>;
+
+#[repr(C)]
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AURenderCallbackStruct {
+    pub inputProc: AURenderCallback,
+    pub inputProcRefCon: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioUnitConnection {
+    pub sourceAudioUnit: AudioUnit,
+    pub sourceOutputNumber: u32,
+    pub destInputNumber: u32,
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AudioUnitParameterUnit(pub u32);
+
+impl AudioUnitParameterUnit {
+    pub const Generic: Self = Self(0);
+    pub const Indexed: Self = Self(1);
+    pub const Boolean: Self = Self(2);
+    pub const Percent: Self = Self(3);
+    pub const Seconds: Self = Self(4);
+    pub const SampleFrames: Self = Self(5);
+    pub const Phase: Self = Self(6);
+    pub const Rate: Self = Self(7);
+    pub const Hertz: Self = Self(8);
+    pub const Cents: Self = Self(9);
+    pub const RelativeSemiTones: Self = Self(10);
+    pub const MIDINoteNumber: Self = Self(11);
+    pub const MIDIController: Self = Self(12);
+    pub const Decibels: Self = Self(13);
+    pub const LinearGain: Self = Self(14);
+    pub const Degrees: Self = Self(15);
+    pub const EqualPowerCrossfade: Self = Self(16);
+    pub const MixerFaderCurve1: Self = Self(17);
+    pub const Pan: Self = Self(18);
+    pub const Meters: Self = Self(19);
+    pub const AbsoluteCents: Self = Self(20);
+    pub const Octaves: Self = Self(21);
+    pub const BPM: Self = Self(22);
+    pub const Beats: Self = Self(23);
+    pub const Milliseconds: Self = Self(24);
+    pub const Ratio: Self = Self(25);
+    pub const CustomUnit: Self = Self(26);
+    pub const MIDI2Controller: Self = Self(27);
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AudioUnitParameterOptions(pub u32);
+
+impl AudioUnitParameterOptions {
+    pub const Flag_CFNameRelease: Self = Self(1 << 4);
+    pub const Flag_OmitFromPresets: Self = Self(1 << 13);
+    pub const Flag_PlotHistory: Self = Self(1 << 14);
+    pub const Flag_MeterReadOnly: Self = Self(1 << 15);
+    pub const Flag_DisplayMask: Self = Self((7 << 16) | (1 << 22));
+    pub const Flag_DisplaySquareRoot: Self = Self(1 << 16);
+    pub const Flag_DisplaySquared: Self = Self(2 << 16);
+    pub const Flag_DisplayCubed: Self = Self(3 << 16);
+    pub const Flag_DisplayCubeRoot: Self = Self(4 << 16);
+    pub const Flag_DisplayExponential: Self = Self(5 << 16);
+    pub const Flag_HasClump: Self = Self(1 << 20);
+    pub const Flag_ValuesHaveStrings: Self = Self(1 << 21);
+    pub const Flag_DisplayLogarithmic: Self = Self(1 << 22);
+    pub const Flag_IsHighResolution: Self = Self(1 << 23);
+    pub const Flag_NonRealTime: Self = Self(1 << 24);
+    pub const Flag_CanRamp: Self = Self(1 << 25);
+    pub const Flag_ExpertMode: Self = Self(1 << 26);
+    pub const Flag_HasCFNameString: Self = Self(1 << 27);
+    pub const Flag_IsGlobalMeta: Self = Self(1 << 28);
+    pub const Flag_IsElementMeta: Self = Self(1 << 29);
+    pub const Flag_IsReadable: Self = Self(1 << 30);
+    pub const Flag_IsWritable: Self = Self(1 << 31);
+}
+
+impl_flags!(AudioUnitParameterOptions);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioUnitParameterInfo {
+    pub name: [c_char; 52],
+    pub unitName: *const CFString,
+    pub clumpID: u32,
+    pub cfNameString: *const CFString,
+    pub unit: AudioUnitParameterUnit,
+    pub minValue: f32,
+    pub maxValue: f32,
+    pub defaultValue: f32,
+    pub flags: AudioUnitParameterOptions,
+}
+
+// AUComponent.h
+
+pub const kAudioUnitType_Output: u32 = 0x61756f75;
+pub const kAudioUnitType_MusicDevice: u32 = 0x61756d75;
+pub const kAudioUnitType_MusicEffect: u32 = 0x61756d66;
+pub const kAudioUnitType_FormatConverter: u32 = 0x61756663;
+pub const kAudioUnitType_Effect: u32 = 0x61756678;
+pub const kAudioUnitType_Mixer: u32 = 0x61756d78;
+pub const kAudioUnitType_Panner: u32 = 0x6175706e;
+pub const kAudioUnitType_Generator: u32 = 0x6175676e;
+pub const kAudioUnitType_OfflineEffect: u32 = 0x61756f6c;
+pub const kAudioUnitType_MIDIProcessor: u32 = 0x61756d69;
+pub const kAudioUnitManufacturer_Apple: u32 = 0x6170706c;
+pub const kAudioUnitSubType_GenericOutput: u32 = 0x67656e72;
+pub const kAudioUnitSubType_VoiceProcessingIO: u32 = 0x7670696f;
+pub const kAudioUnitSubType_HALOutput: u32 = 0x6168616c;
+pub const kAudioUnitSubType_DefaultOutput: u32 = 0x64656620;
+pub const kAudioUnitSubType_SystemOutput: u32 = 0x73797320;
+pub const kAudioUnitSubType_DLSSynth: u32 = 0x646c7320;
+pub const kAudioUnitSubType_Sampler: u32 = 0x73616d70;
+pub const kAudioUnitSubType_AUConverter: u32 = 0x636f6e76;
+pub const kAudioUnitSubType_Varispeed: u32 = 0x76617269;
+pub const kAudioUnitSubType_DeferredRenderer: u32 = 0x64656672;
+pub const kAudioUnitSubType_Splitter: u32 = 0x73706c74;
+pub const kAudioUnitSubType_Merger: u32 = 0x6d657267;
+pub const kAudioUnitSubType_NewTimePitch: u32 = 0x6e757470;
+pub const kAudioUnitSubType_AUiPodTimeOther: u32 = 0x6970746f;
+pub const kAudioUnitSubType_TimePitch: u32 = 0x746d7074;
+pub const kAudioUnitSubType_PeakLimiter: u32 = 0x6c6d7472;
+pub const kAudioUnitSubType_DynamicsProcessor: u32 = 0x64636d70;
+pub const kAudioUnitSubType_LowPassFilter: u32 = 0x6c706173;
+pub const kAudioUnitSubType_HighPassFilter: u32 = 0x68706173;
+pub const kAudioUnitSubType_BandPassFilter: u32 = 0x62706173;
+pub const kAudioUnitSubType_HighShelfFilter: u32 = 0x68736866;
+pub const kAudioUnitSubType_LowShelfFilter: u32 = 0x6c736866;
+pub const kAudioUnitSubType_ParametricEQ: u32 = 0x706d6571;
+pub const kAudioUnitSubType_Distortion: u32 = 0x64697374;
+pub const kAudioUnitSubType_Delay: u32 = 0x64656c79;
+pub const kAudioUnitSubType_SampleDelay: u32 = 0x73646c79;
+pub const kAudioUnitSubType_NBandEQ: u32 = 0x6e626571;
+pub const kAudioUnitSubType_GraphicEQ: u32 = 0x67726571;
+pub const kAudioUnitSubType_MultiBandCompressor: u32 = 0x6d636d70;
+pub const kAudioUnitSubType_MatrixReverb: u32 = 0x6d726576;
+pub const kAudioUnitSubType_Pitch: u32 = 0x746d7074;
+pub const kAudioUnitSubType_AUFilter: u32 = 0x66696c74;
+pub const kAudioUnitSubType_NetSend: u32 = 0x6e736e64;
+pub const kAudioUnitSubType_RogerBeep: u32 = 0x726f6772;
+pub const kAudioUnitSubType_MultiChannelMixer: u32 = 0x6d636d78;
+pub const kAudioUnitSubType_MatrixMixer: u32 = 0x6d786d78;
+pub const kAudioUnitSubType_StereoMixer: u32 = 0x736d7872;
+pub const kAudioUnitSubType_3DMixer: u32 = 0x33646d78;
+pub const kAudioUnitSubType_ScheduledSoundPlayer: u32 = 0x7373706c;
+pub const kAudioUnitSubType_AudioFilePlayer: u32 = 0x6166706c;
+pub const kAudioUnitErr_InvalidProperty: OSStatus = -10879;
+pub const kAudioUnitErr_InvalidParameter: OSStatus = -10878;
+pub const kAudioUnitErr_InvalidElement: OSStatus = -10877;
+pub const kAudioUnitErr_NoConnection: OSStatus = -10876;
+pub const kAudioUnitErr_FailedInitialization: OSStatus = -10875;
+pub const kAudioUnitErr_TooManyFramesToProcess: OSStatus = -10874;
+pub const kAudioUnitErr_InvalidFile: OSStatus = -10871;
+pub const kAudioUnitErr_FormatNotSupported: OSStatus = -10868;
+pub const kAudioUnitErr_Uninitialized: OSStatus = -10867;
+pub const kAudioUnitErr_InvalidScope: OSStatus = -10866;
+pub const kAudioUnitErr_PropertyNotWritable: OSStatus = -10865;
+pub const kAudioUnitErr_CannotDoInCurrentContext: OSStatus = -10863;
+pub const kAudioUnitErr_InvalidPropertyValue: OSStatus = -10851;
+pub const kAudioUnitErr_PropertyNotInUse: OSStatus = -10850;
+pub const kAudioUnitErr_Initialized: OSStatus = -10849;
+pub const kAudioUnitErr_InvalidOfflineRender: OSStatus = -10848;
+pub const kAudioUnitErr_Unauthorized: OSStatus = -10847;
+
+// AudioUnitProperties.h
+
+pub const kAudioUnitScope_Global: u32 = 0;
+pub const kAudioUnitScope_Input: u32 = 1;
+pub const kAudioUnitScope_Output: u32 = 2;
+pub const kAudioUnitScope_Group: u32 = 3;
+pub const kAudioUnitScope_Part: u32 = 4;
+pub const kAudioUnitScope_Note: u32 = 5;
+pub const kAudioUnitScope_Layer: u32 = 6;
+pub const kAudioUnitScope_LayerItem: u32 = 7;
+pub const kAudioUnitProperty_MakeConnection: u32 = 1;
+pub const kAudioUnitProperty_SampleRate: u32 = 2;
+pub const kAudioUnitProperty_ParameterList: u32 = 3;
+pub const kAudioUnitProperty_ParameterInfo: u32 = 4;
+pub const kAudioUnitProperty_CPULoad: u32 = 6;
+pub const kAudioUnitProperty_StreamFormat: u32 = 8;
+pub const kAudioUnitProperty_ElementCount: u32 = 11;
+pub const kAudioUnitProperty_Latency: u32 = 12;
+pub const kAudioUnitProperty_MaximumFramesPerSlice: u32 = 14;
+pub const kAudioUnitProperty_TailTime: u32 = 20;
+pub const kAudioUnitProperty_BypassEffect: u32 = 21;
+pub const kAudioUnitProperty_LastRenderError: u32 = 22;
+pub const kAudioUnitProperty_SetRenderCallback: u32 = 23;
+pub const kAudioUnitProperty_ShouldAllocateBuffer: u32 = 51;
+pub const kAudioOutputUnitProperty_IsRunning: u32 = 2001;
+pub const kAudioOutputUnitProperty_SetInputCallback: u32 = 2005;
+
+// AudioUnitParameters.h
+
+pub const kReverbParam_FilterFrequency: u32 = 14;
+pub const kReverbParam_FilterBandwidth: u32 = 15;
+pub const kReverbParam_FilterGain: u32 = 16;
+pub const kReverbParam_FilterType: u32 = 17;
+pub const kReverbParam_FilterEnable: u32 = 18;
+pub const k3DMixerParam_Azimuth: u32 = 0;
+pub const k3DMixerParam_Elevation: u32 = 1;
+pub const k3DMixerParam_Distance: u32 = 2;
+pub const k3DMixerParam_Gain: u32 = 3;
+pub const k3DMixerParam_PlaybackRate: u32 = 4;
+pub const k3DMixerParam_BusEnable: u32 = 20;
+pub const k3DMixerParam_MinGainInDecibels: u32 = 21;
+pub const k3DMixerParam_MaxGainInDecibels: u32 = 22;
+pub const k3DMixerParam_DryWetReverbBlend: u32 = 23;
+pub const k3DMixerParam_GlobalReverbGainInDecibels: u32 = 24;
+pub const k3DMixerParam_OcclusionAttenuationInDecibels: u32 = 25;
+pub const k3DMixerParam_ObstructionAttenuationInDecibels: u32 = 26;
+pub const k3DMixerParam_PreAveragePower: u32 = 1000;
+pub const k3DMixerParam_PrePeakHoldLevel: u32 = 2000;
+pub const k3DMixerParam_PostAveragePower: u32 = 3000;
+pub const k3DMixerParam_PostPeakHoldLevel: u32 = 4000;
+pub const kMultiChannelMixerParam_Volume: u32 = 0;
+pub const kMultiChannelMixerParam_Enable: u32 = 1;
+pub const kMultiChannelMixerParam_Pan: u32 = 2;
+pub const kMultiChannelMixerParam_PreAveragePower: u32 = 1000;
+pub const kMultiChannelMixerParam_PrePeakHoldLevel: u32 = 2000;
+pub const kMultiChannelMixerParam_PostAveragePower: u32 = 3000;
+pub const kMultiChannelMixerParam_PostPeakHoldLevel: u32 = 4000;
+pub const kMatrixMixerParam_Volume: u32 = 0;
+pub const kMatrixMixerParam_Enable: u32 = 1;
+pub const kMatrixMixerParam_PreAveragePower: u32 = 1000;
+pub const kMatrixMixerParam_PrePeakHoldLevel: u32 = 2000;
+pub const kMatrixMixerParam_PostAveragePower: u32 = 3000;
+pub const kMatrixMixerParam_PostPeakHoldLevel: u32 = 4000;
+pub const kTimePitchParam_Rate: u32 = 0;
+pub const kTimePitchParam_Pitch: u32 = 1;
+pub const kTimePitchParam_EffectBlend: u32 = 2;
+pub const kBandpassParam_CenterFrequency: u32 = 0;
+pub const kBandpassParam_Bandwidth: u32 = 1;
+pub const kHipassParam_CutoffFrequency: u32 = 0;
+pub const kHipassParam_Resonance: u32 = 1;
+pub const kLowPassParam_CutoffFrequency: u32 = 0;
+pub const kLowPassParam_Resonance: u32 = 1;
+pub const kHighShelfParam_CutOffFrequency: u32 = 0;
+pub const kHighShelfParam_Gain: u32 = 1;
+pub const kAULowShelfParam_CutoffFrequency: u32 = 0;
+pub const kAULowShelfParam_Gain: u32 = 1;
+pub const kParametricEQParam_CenterFreq: u32 = 0;
+pub const kParametricEQParam_Q: u32 = 1;
+pub const kParametricEQParam_Gain: u32 = 2;
+pub const kLimiterParam_AttackTime: u32 = 0;
+pub const kLimiterParam_DecayTime: u32 = 1;
+pub const kLimiterParam_PreGain: u32 = 2;
+pub const kDynamicsProcessorParam_Threshold: u32 = 0;
+pub const kDynamicsProcessorParam_HeadRoom: u32 = 1;
+pub const kDynamicsProcessorParam_ExpansionRatio: u32 = 2;
+pub const kDynamicsProcessorParam_ExpansionThreshold: u32 = 3;
+pub const kDynamicsProcessorParam_AttackTime: u32 = 4;
+pub const kDynamicsProcessorParam_ReleaseTime: u32 = 5;
+pub const kDynamicsProcessorParam_OverallGain: u32 = 6;
+pub const kDynamicsProcessorParam_CompressionAmount: u32 = 1000;
+pub const kDynamicsProcessorParam_InputAmplitude: u32 = 2000;
+pub const kDynamicsProcessorParam_OutputAmplitude: u32 = 3000;
+pub const kDistortionParam_Delay: u32 = 0;
+pub const kDistortionParam_Decay: u32 = 1;
+pub const kDistortionParam_DelayMix: u32 = 2;
+pub const kDistortionParam_Decimation: u32 = 3;
+pub const kDistortionParam_Rounding: u32 = 4;
+pub const kDistortionParam_DecimationMix: u32 = 5;
+pub const kDistortionParam_LinearTerm: u32 = 6;
+pub const kDistortionParam_SquaredTerm: u32 = 7;
+pub const kDistortionParam_CubicTerm: u32 = 8;
+pub const kDistortionParam_PolynomialMix: u32 = 9;
+pub const kDistortionParam_RingModFreq1: u32 = 10;
+pub const kDistortionParam_RingModFreq2: u32 = 11;
+pub const kDistortionParam_RingModBalance: u32 = 12;
+pub const kDistortionParam_RingModMix: u32 = 13;
+pub const kDistortionParam_SoftClipGain: u32 = 14;
+pub const kDistortionParam_FinalMix: u32 = 15;
+pub const kDelayParam_WetDryMix: u32 = 0;
+pub const kDelayParam_DelayTime: u32 = 1;
+pub const kDelayParam_Feedback: u32 = 2;
+pub const kDelayParam_LopassCutoff: u32 = 3;
+pub const kSampleDelayParam_DelayFrames: u32 = 0;
+pub const kAUNBandEQParam_GlobalGain: u32 = 0;
+pub const kAUNBandEQParam_BypassBand: u32 = 1000;
+pub const kAUNBandEQParam_FilterType: u32 = 2000;
+pub const kAUNBandEQParam_Frequency: u32 = 3000;
+pub const kAUNBandEQParam_Gain: u32 = 4000;
+pub const kAUNBandEQParam_Bandwidth: u32 = 5000;
+pub const kGraphicEQParam_NumberOfBands: u32 = 10000;
+pub const kReverbParam_DryWetMix: u32 = 0;
+pub const kReverbParam_SmallLargeMix: u32 = 1;
+pub const kReverbParam_SmallSize: u32 = 2;
+pub const kReverbParam_LargeSize: u32 = 3;
+pub const kReverbParam_PreDelay: u32 = 4;
+pub const kReverbParam_LargeDelay: u32 = 5;
+pub const kReverbParam_SmallDensity: u32 = 6;
+pub const kReverbParam_LargeDensity: u32 = 7;
+pub const kReverbParam_LargeDelayRange: u32 = 8;
+pub const kReverbParam_SmallBrightness: u32 = 9;
+pub const kReverbParam_LargeBrightness: u32 = 10;
+pub const kReverbParam_SmallDelayRange: u32 = 11;
+pub const kReverbParam_ModulationRate: u32 = 12;
+pub const kReverbParam_ModulationDepth: u32 = 13;
+pub const kMultibandCompressorParam_Pregain: u32 = 0;
+pub const kMultibandCompressorParam_Postgain: u32 = 1;
+pub const kMultibandCompressorParam_Crossover1: u32 = 2;
+pub const kMultibandCompressorParam_Crossover2: u32 = 3;
+pub const kMultibandCompressorParam_Crossover3: u32 = 4;
+pub const kMultibandCompressorParam_Threshold1: u32 = 5;
+pub const kMultibandCompressorParam_Threshold2: u32 = 6;
+pub const kMultibandCompressorParam_Threshold3: u32 = 7;
+pub const kMultibandCompressorParam_Threshold4: u32 = 8;
+pub const kMultibandCompressorParam_Headroom1: u32 = 9;
+pub const kMultibandCompressorParam_Headroom2: u32 = 10;
+pub const kMultibandCompressorParam_Headroom3: u32 = 11;
+pub const kMultibandCompressorParam_Headroom4: u32 = 12;
+pub const kMultibandCompressorParam_AttackTime: u32 = 13;
+pub const kMultibandCompressorParam_ReleaseTime: u32 = 14;
+pub const kMultibandCompressorParam_EQ1: u32 = 15;
+pub const kMultibandCompressorParam_EQ2: u32 = 16;
+pub const kMultibandCompressorParam_EQ3: u32 = 17;
+pub const kMultibandCompressorParam_EQ4: u32 = 18;
+
+// AudioCodec.h
+
+pub const kAudioCodecUnspecifiedError: OSStatus = 0x77686174;
+pub const kAudioCodecUnknownPropertyError: OSStatus = 0x77686f3f;
+pub const kAudioCodecBadPropertySizeError: OSStatus = 0x2173697a;
+pub const kAudioCodecIllegalOperationError: OSStatus = 0x6e6f7065;
+pub const kAudioCodecUnsupportedFormatError: OSStatus = 0x21646174;
+pub const kAudioCodecStateError: OSStatus = 0x21737474;
+pub const kAudioCodecNotEnoughBufferSpaceError: OSStatus = 0x21627566;
+pub const kAudioCodecBadDataError: OSStatus = 0x62616461;
+
+// AudioFormat.h
+
+pub const kAudioFormatUnspecifiedError: OSStatus = 0x77686174;
+pub const kAudioFormatUnsupportedPropertyError: OSStatus = 0x70726f70;
+pub const kAudioFormatBadPropertySizeError: OSStatus = 0x2173697a;
+pub const kAudioFormatBadSpecifierSizeError: OSStatus = 0x21737063;
+pub const kAudioFormatUnsupportedDataFormatError: OSStatus = 0x666d743f;
+pub const kAudioFormatUnknownFormatError: OSStatus = 0x21666d74;
+
+// AudioServices.h
+
+pub const kAudioServicesSystemSoundUnspecifiedError: OSStatus = -1500;
+pub const kAudioServicesSystemSoundClientTimedOutError: OSStatus = -1501;
+
+// AudioSession.h
+
+pub const kAudioSessionProperty_CurrentHardwareSampleRate: u32 = 0x63687372;
+pub const kAudioSessionProperty_CurrentHardwareIOBufferDuration: u32 = 0x63686264;
+
+// AudioHardware.h
+
+pub const kAudioDevicePropertyBufferFrameSize: u32 = 0x6673697a;
+
+// CoreAudioBaseTypes.h
+
+pub const kAudio_UnimplementedError: OSStatus = -4;
+pub const kAudio_FileNotFoundError: OSStatus = -43;
+pub const kAudio_FilePermissionError: OSStatus = -54;
+pub const kAudio_TooManyFilesOpenError: OSStatus = -42;
+pub const kAudio_BadFilePathError: OSStatus = 0x21707468;
+pub const kAudio_ParamError: OSStatus = -50;
+pub const kAudio_MemFullError: OSStatus = -108;
+pub const kAudioFormatLinearPCM: u32 = 0x6c70636d;
+pub const kAudioFormatAC3: u32 = 0x61632d33;
+pub const kAudioFormat60958AC3: u32 = 0x63616333;
+pub const kAudioFormatAppleIMA4: u32 = 0x696d6134;
+pub const kAudioFormatMPEG4AAC: u32 = 0x61616320;
+pub const kAudioFormatMPEG4CELP: u32 = 0x63656c70;
+pub const kAudioFormatMPEG4HVXC: u32 = 0x68767863;
+pub const kAudioFormatMPEG4TwinVQ: u32 = 0x74777671;
+pub const kAudioFormatMACE3: u32 = 0x4d414333;
+pub const kAudioFormatMACE6: u32 = 0x4d414336;
+pub const kAudioFormatULaw: u32 = 0x756c6177;
+pub const kAudioFormatALaw: u32 = 0x616c6177;
+pub const kAudioFormatQDesign: u32 = 0x51444d43;
+pub const kAudioFormatQDesign2: u32 = 0x51444d32;
+pub const kAudioFormatQUALCOMM: u32 = 0x51636c70;
+pub const kAudioFormatMPEGLayer1: u32 = 0x2e6d7031;
+pub const kAudioFormatMPEGLayer2: u32 = 0x2e6d7032;
+pub const kAudioFormatMPEGLayer3: u32 = 0x2e6d7033;
+pub const kAudioFormatTimeCode: u32 = 0x74696d65;
+pub const kAudioFormatMIDIStream: u32 = 0x6d696469;
+pub const kAudioFormatParameterValueStream: u32 = 0x61707673;
+pub const kAudioFormatAppleLossless: u32 = 0x616c6163;
+pub const kAudioFormatMPEG4AAC_HE: u32 = 0x61616368;
+pub const kAudioFormatMPEG4AAC_LD: u32 = 0x6161636c;
+pub const kAudioFormatMPEG4AAC_ELD: u32 = 0x61616365;
+pub const kAudioFormatMPEG4AAC_ELD_SBR: u32 = 0x61616366;
+pub const kAudioFormatMPEG4AAC_ELD_V2: u32 = 0x61616367;
+pub const kAudioFormatMPEG4AAC_HE_V2: u32 = 0x61616370;
+pub const kAudioFormatMPEG4AAC_Spatial: u32 = 0x61616373;
+pub const kAudioFormatAMR: u32 = 0x73616d72;
+pub const kAudioFormatAMR_WB: u32 = 0x73617762;
+pub const kAudioFormatAudible: u32 = 0x41554442;
+pub const kAudioFormatiLBC: u32 = 0x696c6263;
+pub const kAudioFormatDVIIntelIMA: u32 = 0x6D730011;
+pub const kAudioFormatMicrosoftGSM: u32 = 0x6D730031;
+pub const kAudioFormatAES3: u32 = 0x61657333;
+pub const kAudioFormatFlagIsFloat: u32 = 1 << 0;
+pub const kAudioFormatFlagIsBigEndian: u32 = 1 << 1;
+pub const kAudioFormatFlagIsSignedInteger: u32 = 1 << 2;
+pub const kAudioFormatFlagIsPacked: u32 = 1 << 3;
+pub const kAudioFormatFlagIsAlignedHigh: u32 = 1 << 4;
+pub const kAudioFormatFlagIsNonInterleaved: u32 = 1 << 5;
+pub const kAudioFormatFlagIsNonMixable: u32 = 1 << 6;
+pub const kLinearPCMFormatFlagsSampleFractionShift: u32 = 7;
+pub const kAppleLosslessFormatFlag_16BitSourceData: u32 = 1;
+pub const kAppleLosslessFormatFlag_20BitSourceData: u32 = 2;
+pub const kAppleLosslessFormatFlag_24BitSourceData: u32 = 3;
+pub const kAppleLosslessFormatFlag_32BitSourceData: u32 = 4;
+pub const kLinearPCMFormatFlagsSampleFractionMask: u32 =
+    0x3F << kLinearPCMFormatFlagsSampleFractionShift;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+
+    // The sizes of the structs as laid out by the Apple SDK headers on 64-bit targets.
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_struct_sizes_match_the_sdk() {
+        assert_eq!(size_of::<AudioBuffer>(), 16);
+        assert_eq!(size_of::<AudioBufferList>(), 24);
+        assert_eq!(size_of::<AudioStreamBasicDescription>(), 40);
+        assert_eq!(size_of::<SMPTETime>(), 24);
+        assert_eq!(size_of::<AudioTimeStamp>(), 64);
+        assert_eq!(size_of::<AURenderCallbackStruct>(), 16);
+        assert_eq!(size_of::<AudioUnitConnection>(), 16);
+        assert_eq!(size_of::<AudioUnitParameterInfo>(), 104);
+    }
+}